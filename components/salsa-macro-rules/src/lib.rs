@@ -17,6 +17,7 @@ mod maybe_backdate;
 mod maybe_clone;
 mod maybe_default;
 mod setup_accumulator_impl;
+mod setup_ingredient_cache;
 mod setup_input_struct;
 mod setup_interned_struct;
 mod setup_method_body;
@@ -19,6 +19,21 @@ macro_rules! maybe_backdate {
         );
     };
 
+    (
+        ($maybe_clone:ident, (backdate_with $eq:path), $maybe_default:ident),
+        $field_ty:ty,
+        $old_field_place:expr,
+        $new_field_place:expr,
+        $revision_place:expr,
+        $current_revision:expr,
+        $zalsa:ident,
+     ) => {
+        if !$eq(&$old_field_place, &$new_field_place) {
+            $revision_place = $current_revision;
+        }
+        $old_field_place = $new_field_place;
+    };
+
     (
         ($maybe_clone:ident, backdate, $maybe_default:ident),
         $field_ty:ty,
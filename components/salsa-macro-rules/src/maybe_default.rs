@@ -4,7 +4,7 @@
 #[macro_export]
 macro_rules! maybe_default {
     (
-        ($maybe_clone:ident, $maybe_backdate:ident, default),
+        ($maybe_clone:ident, $maybe_backdate:tt, default),
         $field_ty:ty,
         $field_ref_expr:expr,
     ) => {
@@ -12,7 +12,7 @@ macro_rules! maybe_default {
     };
 
     (
-        ($maybe_clone:ident, $maybe_backdate:ident, required),
+        ($maybe_clone:ident, $maybe_backdate:tt, required),
         $field_ty:ty,
         $field_ref_expr:expr,
     ) => {
@@ -22,11 +22,11 @@ macro_rules! maybe_default {
 
 #[macro_export]
 macro_rules! maybe_default_tt {
-    (($maybe_clone:ident, $maybe_backdate:ident, default) => $($t:tt)*) => {
+    (($maybe_clone:ident, $maybe_backdate:tt, default) => $($t:tt)*) => {
         $($t)*
     };
 
-    (($maybe_clone:ident, $maybe_backdate:ident, required) => $($t:tt)*) => {
+    (($maybe_clone:ident, $maybe_backdate:tt, required) => $($t:tt)*) => {
 
     };
 }
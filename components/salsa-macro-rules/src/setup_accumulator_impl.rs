@@ -31,6 +31,11 @@ macro_rules! setup_accumulator_impl {
             impl $zalsa::Accumulator for $Struct {
                 const DEBUG_NAME: &'static str = stringify!($Struct);
 
+                fn values_eq(old: &[Self], new: &[Self]) -> bool {
+                    use $zalsa::AccumulatorFallback as _;
+                    $zalsa::AccumulatorDispatch::<$Struct>::values_eq(old, new)
+                }
+
                 fn accumulate<Db>(self, db: &Db)
                 where
                     Db: ?Sized + $zalsa::Database,
@@ -0,0 +1,23 @@
+/// Declares the `static` used to cache the ingredient of a salsa item.
+///
+/// Generic items get one ingredient per monomorphization, and a `static`
+/// cannot name the type parameters of the item it is declared in,
+/// so they use a cache keyed by the ingredient type instead.
+#[macro_export]
+macro_rules! setup_ingredient_cache {
+    (
+        type_params: [],
+        zalsa: $zalsa:ident,
+        $CACHE:ident: $Ingredient:ty
+    ) => {
+        static $CACHE: $zalsa::IngredientCache<$Ingredient> = $zalsa::IngredientCache::new();
+    };
+
+    (
+        type_params: [$($T:ident),+],
+        zalsa: $zalsa:ident,
+        $CACHE:ident: $Ingredient:ty
+    ) => {
+        static $CACHE: $zalsa::GenericIngredientCache = $zalsa::GenericIngredientCache::new();
+    };
+}
@@ -11,6 +11,12 @@ macro_rules! setup_input_struct {
         // Name of the struct
         Struct: $Struct:ident,
 
+        // Type parameters of the struct; each monomorphization gets its own ingredients.
+        type_params: [$($T:ident),*],
+
+        // Predicates that hold for the type parameters (including their inline bounds and `'static`).
+        where_clause: [$($where_clause:tt)*],
+
         // Name user gave for `new`
         new_fn: $new_fn:ident,
 
@@ -47,6 +53,9 @@ macro_rules! setup_input_struct {
         // If true, generate a debug impl.
         generate_debug_impl: $generate_debug_impl:tt,
 
+        // If true, generate `Serialize` and `Deserialize` impls.
+        generate_serde_impls: $generate_serde_impls:tt,
+
         // Annoyingly macro-rules hygiene does not extend to items defined in the macro.
         // We have the procedural macro generate names for those items that are
         // not used elsewhere in the user's code.
@@ -54,28 +63,32 @@ macro_rules! setup_input_struct {
             $zalsa:ident,
             $zalsa_struct:ident,
             $Configuration:ident,
+            $HasConfiguration:ident,
             $Builder:ident,
             $CACHE:ident,
             $Db:ident,
         ]
     ) => {
         $(#[$attr])*
-        #[derive(Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Hash)]
-        $vis struct $Struct(salsa::Id);
+        $vis struct $Struct<$($T),*>(salsa::Id, std::marker::PhantomData<fn() -> ($($T,)*)>);
 
         const _: () = {
             use salsa::plumbing as $zalsa;
             use $zalsa::input as $zalsa_struct;
 
-            struct $Configuration;
+            struct $Configuration<$($T),*>(std::marker::PhantomData<fn() -> ($($T,)*)>);
 
-            impl $zalsa_struct::Configuration for $Configuration {
+            impl<$($T),*> $zalsa_struct::Configuration for $Configuration<$($T),*>
+            where
+                $($where_clause)*
+            {
                 const DEBUG_NAME: &'static str = stringify!($Struct);
                 const FIELD_DEBUG_NAMES: &'static [&'static str] = &[$(stringify!($field_id)),*];
                 const IS_SINGLETON: bool = $is_singleton;
+                const IS_RECORDED: bool = $generate_serde_impls;
 
                 /// The input struct (which wraps an `Id`)
-                type Struct = $Struct;
+                type Struct = $Struct<$($T),*>;
 
                 /// A (possibly empty) tuple of the fields for this struct.
                 type Fields = ($($field_ty,)*);
@@ -84,18 +97,24 @@ macro_rules! setup_input_struct {
                 type Stamps = $zalsa::Array<$zalsa::Stamp, $N>;
             }
 
-            impl $Configuration {
+            impl<$($T),*> $Configuration<$($T),*>
+            where
+                $($where_clause)*
+            {
                 pub fn ingredient(db: &dyn $zalsa::Database) -> &$zalsa_struct::IngredientImpl<Self> {
-                    static CACHE: $zalsa::IngredientCache<$zalsa_struct::IngredientImpl<$Configuration>> =
-                        $zalsa::IngredientCache::new();
+                    $zalsa::setup_ingredient_cache! {
+                        type_params: [$($T),*],
+                        zalsa: $zalsa,
+                        CACHE: $zalsa_struct::IngredientImpl<$Configuration>
+                    }
                     CACHE.get_or_create(db, || {
-                        db.zalsa().add_or_lookup_jar_by_type(&<$zalsa_struct::JarImpl<$Configuration>>::default())
+                        db.zalsa().add_or_lookup_jar_by_type(&<$zalsa_struct::JarImpl<Self>>::default())
                     })
                 }
 
                 pub fn ingredient_mut(db: &mut dyn $zalsa::Database) -> (&mut $zalsa_struct::IngredientImpl<Self>, &mut $zalsa::Runtime) {
                     let zalsa_mut = db.zalsa_mut();
-                    let index = zalsa_mut.add_or_lookup_jar_by_type(&<$zalsa_struct::JarImpl<$Configuration>>::default());
+                    let index = zalsa_mut.add_or_lookup_jar_by_type(&<$zalsa_struct::JarImpl<Self>>::default());
                     let current_revision = zalsa_mut.current_revision();
                     let (ingredient, runtime) = zalsa_mut.lookup_ingredient_mut(index);
                     let ingredient = ingredient.assert_type_mut::<$zalsa_struct::IngredientImpl<Self>>();
@@ -103,30 +122,186 @@ macro_rules! setup_input_struct {
                 }
             }
 
-            impl $zalsa::FromId for $Struct {
+            // Lets code that is repeated per field name the configuration
+            // without naming the type parameters.
+            trait $HasConfiguration {
+                type Configuration;
+            }
+
+            impl<$($T),*> $HasConfiguration for $Struct<$($T),*> {
+                type Configuration = $Configuration<$($T),*>;
+            }
+
+            impl<$($T),*> Copy for $Struct<$($T),*> {}
+
+            impl<$($T),*> Clone for $Struct<$($T),*> {
+                fn clone(&self) -> Self {
+                    *self
+                }
+            }
+
+            impl<$($T),*> PartialEq for $Struct<$($T),*> {
+                fn eq(&self, other: &Self) -> bool {
+                    self.0 == other.0
+                }
+            }
+
+            impl<$($T),*> Eq for $Struct<$($T),*> {}
+
+            impl<$($T),*> PartialOrd for $Struct<$($T),*> {
+                fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            impl<$($T),*> Ord for $Struct<$($T),*> {
+                fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                    self.0.cmp(&other.0)
+                }
+            }
+
+            impl<$($T),*> std::hash::Hash for $Struct<$($T),*> {
+                fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                    self.0.hash(state);
+                }
+            }
+
+            impl<$($T),*> $zalsa::FromId for $Struct<$($T),*>
+            where
+                $($where_clause)*
+            {
                 fn from_id(id: salsa::Id) -> Self {
-                    Self(id)
+                    Self(id, std::marker::PhantomData)
                 }
             }
 
-            impl $zalsa::AsId for $Struct {
+            impl<$($T),*> $zalsa::AsId for $Struct<$($T),*> {
                 fn as_id(&self) -> salsa::Id {
                     self.0
                 }
             }
 
             $zalsa::macro_if! { $generate_debug_impl =>
-                impl std::fmt::Debug for $Struct {
+                impl<$($T),*> std::fmt::Debug for $Struct<$($T),*>
+                where
+                    $($where_clause)*
+                {
                     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                         Self::default_debug_fmt(*self, f)
                     }
                 }
             }
 
-            impl $zalsa::SalsaStructInDb for $Struct {
+            $zalsa::macro_if! { $generate_serde_impls =>
+                impl<$($T),*> $zalsa::serde::Serialize for $Struct<$($T),*>
+                where
+                    $($where_clause)*
+                    $($field_ty: $zalsa::serde::Serialize,)*
+                {
+                    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                    where
+                        S: $zalsa::serde::Serializer,
+                    {
+                        if $zalsa::is_recording_ids() {
+                            // Record the id, so that the replay refers to the input created in place of this one.
+                            return serializer.serialize_u32($zalsa::AsId::as_id(self).as_u32());
+                        }
+                        $zalsa::with_attached_database(|db| {
+                            let ingredient = $Configuration::<$($T),*>::ingredient(db);
+                            // Read each field as the getters do, so that serializing
+                            // within a tracked function records a dependency on them.
+                            $(ingredient.field(db, *self, $field_index);)*
+                            let fields = ingredient.leak_fields(db, *self);
+                            $zalsa::SerializeFields::serialize_fields(
+                                fields,
+                                serializer,
+                                stringify!($Struct),
+                                &[$(stringify!($field_id)),*],
+                            )
+                        }).unwrap_or_else(|| Err($zalsa::serialize_without_database(stringify!($Struct))))
+                    }
+                }
+
+                impl<$($T),*> $zalsa::ReplayInput for $Struct<$($T),*>
+                where
+                    $($where_clause)*
+                    $($field_ty: $zalsa::serde::Serialize + $zalsa::serde::de::DeserializeOwned,)*
+                {
+                    const RECORDED_NAME: &'static str = concat!(module_path!(), "::", stringify!($Struct));
+                    const FIELD_COUNT: usize = $N;
+
+                    fn replay_new(
+                        db: &dyn $zalsa::Database,
+                        fields: &[String],
+                        durabilities: &[salsa::Durability],
+                    ) -> Result<salsa::Id, salsa::ReplayError> {
+                        let builder = builder::new_builder::<$($T),*>($($zalsa::from_json::<$field_ty>(&fields[$field_index])?),*);
+                        $(let builder = builder.$field_durability_id(durabilities[$field_index]);)*
+                        Ok($zalsa::AsId::as_id(&builder.new(db)))
+                    }
+
+                    fn replay_set(
+                        db: &mut dyn $zalsa::Database,
+                        id: salsa::Id,
+                        field_index: usize,
+                        value: &str,
+                        durability: Option<salsa::Durability>,
+                    ) -> Result<(), salsa::ReplayError> {
+                        let this: Self = $zalsa::FromId::from_id(id);
+                        match field_index {
+                            $(
+                                $field_index => {
+                                    let value = $zalsa::from_json::<$field_ty>(value)?;
+                                    let setter = this.$field_setter_id(db);
+                                    let setter = match durability {
+                                        Some(durability) => salsa::Setter::with_durability(setter, durability),
+                                        None => setter,
+                                    };
+                                    salsa::Setter::to(setter, value);
+                                }
+                            )*
+                            _ => return Err(salsa::ReplayError::UnknownField {
+                                input: <Self as $zalsa::ReplayInput>::RECORDED_NAME.to_string(),
+                                field_index,
+                            }),
+                        }
+                        Ok(())
+                    }
+                }
+
+                impl<'de, $($T),*> $zalsa::serde::Deserialize<'de> for $Struct<$($T),*>
+                where
+                    $($where_clause)*
+                    $($field_ty: $zalsa::serde::Deserialize<'de>,)*
+                {
+                    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                    where
+                        D: $zalsa::serde::Deserializer<'de>,
+                    {
+                        if $zalsa::is_recording_ids() {
+                            let recorded_id = <u32 as $zalsa::serde::Deserialize>::deserialize(deserializer)?;
+                            return $zalsa::replayed_id(recorded_id).map($zalsa::FromId::from_id);
+                        }
+                        let ($($field_id,)*) = <($($field_ty,)*) as $zalsa::DeserializeFields<'de>>::deserialize_fields(
+                            deserializer,
+                            stringify!($Struct),
+                            &[$(stringify!($field_id)),*],
+                        )?;
+                        $zalsa::with_attached_database(|db| {
+                            builder::new_builder::<$($T),*>($($field_id),*).new(db)
+                        })
+                        .ok_or_else(|| $zalsa::deserialize_without_database(stringify!($Struct)))
+                    }
+                }
+            }
+
+            impl<$($T),*> $zalsa::SalsaStructInDb for $Struct<$($T),*> {
             }
 
-            impl $Struct {
+            impl<$($T),*> $Struct<$($T),*>
+            where
+                $($where_clause)*
+            {
                 #[inline]
                 pub fn $new_fn<$Db>(db: &$Db, $($required_field_id: $required_field_ty),*) -> Self
                 where
@@ -136,6 +311,15 @@ macro_rules! setup_input_struct {
                     Self::builder($($required_field_id,)*).new(db)
                 }
 
+                /// Iterates over all the instances of this input created so far.
+                pub fn all<$Db>(db: &$Db) -> impl Iterator<Item = Self> + '_
+                where
+                    // FIXME(rust-lang/rust#65991): The `db` argument *should* have the type `dyn Database`
+                    $Db: ?Sized + salsa::Database,
+                {
+                    $Configuration::<$($T),*>::ingredient(db.as_dyn_database()).all(db.as_dyn_database())
+                }
+
                 pub fn builder($($required_field_id: $required_field_ty),*) -> <Self as $zalsa_struct::HasBuilder>::Builder
                 {
                     builder::new_builder($($zalsa::maybe_default!($field_option, $field_ty, $field_id,)),*)
@@ -147,7 +331,7 @@ macro_rules! setup_input_struct {
                         // FIXME(rust-lang/rust#65991): The `db` argument *should* have the type `dyn Database`
                         $Db: ?Sized + $zalsa::Database,
                     {
-                        let fields = $Configuration::ingredient(db.as_dyn_database()).field(
+                        let fields = <<Self as $HasConfiguration>::Configuration>::ingredient(db.as_dyn_database()).field(
                             db.as_dyn_database(),
                             self,
                             $field_index,
@@ -167,14 +351,24 @@ macro_rules! setup_input_struct {
                         // FIXME(rust-lang/rust#65991): The `db` argument *should* have the type `dyn Database`
                         $Db: ?Sized + $zalsa::Database,
                     {
-                        let (ingredient, revision) = $Configuration::ingredient_mut(db.as_dyn_database_mut());
-                        $zalsa::input::SetterImpl::new(
+                        let (ingredient, revision) = <<Self as $HasConfiguration>::Configuration>::ingredient_mut(db.as_dyn_database_mut());
+                        let setter = $zalsa::input::SetterImpl::new(
                             revision,
                             self,
                             $field_index,
                             ingredient,
                             |fields, f| std::mem::replace(&mut fields.$field_index, f),
-                        )
+                        );
+                        $zalsa::macro_if! {
+                            if $generate_serde_impls {
+                                setter.recorded_with(
+                                    concat!(module_path!(), "::", stringify!($Struct)),
+                                    $zalsa::to_json::<$field_ty>,
+                                )
+                            } else {
+                                setter
+                            }
+                        }
                     }
                 )*
 
@@ -184,7 +378,7 @@ macro_rules! setup_input_struct {
                         // FIXME(rust-lang/rust#65991): The `db` argument *should* have the type `dyn Database`
                         $Db: ?Sized + salsa::Database,
                     {
-                        $Configuration::ingredient(db.as_dyn_database()).get_singleton_input()
+                        $Configuration::<$($T),*>::ingredient(db.as_dyn_database()).get_singleton_input()
                     }
 
                     #[track_caller]
@@ -200,7 +394,7 @@ macro_rules! setup_input_struct {
                 /// Default debug formatting for this struct (may be useful if you define your own `Debug` impl)
                 pub fn default_debug_fmt(this: Self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                     $zalsa::with_attached_database(|db| {
-                        let fields = $Configuration::ingredient(db).leak_fields(db, this);
+                        let fields = $Configuration::<$($T),*>::ingredient(db).leak_fields(db, this);
                         let mut f = f.debug_struct(stringify!($Struct));
                         let f = f.field("[salsa id]", &$zalsa::AsId::as_id(&this));
                         $(
@@ -209,30 +403,57 @@ macro_rules! setup_input_struct {
                         f.finish()
                     }).unwrap_or_else(|| {
                         f.debug_struct(stringify!($Struct))
-                            .field("[salsa id]", &this.0)
+                            .field("[salsa id]", &$zalsa::AsId::as_id(&this))
                             .finish()
                     })
                 }
             }
 
-            impl $zalsa_struct::HasBuilder for $Struct {
-                type Builder = builder::$Builder;
+            impl<$($T),*> $zalsa_struct::HasBuilder for $Struct<$($T),*>
+            where
+                $($where_clause)*
+            {
+                type Builder = builder::$Builder<$($T),*>;
             }
 
             // Implement `new` here instead of inside the builder module
             // because $Configuration can't be named in `builder`.
-            impl builder::$Builder {
+            impl<$($T),*> builder::$Builder<$($T),*>
+            where
+                $($where_clause)*
+            {
                 /// Creates the new input with the set values.
                 #[must_use]
-                pub fn new<$Db>(self, db: &$Db) -> $Struct
+                pub fn new<$Db>(self, db: &$Db) -> $Struct<$($T),*>
                 where
                     // FIXME(rust-lang/rust#65991): The `db` argument *should* have the type `dyn Database`
                     $Db: ?Sized + salsa::Database
                 {
                     let current_revision = $zalsa::current_revision(db);
-                    let ingredient = $Configuration::ingredient(db.as_dyn_database());
+                    let ingredient = $Configuration::<$($T),*>::ingredient(db.as_dyn_database());
                     let (fields, stamps) = builder::builder_into_inner(self, current_revision);
-                    ingredient.new_input(db.as_dyn_database(), fields, stamps)
+                    $zalsa::macro_if! {
+                        if $generate_serde_impls {
+                            // Serialize the fields before they are moved into the input.
+                            let recorded_fields = $zalsa::is_recording(db.as_dyn_database()).then(|| {
+                                $zalsa::attach(db, || Ok(vec![$($zalsa::to_json(&fields.$field_index)?),*]))
+                            });
+                            let durabilities: Vec<salsa::Durability> = stamps.iter().map(|stamp| stamp.durability).collect();
+                            let input = ingredient.new_input(db.as_dyn_database(), fields, stamps);
+                            if let Some(recorded_fields) = recorded_fields {
+                                $zalsa::record_new_input(
+                                    db.as_dyn_database(),
+                                    concat!(module_path!(), "::", stringify!($Struct)),
+                                    $zalsa::AsId::as_id(&input),
+                                    recorded_fields,
+                                    durabilities.into_iter(),
+                                );
+                            }
+                            input
+                        } else {
+                            ingredient.new_input(db.as_dyn_database(), fields, stamps)
+                        }
+                    }
                 }
             }
 
@@ -244,14 +465,21 @@ macro_rules! setup_input_struct {
 
                 // These are standalone functions instead of methods on `Builder` to prevent
                 // that the enclosing module can call them.
-                pub(super) fn new_builder($($field_id: $field_ty),*) -> $Builder {
+                pub(super) fn new_builder<$($T),*>($($field_id: $field_ty),*) -> $Builder<$($T),*>
+                where
+                    $($where_clause)*
+                {
                     $Builder {
                         fields: ($($field_id,)*),
                         durabilities: [salsa::Durability::default(); $N],
+                        phantom: std::marker::PhantomData,
                     }
                 }
 
-                pub(super) fn builder_into_inner(builder: $Builder, revision: $zalsa::Revision) -> (($($field_ty,)*), $zalsa::Array<$zalsa::Stamp, $N>) {
+                pub(super) fn builder_into_inner<$($T),*>(builder: $Builder<$($T),*>, revision: $zalsa::Revision) -> (($($field_ty,)*), $zalsa::Array<$zalsa::Stamp, $N>)
+                where
+                    $($where_clause)*
+                {
                     let stamps = $zalsa::Array::new([
                         $($zalsa::stamp(revision, builder.durabilities[$field_index])),*
                     ]);
@@ -260,15 +488,23 @@ macro_rules! setup_input_struct {
                 }
 
                 #[must_use]
-                pub struct $Builder {
+                pub struct $Builder<$($T),*>
+                where
+                    $($where_clause)*
+                {
                     /// The field values.
                     fields: ($($field_ty,)*),
 
                     /// The durabilities per field.
                     durabilities: [salsa::Durability; $N],
+
+                    phantom: std::marker::PhantomData<fn() -> ($($T,)*)>,
                 }
 
-                impl $Builder {
+                impl<$($T),*> $Builder<$($T),*>
+                where
+                    $($where_clause)*
+                {
                     /// Sets the durability of all fields.
                     ///
                     /// Overrides any previously set durabilities.
@@ -11,6 +11,12 @@ macro_rules! setup_interned_struct {
         // Name of the struct
         Struct: $Struct:ident,
 
+        // Type parameters of the struct; each monomorphization gets its own ingredients.
+        type_params: [$($T:ident),*],
+
+        // Predicates that hold for the type parameters (including their inline bounds and `'static`).
+        where_clause: [$($where_clause:tt)*],
+
         // Name of the `'db` lifetime that the user gave
         db_lt: $db_lt:lifetime,
 
@@ -38,6 +44,9 @@ macro_rules! setup_interned_struct {
         // If true, generate a debug impl.
         generate_debug_impl: $generate_debug_impl:tt,
 
+        // If true, generate `Serialize` and `Deserialize` impls.
+        generate_serde_impls: $generate_serde_impls:tt,
+
         // Annoyingly macro-rules hygiene does not extend to items defined in the macro.
         // We have the procedural macro generate names for those items that are
         // not used elsewhere in the user's code.
@@ -45,27 +54,30 @@ macro_rules! setup_interned_struct {
             $zalsa:ident,
             $zalsa_struct:ident,
             $Configuration:ident,
+            $HasConfiguration:ident,
             $CACHE:ident,
             $Db:ident,
         ]
     ) => {
         $(#[$attr])*
-        #[derive(Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Hash)]
-        $vis struct $Struct<$db_lt>(
+        $vis struct $Struct<$db_lt, $($T),*>(
             salsa::Id,
-            std::marker::PhantomData < & $db_lt salsa::plumbing::interned::Value < $Struct<'static> > >
+            std::marker::PhantomData<(&$db_lt (), fn() -> ($($T,)*))>
         );
 
         const _: () = {
             use salsa::plumbing as $zalsa;
             use $zalsa::interned as $zalsa_struct;
 
-            type $Configuration = $Struct<'static>;
+            type $Configuration<$($T),*> = $Struct<'static, $($T),*>;
 
-            impl $zalsa_struct::Configuration for $Configuration {
+            impl<$($T),*> $zalsa_struct::Configuration for $Configuration<$($T),*>
+            where
+                $($where_clause)*
+            {
                 const DEBUG_NAME: &'static str = stringify!($Struct);
                 type Data<$db_lt> = ($($field_ty,)*);
-                type Struct<$db_lt> = $Struct<$db_lt>;
+                type Struct<$db_lt> = $Struct<$db_lt, $($T),*>;
                 fn struct_from_id<'db>(id: salsa::Id) -> Self::Struct<'db> {
                     $Struct(id, std::marker::PhantomData)
                 }
@@ -74,47 +86,154 @@ macro_rules! setup_interned_struct {
                 }
             }
 
-            impl $Configuration {
+            impl<$($T),*> $Configuration<$($T),*>
+            where
+                $($where_clause)*
+            {
                 pub fn ingredient<Db>(db: &Db) -> &$zalsa_struct::IngredientImpl<Self>
                 where
                     Db: ?Sized + $zalsa::Database,
                 {
-                    static CACHE: $zalsa::IngredientCache<$zalsa_struct::IngredientImpl<$Configuration>> =
-                        $zalsa::IngredientCache::new();
+                    $zalsa::setup_ingredient_cache! {
+                        type_params: [$($T),*],
+                        zalsa: $zalsa,
+                        CACHE: $zalsa_struct::IngredientImpl<$Configuration>
+                    }
                     CACHE.get_or_create(db.as_dyn_database(), || {
-                        db.zalsa().add_or_lookup_jar_by_type(&<$zalsa_struct::JarImpl<$Configuration>>::default())
+                        db.zalsa().add_or_lookup_jar_by_type(&<$zalsa_struct::JarImpl<Self>>::default())
                     })
                 }
             }
 
-            impl $zalsa::AsId for $Struct<'_> {
+            // Lets code that is repeated per field name the configuration
+            // without naming the type parameters.
+            trait $HasConfiguration {
+                type Configuration;
+            }
+
+            impl<$($T),*> $HasConfiguration for $Struct<'_, $($T),*> {
+                type Configuration = $Configuration<$($T),*>;
+            }
+
+            impl<$($T),*> Copy for $Struct<'_, $($T),*> {}
+
+            impl<$($T),*> Clone for $Struct<'_, $($T),*> {
+                fn clone(&self) -> Self {
+                    *self
+                }
+            }
+
+            impl<$($T),*> PartialEq for $Struct<'_, $($T),*> {
+                fn eq(&self, other: &Self) -> bool {
+                    self.0 == other.0
+                }
+            }
+
+            impl<$($T),*> Eq for $Struct<'_, $($T),*> {}
+
+            impl<$($T),*> PartialOrd for $Struct<'_, $($T),*> {
+                fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            impl<$($T),*> Ord for $Struct<'_, $($T),*> {
+                fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                    self.0.cmp(&other.0)
+                }
+            }
+
+            impl<$($T),*> std::hash::Hash for $Struct<'_, $($T),*> {
+                fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                    self.0.hash(state);
+                }
+            }
+
+            impl<$($T),*> $zalsa::AsId for $Struct<'_, $($T),*> {
                 fn as_id(&self) -> salsa::Id {
                     self.0
                 }
             }
 
-            impl $zalsa::FromId for $Struct<'_> {
+            impl<$($T),*> $zalsa::FromId for $Struct<'_, $($T),*>
+            where
+                $($where_clause)*
+            {
                 fn from_id(id: salsa::Id) -> Self {
                     Self(id, std::marker::PhantomData)
                 }
             }
 
-            unsafe impl Send for $Struct<'_> {}
+            unsafe impl<$($T),*> Send for $Struct<'_, $($T),*> {}
 
-            unsafe impl Sync for $Struct<'_> {}
+            unsafe impl<$($T),*> Sync for $Struct<'_, $($T),*> {}
 
             $zalsa::macro_if! { $generate_debug_impl =>
-                impl std::fmt::Debug for $Struct<'_> {
+                impl<$($T),*> std::fmt::Debug for $Struct<'_, $($T),*>
+                where
+                    $($where_clause)*
+                {
                     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                         Self::default_debug_fmt(*self, f)
                     }
                 }
             }
 
-            impl $zalsa::SalsaStructInDb for $Struct<'_> {
+            $zalsa::macro_if! { $generate_serde_impls =>
+                impl<$db_lt, $($T),*> $zalsa::serde::Serialize for $Struct<$db_lt, $($T),*>
+                where
+                    $($where_clause)*
+                    $($field_ty: $zalsa::serde::Serialize,)*
+                {
+                    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                    where
+                        S: $zalsa::serde::Serializer,
+                    {
+                        $zalsa::with_attached_database(|db| {
+                            // SAFETY: the fields are only borrowed for the duration of this call,
+                            // so we can give the database the lifetime of `Self` to type them.
+                            let db: &$db_lt dyn $zalsa::Database = unsafe { std::mem::transmute(db) };
+                            let fields = $Configuration::<$($T),*>::ingredient(db).fields(db, *self);
+                            $zalsa::SerializeFields::serialize_fields(
+                                fields,
+                                serializer,
+                                stringify!($Struct),
+                                &[$(stringify!($field_id)),*],
+                            )
+                        }).unwrap_or_else(|| Err($zalsa::serialize_without_database(stringify!($Struct))))
+                    }
+                }
+
+                impl<'de, $db_lt, $($T),*> $zalsa::serde::Deserialize<'de> for $Struct<$db_lt, $($T),*>
+                where
+                    $($where_clause)*
+                    $($field_ty: $zalsa::serde::Deserialize<'de>,)*
+                {
+                    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                    where
+                        D: $zalsa::serde::Deserializer<'de>,
+                    {
+                        let fields = <($($field_ty,)*) as $zalsa::DeserializeFields<'de>>::deserialize_fields(
+                            deserializer,
+                            stringify!($Struct),
+                            &[$(stringify!($field_id)),*],
+                        )?;
+                        $zalsa::with_attached_database(|db| {
+                            // SAFETY: the database is only used within this call, and the interned
+                            // struct is only an id, like those created by `FromId`.
+                            let db: &$db_lt dyn $zalsa::Database = unsafe { std::mem::transmute(db) };
+                            let id = $Configuration::<$($T),*>::ingredient(db).intern_id(db, fields);
+                            $zalsa::FromId::from_id(id)
+                        })
+                        .ok_or_else(|| $zalsa::deserialize_without_database(stringify!($Struct)))
+                    }
+                }
+            }
+
+            impl<$($T),*> $zalsa::SalsaStructInDb for $Struct<'_, $($T),*> {
             }
 
-            unsafe impl $zalsa::Update for $Struct<'_> {
+            unsafe impl<$($T),*> $zalsa::Update for $Struct<'_, $($T),*> {
                 unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
                     if unsafe { *old_pointer } != new_value {
                         unsafe { *old_pointer = new_value };
@@ -125,14 +244,26 @@ macro_rules! setup_interned_struct {
                 }
             }
 
-            impl<$db_lt> $Struct<$db_lt> {
+            impl<$db_lt, $($T),*> $Struct<$db_lt, $($T),*>
+            where
+                $($where_clause)*
+            {
                 pub fn $new_fn<$Db>(db: &$db_lt $Db, $($field_id: $field_ty),*) -> Self
                 where
                     // FIXME(rust-lang/rust#65991): The `db` argument *should* have the type `dyn Database`
                     $Db: ?Sized + salsa::Database,
                 {
                     let current_revision = $zalsa::current_revision(db);
-                    $Configuration::ingredient(db).intern(db.as_dyn_database(), ($($field_id,)*))
+                    $Configuration::<$($T),*>::ingredient(db).intern(db.as_dyn_database(), ($($field_id,)*))
+                }
+
+                /// Iterates over all the instances of this struct interned so far.
+                pub fn all<$Db>(db: &$db_lt $Db) -> impl Iterator<Item = Self> + $db_lt
+                where
+                    // FIXME(rust-lang/rust#65991): The `db` argument *should* have the type `dyn Database`
+                    $Db: ?Sized + salsa::Database,
+                {
+                    $Configuration::<$($T),*>::ingredient(db).all(db.as_dyn_database())
                 }
 
                 $(
@@ -141,7 +272,7 @@ macro_rules! setup_interned_struct {
                         // FIXME(rust-lang/rust#65991): The `db` argument *should* have the type `dyn Database`
                         $Db: ?Sized + $zalsa::Database,
                     {
-                        let fields = $Configuration::ingredient(db).fields(db.as_dyn_database(), self);
+                        let fields = <<Self as $HasConfiguration>::Configuration>::ingredient(db).fields(db.as_dyn_database(), self);
                         $zalsa::maybe_clone!(
                             $field_option,
                             $field_ty,
@@ -153,7 +284,7 @@ macro_rules! setup_interned_struct {
                 /// Default debug formatting for this struct (may be useful if you define your own `Debug` impl)
                 pub fn default_debug_fmt(this: Self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                     $zalsa::with_attached_database(|db| {
-                        let fields = $Configuration::ingredient(db).fields(db.as_dyn_database(), this);
+                        let fields = $Configuration::<$($T),*>::ingredient(db).fields(db.as_dyn_database(), this);
                         let mut f = f.debug_struct(stringify!($Struct));
                         $(
                             let f = f.field(stringify!($field_id), &fields.$field_index);
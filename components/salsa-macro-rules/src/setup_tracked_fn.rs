@@ -14,6 +14,12 @@ macro_rules! setup_tracked_fn {
         // Name of the `'db` lifetime that the user gave; if they didn't, then defaults to `'db`
         db_lt: $db_lt:lifetime,
 
+        // Type parameters of the function; each monomorphization gets its own ingredients.
+        type_params: [$($T:ident),*],
+
+        // Predicates that hold for the type parameters (including their inline bounds and `'static`).
+        where_clause: [$($where_clause:tt)*],
+
         // Path to the database trait that the user's database parameter used
         Db: $Db:path,
 
@@ -46,15 +52,33 @@ macro_rules! setup_tracked_fn {
         // If true, don't backdate the value when the new value compares equal to the old value.
         no_eq: $no_eq:tt,
 
+        // Function deciding whether the new value equals the old one (`should_backdate_value` by default).
+        eq: $eq:path,
+
+        // If true, the values count against the memory budget, using their `EstimatedSize` impl.
+        estimated_size: $estimated_size:tt,
+
+        // If true, memos keep a fingerprint of their value, using its `Hash` impl.
+        fingerprint: $fingerprint:tt,
+
         // If true, the input needs an interner (because it has >1 argument).
         needs_interner: $needs_interner:tt,
 
         // LRU capacity (a literal, maybe 0)
         lru: $lru:tt,
 
+        // Eviction policy of the function, if given (replaces the LRU policy)
+        eviction: [$($eviction:expr)?],
+
         // True if we `return_ref` flag was given to the function
         return_ref: $return_ref:tt,
 
+        // True if we `return_arc` flag was given to the function
+        return_arc: $return_arc:tt,
+
+        // If true, panics of the function are memoized and re-raised until its inputs change.
+        catch_panic: $catch_panic:tt,
+
         // Annoyingly macro-rules hygiene does not extend to items defined in the macro.
         // We have the procedural macro generate names for those items that are
         // not used elsewhere in the user's code.
@@ -75,43 +99,68 @@ macro_rules! setup_tracked_fn {
         // Suppress this clippy lint because we sometimes require `'db` where the ordinary Rust rules would not.
         #[allow(clippy::needless_lifetimes)]
         $(#[$attr])*
-        $vis fn $fn_name<$db_lt>(
+        $vis fn $fn_name<$db_lt, $($T),*>(
             $db: &$db_lt dyn $Db,
             $($input_id: $input_ty,)*
         ) -> salsa::plumbing::macro_if! {
             if $return_ref {
                 &$db_lt $output_ty
             } else {
-                $output_ty
+                salsa::plumbing::macro_if! {
+                    if $return_arc {
+                        std::sync::Arc<$output_ty>
+                    } else {
+                        $output_ty
+                    }
+                }
             }
-        } {
+        }
+        where
+            $($where_clause)*
+        {
             use salsa::plumbing as $zalsa;
 
-            struct $Configuration;
+            struct $Configuration<$($T),*>(std::marker::PhantomData<fn() -> ($($T,)*)>);
 
-            static $FN_CACHE: $zalsa::IngredientCache<$zalsa::function::IngredientImpl<$Configuration>> =
-                $zalsa::IngredientCache::new();
+            $zalsa::setup_ingredient_cache! {
+                type_params: [$($T),*],
+                zalsa: $zalsa,
+                $FN_CACHE: $zalsa::function::IngredientImpl<$Configuration>
+            }
 
             $zalsa::macro_if! {
                 if $needs_interner {
-                    #[derive(Copy, Clone)]
-                    struct $InternedData<$db_lt>(
+                    struct $InternedData<$db_lt, $($T),*>(
                         salsa::Id,
-                        std::marker::PhantomData<&$db_lt $zalsa::interned::Value<$Configuration>>,
+                        std::marker::PhantomData<(&$db_lt (), fn() -> ($($T,)*))>,
                     );
 
-                    static $INTERN_CACHE: $zalsa::IngredientCache<$zalsa::interned::IngredientImpl<$Configuration>> =
-                        $zalsa::IngredientCache::new();
+                    impl<$db_lt, $($T),*> Copy for $InternedData<$db_lt, $($T),*> {}
 
-                    impl $zalsa::SalsaStructInDb for $InternedData<'_> {
+                    impl<$db_lt, $($T),*> Clone for $InternedData<$db_lt, $($T),*> {
+                        fn clone(&self) -> Self {
+                            *self
+                        }
+                    }
+
+                    $zalsa::setup_ingredient_cache! {
+                        type_params: [$($T),*],
+                        zalsa: $zalsa,
+                        $INTERN_CACHE: $zalsa::interned::IngredientImpl<$Configuration>
                     }
 
-                    impl $zalsa::interned::Configuration for $Configuration {
+                    impl<$($T),*> $zalsa::SalsaStructInDb for $InternedData<'_, $($T),*> {
+                    }
+
+                    impl<$($T),*> $zalsa::interned::Configuration for $Configuration<$($T),*>
+                    where
+                        $($where_clause)*
+                    {
                         const DEBUG_NAME: &'static str = "Configuration";
 
                         type Data<$db_lt> = ($($input_ty),*);
 
-                        type Struct<$db_lt> = $InternedData<$db_lt>;
+                        type Struct<$db_lt> = $InternedData<$db_lt, $($T),*>;
 
                         fn struct_from_id<$db_lt>(
                             id: salsa::Id,
@@ -124,42 +173,58 @@ macro_rules! setup_tracked_fn {
                         }
                     }
                 } else {
-                    type $InternedData<$db_lt> = ($($input_ty),*);
+                    type $InternedData<$db_lt, $($T),*> = ($($input_ty),*);
                 }
             }
 
-            impl $Configuration {
-                fn fn_ingredient(db: &dyn $Db) -> &$zalsa::function::IngredientImpl<$Configuration> {
+            impl<$($T),*> $Configuration<$($T),*>
+            where
+                $($where_clause)*
+            {
+                fn fn_ingredient(db: &dyn $Db) -> &$zalsa::function::IngredientImpl<Self> {
                     $FN_CACHE.get_or_create(db.as_dyn_database(), || {
                         <dyn $Db as $Db>::zalsa_db(db);
-                        db.zalsa().add_or_lookup_jar_by_type(&$Configuration)
+                        db.zalsa().add_or_lookup_jar_by_type(&Self(std::marker::PhantomData))
                     })
                 }
 
                 $zalsa::macro_if! { $needs_interner =>
                     fn intern_ingredient(
                         db: &dyn $Db,
-                    ) -> &$zalsa::interned::IngredientImpl<$Configuration> {
+                    ) -> &$zalsa::interned::IngredientImpl<Self> {
                         $INTERN_CACHE.get_or_create(db.as_dyn_database(), || {
-                            db.zalsa().add_or_lookup_jar_by_type(&$Configuration).successor(0)
+                            db.zalsa().add_or_lookup_jar_by_type(&Self(std::marker::PhantomData)).successor(0)
                         })
                     }
                 }
             }
 
-            impl $zalsa::function::Configuration for $Configuration {
+            impl<$($T),*> $zalsa::function::Configuration for $Configuration<$($T),*>
+            where
+                $($where_clause)*
+            {
                 const DEBUG_NAME: &'static str = stringify!($fn_name);
 
                 type DbView = dyn $Db;
 
-                type SalsaStruct<$db_lt> = $InternedData<$db_lt>;
+                type SalsaStruct<$db_lt> = $InternedData<$db_lt, $($T),*>;
 
                 type Input<$db_lt> = ($($input_ty),*);
 
-                type Output<$db_lt> = $output_ty;
+                type Output<$db_lt> = $zalsa::macro_if! {
+                    if $return_arc {
+                        std::sync::Arc<$output_ty>
+                    } else {
+                        $output_ty
+                    }
+                };
 
                 const CYCLE_STRATEGY: $zalsa::CycleRecoveryStrategy = $zalsa::CycleRecoveryStrategy::$cycle_recovery_strategy;
 
+                const CATCH_PANIC: bool = $catch_panic;
+
+                const NO_EQ: bool = $no_eq;
+
                 fn should_backdate_value(
                     old_value: &Self::Output<'_>,
                     new_value: &Self::Output<'_>,
@@ -168,7 +233,45 @@ macro_rules! setup_tracked_fn {
                         if $no_eq {
                             false
                         } else {
-                            $zalsa::should_backdate_value(old_value, new_value)
+                            $zalsa::macro_if! {
+                                if $return_arc {
+                                    $eq(&**old_value, &**new_value)
+                                } else {
+                                    $eq(old_value, new_value)
+                                }
+                            }
+                        }
+                    }
+                }
+
+                fn estimated_size(value: &Self::Output<'_>) -> Option<usize> {
+                    $zalsa::macro_if! {
+                        if $estimated_size {
+                            Some($zalsa::macro_if! {
+                                if $return_arc {
+                                    $zalsa::EstimatedSize::estimated_size(&**value)
+                                } else {
+                                    $zalsa::EstimatedSize::estimated_size(value)
+                                }
+                            })
+                        } else {
+                            {
+                                let _ = value;
+                                None
+                            }
+                        }
+                    }
+                }
+
+                fn fingerprint(value: &Self::Output<'_>) -> Option<u128> {
+                    $zalsa::macro_if! {
+                        if $fingerprint {
+                            Some($zalsa::fingerprint(value))
+                        } else {
+                            {
+                                let _ = value;
+                                None
+                            }
                         }
                     }
                 }
@@ -176,21 +279,35 @@ macro_rules! setup_tracked_fn {
                 fn execute<$db_lt>($db: &$db_lt Self::DbView, ($($input_id),*): ($($input_ty),*)) -> Self::Output<$db_lt> {
                     $inner_fn
 
-                    $inner($db, $($input_id),*)
+                    $zalsa::macro_if! {
+                        if $return_arc {
+                            std::sync::Arc::new($inner::<$($T),*>($db, $($input_id),*))
+                        } else {
+                            $inner::<$($T),*>($db, $($input_id),*)
+                        }
+                    }
                 }
 
+                // With `return_arc`, wrapping the result of `unexpected_cycle_recovery!` is unreachable.
+                #[allow(unreachable_code)]
                 fn recover_from_cycle<$db_lt>(
                     db: &$db_lt dyn $Db,
                     cycle: &$zalsa::Cycle,
                     ($($input_id),*): ($($input_ty),*)
                 ) -> Self::Output<$db_lt> {
-                    $($cycle_recovery_fn)*(db, cycle, $($input_id),*)
+                    $zalsa::macro_if! {
+                        if $return_arc {
+                            std::sync::Arc::new($($cycle_recovery_fn)*(db, cycle, $($input_id),*))
+                        } else {
+                            $($cycle_recovery_fn)*(db, cycle, $($input_id),*)
+                        }
+                    }
                 }
 
                 fn id_to_input<$db_lt>(db: &$db_lt Self::DbView, key: salsa::Id) -> Self::Input<$db_lt> {
                     $zalsa::macro_if! {
                         if $needs_interner {
-                            $Configuration::intern_ingredient(db).data(db.as_dyn_database(), key).clone()
+                            Self::intern_ingredient(db).data(db.as_dyn_database(), key).clone()
                         } else {
                             $zalsa::FromId::from_id(key)
                         }
@@ -198,22 +315,26 @@ macro_rules! setup_tracked_fn {
                 }
             }
 
-            impl $zalsa::Jar for $Configuration {
+            impl<$($T),*> $zalsa::Jar for $Configuration<$($T),*>
+            where
+                $($where_clause)*
+            {
                 fn create_ingredients(
                     &self,
                     aux: &dyn $zalsa::JarAux,
                     first_index: $zalsa::IngredientIndex,
                 ) -> Vec<Box<dyn $zalsa::Ingredient>> {
-                    let mut fn_ingredient = <$zalsa::function::IngredientImpl<$Configuration>>::new(
+                    let mut fn_ingredient = <$zalsa::function::IngredientImpl<Self>>::new(
                         first_index,
                         aux,
                     );
                     fn_ingredient.set_capacity($lru);
+                    $(fn_ingredient.set_eviction_policy($eviction);)?
                     $zalsa::macro_if! {
                         if $needs_interner {
                             vec![
                                 Box::new(fn_ingredient),
-                                Box::new(<$zalsa::interned::IngredientImpl<$Configuration>>::new(
+                                Box::new(<$zalsa::interned::IngredientImpl<Self>>::new(
                                     first_index.successor(0)
                                 )),
                             ]
@@ -228,41 +349,216 @@ macro_rules! setup_tracked_fn {
 
             #[allow(non_local_definitions)]
             impl $fn_name {
-                pub fn accumulated<$db_lt, A: salsa::Accumulator>(
+                pub fn accumulated<$db_lt, $($T,)* A: salsa::Accumulator>(
                     $db: &$db_lt dyn $Db,
                     $($input_id: $input_ty,)*
-                ) -> Vec<A> {
+                ) -> Vec<A>
+                where
+                    $($where_clause)*
+                {
                     use salsa::plumbing as $zalsa;
                     let key = $zalsa::macro_if! {
                         if $needs_interner {
-                            $Configuration::intern_ingredient($db).intern_id($db.as_dyn_database(), ($($input_id),*))
+                            $Configuration::<$($T),*>::intern_ingredient($db).intern_id($db.as_dyn_database(), ($($input_id),*))
                         } else {
                             $zalsa::AsId::as_id(&($($input_id),*))
                         }
                     };
 
-                    $Configuration::fn_ingredient($db).accumulated_by::<A>($db, key)
+                    $Configuration::<$($T),*>::fn_ingredient($db).accumulated_by::<A>($db, key)
+                }
+
+                /// Like `accumulated`, but also returns the query that accumulated each value.
+                /// `filter` is called for each query reached from this one; if it returns false,
+                /// the values of that query and of the queries only reached through it are skipped.
+                #[allow(dead_code)]
+                pub fn accumulated_with_origin<$db_lt, $($T,)* A: salsa::Accumulator>(
+                    $db: &$db_lt dyn $Db,
+                    $($input_id: $input_ty,)*
+                    filter: impl FnMut(salsa::DatabaseKeyIndex) -> bool,
+                ) -> Vec<(salsa::DatabaseKeyIndex, A)>
+                where
+                    $($where_clause)*
+                {
+                    use salsa::plumbing as $zalsa;
+                    let key = $zalsa::macro_if! {
+                        if $needs_interner {
+                            $Configuration::<$($T),*>::intern_ingredient($db).intern_id($db.as_dyn_database(), ($($input_id),*))
+                        } else {
+                            $zalsa::AsId::as_id(&($($input_id),*))
+                        }
+                    };
+
+                    $Configuration::<$($T),*>::fn_ingredient($db).accumulated_with_origin::<A>($db, key, filter)
+                }
+
+                /// Subscribes `callback` to the changes of the result of this function for the given inputs,
+                /// see [`Database::notify_watchers`](`salsa::Database::notify_watchers`).
+                #[allow(dead_code, private_interfaces)]
+                pub fn watch<$db_lt, $($T),*>(
+                    $db: &$db_lt dyn $Db,
+                    $($input_id: $input_ty,)*
+                    callback: impl for<'w> Fn(
+                        &'w dyn $Db,
+                        &<$Configuration<$($T),*> as salsa::plumbing::function::Configuration>::Output<'w>,
+                    ) + Send + Sync + 'static,
+                ) -> salsa::WatchId
+                where
+                    $($where_clause)*
+                {
+                    use salsa::plumbing as $zalsa;
+                    let key = $zalsa::macro_if! {
+                        if $needs_interner {
+                            $Configuration::<$($T),*>::intern_ingredient($db).intern_id($db.as_dyn_database(), ($($input_id),*))
+                        } else {
+                            $zalsa::AsId::as_id(&($($input_id),*))
+                        }
+                    };
+
+                    let database_key = $Configuration::<$($T),*>::fn_ingredient($db).database_key_index(key);
+                    $zalsa::watch($db.as_dyn_database(), database_key, move |db| {
+                        let db = db.as_view::<dyn $Db>();
+                        callback(db, $Configuration::<$($T),*>::fn_ingredient(db).fetch(db, key))
+                    })
+                }
+
+                /// Registers this function with the given inputs as a hot query,
+                /// see [`Database::warm_hot_queries`](`salsa::Database::warm_hot_queries`).
+                #[allow(dead_code)]
+                pub fn register_hot<$db_lt, $($T),*>(
+                    $db: &$db_lt dyn $Db,
+                    $($input_id: $input_ty,)*
+                )
+                where
+                    $($where_clause)*
+                {
+                    use salsa::plumbing as $zalsa;
+                    let key = $zalsa::macro_if! {
+                        if $needs_interner {
+                            $Configuration::<$($T),*>::intern_ingredient($db).intern_id($db.as_dyn_database(), ($($input_id),*))
+                        } else {
+                            $zalsa::AsId::as_id(&($($input_id),*))
+                        }
+                    };
+
+                    let database_key = $Configuration::<$($T),*>::fn_ingredient($db).database_key_index(key);
+                    $zalsa::register_hot($db.as_dyn_database(), database_key, move |db| {
+                        let db = db.as_view::<dyn $Db>();
+                        $Configuration::<$($T),*>::fn_ingredient(db).fetch(db, key);
+                    })
+                }
+
+                /// The key identifying this function with the given inputs in events,
+                /// see [`salsa::testing`].
+                #[allow(dead_code)]
+                pub fn database_key<$db_lt, $($T),*>(
+                    $db: &$db_lt dyn $Db,
+                    $($input_id: $input_ty,)*
+                ) -> salsa::DatabaseKeyIndex
+                where
+                    $($where_clause)*
+                {
+                    use salsa::plumbing as $zalsa;
+                    let key = $zalsa::macro_if! {
+                        if $needs_interner {
+                            $Configuration::<$($T),*>::intern_ingredient($db).intern_id($db.as_dyn_database(), ($($input_id),*))
+                        } else {
+                            $zalsa::AsId::as_id(&($($input_id),*))
+                        }
+                    };
+
+                    $Configuration::<$($T),*>::fn_ingredient($db).database_key_index(key)
+                }
+
+                /// Like calling the function directly, but a cycle without recovery
+                /// or a cancellation is returned as an `Err` instead of unwinding.
+                ///
+                /// Inside a tracked function, the error is returned to the function too,
+                /// and the function is executed again in the next revision; if it takes
+                /// part in the cycle and recovers from it, it unwinds to recover instead.
+                /// See `salsa::QueryError`.
+                #[allow(dead_code, clippy::needless_lifetimes)]
+                pub fn try_call<$db_lt, $($T),*>(
+                    $db: &$db_lt dyn $Db,
+                    $($input_id: $input_ty,)*
+                ) -> Result<
+                    salsa::plumbing::macro_if! {
+                        if $return_ref {
+                            &$db_lt $output_ty
+                        } else {
+                            salsa::plumbing::macro_if! {
+                                if $return_arc {
+                                    std::sync::Arc<$output_ty>
+                                } else {
+                                    $output_ty
+                                }
+                            }
+                        }
+                    },
+                    salsa::QueryError,
+                >
+                where
+                    $($where_clause)*
+                {
+                    use salsa::plumbing as $zalsa;
+                    $zalsa::attach($db, || {
+                        let key = $zalsa::macro_if! {
+                            if $needs_interner {
+                                $Configuration::<$($T),*>::intern_ingredient($db).intern_id($db.as_dyn_database(), ($($input_id),*))
+                            } else {
+                                $zalsa::AsId::as_id(&($($input_id),*))
+                            }
+                        };
+
+                        let result = $Configuration::<$($T),*>::fn_ingredient($db).try_fetch($db, key)?;
+
+                        Ok($zalsa::macro_if! {
+                            if $return_ref {
+                                result
+                            } else {
+                                $zalsa::macro_if! {
+                                    if $return_arc {
+                                        std::sync::Arc::clone(result)
+                                    } else {
+                                        <$output_ty as std::clone::Clone>::clone(result)
+                                    }
+                                }
+                            }
+                        })
+                    })
                 }
 
                 $zalsa::macro_if! { $is_specifiable =>
-                    pub fn specify<$db_lt>(
+                    pub fn specify<$db_lt, $($T),*>(
                         $db: &$db_lt dyn $Db,
                         $($input_id: $input_ty,)*
                         value: $output_ty,
-                    ) {
+                    )
+                    where
+                        $($where_clause)*
+                    {
                         let key = $zalsa::AsId::as_id(&($($input_id),*));
-                        $Configuration::fn_ingredient($db).specify_and_record(
+                        $Configuration::<$($T),*>::fn_ingredient($db).specify_and_record(
                             $db,
                             key,
-                            value,
+                            $zalsa::macro_if! {
+                                if $return_arc {
+                                    std::sync::Arc::new(value)
+                                } else {
+                                    value
+                                }
+                            },
                         )
                     }
                 }
 
                 $zalsa::macro_if! { if0 $lru { } else {
                     #[allow(dead_code)]
-                    fn set_lru_capacity(db: &dyn $Db, value: usize) {
-                        $Configuration::fn_ingredient(db).set_capacity(value);
+                    fn set_lru_capacity<$($T),*>(db: &dyn $Db, value: usize)
+                    where
+                        $($where_clause)*
+                    {
+                        $Configuration::<$($T),*>::fn_ingredient(db).set_capacity(value);
                     }
                 } }
             }
@@ -271,11 +567,11 @@ macro_rules! setup_tracked_fn {
                 let result = $zalsa::macro_if! {
                     if $needs_interner {
                         {
-                            let key = $Configuration::intern_ingredient($db).intern_id($db.as_dyn_database(), ($($input_id),*));
-                            $Configuration::fn_ingredient($db).fetch($db, key)
+                            let key = $Configuration::<$($T),*>::intern_ingredient($db).intern_id($db.as_dyn_database(), ($($input_id),*));
+                            $Configuration::<$($T),*>::fn_ingredient($db).fetch($db, key)
                         }
                     } else {
-                        $Configuration::fn_ingredient($db).fetch($db, $zalsa::AsId::as_id(&($($input_id),*)))
+                        $Configuration::<$($T),*>::fn_ingredient($db).fetch($db, $zalsa::AsId::as_id(&($($input_id),*)))
                     }
                 };
 
@@ -283,7 +579,13 @@ macro_rules! setup_tracked_fn {
                     if $return_ref {
                         result
                     } else {
-                        <$output_ty as std::clone::Clone>::clone(result)
+                        $zalsa::macro_if! {
+                            if $return_arc {
+                                std::sync::Arc::clone(result)
+                            } else {
+                                <$output_ty as std::clone::Clone>::clone(result)
+                            }
+                        }
                     }
                 }
             })
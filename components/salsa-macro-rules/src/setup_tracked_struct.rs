@@ -11,6 +11,12 @@ macro_rules! setup_tracked_struct {
         // Name of the struct
         Struct: $Struct:ident,
 
+        // Type parameters of the struct; each monomorphization gets its own ingredients.
+        type_params: [$($T:ident),*],
+
+        // Predicates that hold for the type parameters (including their inline bounds and `'static`).
+        where_clause: [$($where_clause:tt)*],
+
         // Name of the `'db` lifetime that the user gave
         db_lt: $db_lt:lifetime,
 
@@ -35,7 +41,8 @@ macro_rules! setup_tracked_struct {
         // A set of "field options". Each field option is a tuple `(maybe_clone, maybe_backdate)` where:
         //
         // * `maybe_clone` is either the identifier `clone` or `no_clone`
-        // * `maybe_backdate` is either the identifier `backdate` or `no_backdate`
+        // * `maybe_backdate` is either the identifier `backdate` or `no_backdate`,
+        //   or `(backdate_with path)` to compare with the function `path`
         //
         // These are used to drive conditional logic for each field via recursive macro invocation
         // (see e.g. @maybe_clone below).
@@ -47,6 +54,9 @@ macro_rules! setup_tracked_struct {
         // If true, generate a debug impl.
         generate_debug_impl: $generate_debug_impl:tt,
 
+        // If true, generate `Serialize` and `Deserialize` impls.
+        generate_serde_impls: $generate_serde_impls:tt,
+
         // Annoyingly macro-rules hygiene does not extend to items defined in the macro.
         // We have the procedural macro generate names for those items that are
         // not used elsewhere in the user's code.
@@ -54,6 +64,7 @@ macro_rules! setup_tracked_struct {
             $zalsa:ident,
             $zalsa_struct:ident,
             $Configuration:ident,
+            $HasConfiguration:ident,
             $CACHE:ident,
             $Db:ident,
             $NonNull:ident,
@@ -61,10 +72,9 @@ macro_rules! setup_tracked_struct {
         ]
     ) => {
         $(#[$attr])*
-        #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-        $vis struct $Struct<$db_lt>(
+        $vis struct $Struct<$db_lt, $($T),*>(
             salsa::Id,
-            std::marker::PhantomData < & $db_lt salsa::plumbing::tracked_struct::Value < $Struct<'static> > >
+            std::marker::PhantomData<(&$db_lt (), fn() -> ($($T,)*))>
         );
 
         #[allow(clippy::all)]
@@ -75,9 +85,12 @@ macro_rules! setup_tracked_struct {
             use $zalsa::Revision as $Revision;
             use std::ptr::NonNull as $NonNull;
 
-            type $Configuration = $Struct<'static>;
+            type $Configuration<$($T),*> = $Struct<'static, $($T),*>;
 
-            impl $zalsa_struct::Configuration for $Configuration {
+            impl<$($T),*> $zalsa_struct::Configuration for $Configuration<$($T),*>
+            where
+                $($where_clause)*
+            {
                 const DEBUG_NAME: &'static str = stringify!($Struct);
 
                 const FIELD_DEBUG_NAMES: &'static [&'static str] = &[
@@ -88,7 +101,7 @@ macro_rules! setup_tracked_struct {
 
                 type Revisions = $zalsa::Array<$Revision, $N>;
 
-                type Struct<$db_lt> = $Struct<$db_lt>;
+                type Struct<$db_lt> = $Struct<$db_lt, $($T),*>;
 
                 fn struct_from_id<$db_lt>(id: salsa::Id) -> Self::Struct<$db_lt> {
                     $Struct(id, std::marker::PhantomData)
@@ -129,50 +142,163 @@ macro_rules! setup_tracked_struct {
                 }
             }
 
-            impl $Configuration {
-                pub fn ingredient(db: &dyn $zalsa::Database) -> &$zalsa_struct::IngredientImpl<$Configuration> {
-                    static CACHE: $zalsa::IngredientCache<$zalsa_struct::IngredientImpl<$Configuration>> =
-                        $zalsa::IngredientCache::new();
+            impl<$($T),*> $Configuration<$($T),*>
+            where
+                $($where_clause)*
+            {
+                pub fn ingredient(db: &dyn $zalsa::Database) -> &$zalsa_struct::IngredientImpl<Self> {
+                    $zalsa::setup_ingredient_cache! {
+                        type_params: [$($T),*],
+                        zalsa: $zalsa,
+                        CACHE: $zalsa_struct::IngredientImpl<$Configuration>
+                    }
                     CACHE.get_or_create(db, || {
-                        db.zalsa().add_or_lookup_jar_by_type(&<$zalsa_struct::JarImpl::<$Configuration>>::default())
+                        db.zalsa().add_or_lookup_jar_by_type(&<$zalsa_struct::JarImpl::<Self>>::default())
                     })
                 }
             }
 
-            impl<$db_lt> $zalsa::FromId for $Struct<$db_lt> {
+            // Lets code that is repeated per field name the configuration
+            // without naming the type parameters.
+            trait $HasConfiguration {
+                type Configuration;
+            }
+
+            impl<$($T),*> $HasConfiguration for $Struct<'_, $($T),*> {
+                type Configuration = $Configuration<$($T),*>;
+            }
+
+            impl<$($T),*> Copy for $Struct<'_, $($T),*> {}
+
+            impl<$($T),*> Clone for $Struct<'_, $($T),*> {
+                fn clone(&self) -> Self {
+                    *self
+                }
+            }
+
+            impl<$($T),*> PartialEq for $Struct<'_, $($T),*> {
+                fn eq(&self, other: &Self) -> bool {
+                    self.0 == other.0
+                }
+            }
+
+            impl<$($T),*> Eq for $Struct<'_, $($T),*> {}
+
+            impl<$($T),*> PartialOrd for $Struct<'_, $($T),*> {
+                fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            impl<$($T),*> Ord for $Struct<'_, $($T),*> {
+                fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                    self.0.cmp(&other.0)
+                }
+            }
+
+            impl<$($T),*> std::hash::Hash for $Struct<'_, $($T),*> {
+                fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                    self.0.hash(state);
+                }
+            }
+
+            impl<$db_lt, $($T),*> $zalsa::FromId for $Struct<$db_lt, $($T),*>
+            where
+                $($where_clause)*
+            {
                 fn from_id(id: salsa::Id) -> Self {
                     $Struct(id, std::marker::PhantomData)
                 }
             }
 
-            impl $zalsa::AsId for $Struct<'_> {
+            impl<$($T),*> $zalsa::AsId for $Struct<'_, $($T),*> {
                 fn as_id(&self) -> $zalsa::Id {
                     self.0
                 }
             }
 
-            impl $zalsa::SalsaStructInDb for $Struct<'_> {
+            $zalsa::macro_if! { $generate_serde_impls =>
+                impl<$db_lt, $($T),*> $zalsa::serde::Serialize for $Struct<$db_lt, $($T),*>
+                where
+                    $($where_clause)*
+                    $($field_ty: $zalsa::serde::Serialize,)*
+                {
+                    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                    where
+                        S: $zalsa::serde::Serializer,
+                    {
+                        $zalsa::with_attached_database(|db| {
+                            // SAFETY: the fields are only borrowed for the duration of this call,
+                            // so we can give the database the lifetime of `Self` to type them.
+                            let db: &$db_lt dyn $zalsa::Database = unsafe { std::mem::transmute(db) };
+                            let ingredient = $Configuration::<$($T),*>::ingredient(db);
+                            // Read each field as the getters do, so that serializing
+                            // within a tracked function records a dependency on them.
+                            $(ingredient.field(db, *self, $field_index);)*
+                            let fields = ingredient.leak_fields(db, *self);
+                            $zalsa::SerializeFields::serialize_fields(
+                                fields,
+                                serializer,
+                                stringify!($Struct),
+                                &[$(stringify!($field_id)),*],
+                            )
+                        }).unwrap_or_else(|| Err($zalsa::serialize_without_database(stringify!($Struct))))
+                    }
+                }
+
+                impl<'de, $db_lt, $($T),*> $zalsa::serde::Deserialize<'de> for $Struct<$db_lt, $($T),*>
+                where
+                    $($where_clause)*
+                    $($field_ty: $zalsa::serde::Deserialize<'de>,)*
+                {
+                    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                    where
+                        D: $zalsa::serde::Deserializer<'de>,
+                    {
+                        let fields = <($($field_ty,)*) as $zalsa::DeserializeFields<'de>>::deserialize_fields(
+                            deserializer,
+                            stringify!($Struct),
+                            &[$(stringify!($field_id)),*],
+                        )?;
+                        $zalsa::with_attached_database(|db| {
+                            // SAFETY: the database is only used within this call, and the new struct
+                            // is only an id, like those created by `FromId`.
+                            let db: &$db_lt dyn $zalsa::Database = unsafe { std::mem::transmute(db) };
+                            $Configuration::<$($T),*>::ingredient(db).new_struct(db, fields)
+                        })
+                        .ok_or_else(|| $zalsa::deserialize_without_database(stringify!($Struct)))
+                    }
+                }
+            }
+
+            impl<$($T),*> $zalsa::SalsaStructInDb for $Struct<'_, $($T),*> {
             }
 
-            impl $zalsa::TrackedStructInDb for $Struct<'_> {
+            impl<$($T),*> $zalsa::TrackedStructInDb for $Struct<'_, $($T),*>
+            where
+                $($where_clause)*
+            {
                 fn database_key_index(db: &dyn $zalsa::Database, id: $zalsa::Id) -> $zalsa::DatabaseKeyIndex {
-                    $Configuration::ingredient(db).database_key_index(id)
+                    $Configuration::<$($T),*>::ingredient(db).database_key_index(id)
                 }
             }
 
-            unsafe impl Send for $Struct<'_> {}
+            unsafe impl<$($T),*> Send for $Struct<'_, $($T),*> {}
 
-            unsafe impl Sync for $Struct<'_> {}
+            unsafe impl<$($T),*> Sync for $Struct<'_, $($T),*> {}
 
             $zalsa::macro_if! { $generate_debug_impl =>
-                impl std::fmt::Debug for $Struct<'_> {
+                impl<$($T),*> std::fmt::Debug for $Struct<'_, $($T),*>
+                where
+                    $($where_clause)*
+                {
                     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                         Self::default_debug_fmt(*self, f)
                     }
                 }
             }
 
-            unsafe impl $zalsa::Update for $Struct<'_> {
+            unsafe impl<$($T),*> $zalsa::Update for $Struct<'_, $($T),*> {
                 unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
                     if unsafe { *old_pointer } != new_value {
                         unsafe { *old_pointer = new_value };
@@ -183,18 +309,30 @@ macro_rules! setup_tracked_struct {
                 }
             }
 
-            impl<$db_lt> $Struct<$db_lt> {
+            impl<$db_lt, $($T),*> $Struct<$db_lt, $($T),*>
+            where
+                $($where_clause)*
+            {
                 pub fn $new_fn<$Db>(db: &$db_lt $Db, $($field_id: $field_ty),*) -> Self
                 where
                     // FIXME(rust-lang/rust#65991): The `db` argument *should* have the type `dyn Database`
                     $Db: ?Sized + $zalsa::Database,
                 {
-                    $Configuration::ingredient(db.as_dyn_database()).new_struct(
+                    $Configuration::<$($T),*>::ingredient(db.as_dyn_database()).new_struct(
                         db.as_dyn_database(),
                         ($($field_id,)*)
                     )
                 }
 
+                /// Iterates over all the instances of this struct that have not been deleted.
+                pub fn all<$Db>(db: &$db_lt $Db) -> impl Iterator<Item = Self> + $db_lt
+                where
+                    // FIXME(rust-lang/rust#65991): The `db` argument *should* have the type `dyn Database`
+                    $Db: ?Sized + $zalsa::Database,
+                {
+                    $Configuration::<$($T),*>::ingredient(db.as_dyn_database()).all(db.as_dyn_database())
+                }
+
                 $(
                     $field_getter_vis fn $field_getter_id<$Db>(self, db: &$db_lt $Db) -> $crate::maybe_cloned_ty!($field_option, $db_lt, $field_ty)
                     where
@@ -202,7 +340,7 @@ macro_rules! setup_tracked_struct {
                         $Db: ?Sized + $zalsa::Database,
                     {
                         let db = db.as_dyn_database();
-                        let fields = $Configuration::ingredient(db).field(db, self, $field_index);
+                        let fields = <<Self as $HasConfiguration>::Configuration>::ingredient(db).field(db, self, $field_index);
                         $crate::maybe_clone!(
                             $field_option,
                             $field_ty,
@@ -214,7 +352,7 @@ macro_rules! setup_tracked_struct {
                 /// Default debug formatting for this struct (may be useful if you define your own `Debug` impl)
                 pub fn default_debug_fmt(this: Self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                     $zalsa::with_attached_database(|db| {
-                        let fields = $Configuration::ingredient(db).leak_fields(db, this);
+                        let fields = $Configuration::<$($T),*>::ingredient(db).leak_fields(db, this);
                         let mut f = f.debug_struct(stringify!($Struct));
                         let f = f.field("[salsa id]", &$zalsa::AsId::as_id(&this));
                         $(
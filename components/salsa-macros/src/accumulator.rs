@@ -41,6 +41,13 @@ impl AllowedOptions for Accumulator {
     const RECOVERY_FN: bool = false;
     const LRU: bool = false;
     const CONSTRUCTOR_NAME: bool = false;
+    const CATCH_PANIC: bool = false;
+    const RETURN_ARC: bool = false;
+    const SERDE: bool = false;
+    const EVICTION: bool = false;
+    const ESTIMATED_SIZE: bool = false;
+    const EQ: bool = false;
+    const FINGERPRINT: bool = false;
 }
 
 struct StructMacro {
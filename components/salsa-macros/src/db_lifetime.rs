@@ -1,7 +1,7 @@
 //! Helper functions for working with fns, structs, and other generic things
 //! that are allowed to have a `'db` lifetime.
 
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream};
 use syn::spanned::Spanned;
 
 /// Normally we try to use whatever lifetime parameter the user gave us
@@ -14,10 +14,11 @@ pub(crate) fn default_db_lifetime(span: Span) -> syn::Lifetime {
     }
 }
 
-/// Require that either there are no generics or exactly one lifetime parameter.
+/// Require that there is at most one lifetime parameter, which (if present)
+/// comes first and is followed only by type parameters.
 pub(crate) fn require_optional_db_lifetime(generics: &syn::Generics) -> syn::Result<()> {
-    if generics.params.is_empty() {
-        return Ok(());
+    if generics.lifetimes().next().is_none() {
+        return require_no_lifetime(generics);
     }
 
     require_db_lifetime(generics)?;
@@ -25,9 +26,10 @@ pub(crate) fn require_optional_db_lifetime(generics: &syn::Generics) -> syn::Res
     Ok(())
 }
 
-/// Require that either there is exactly one lifetime parameter.
+/// Require that there is exactly one lifetime parameter,
+/// optionally followed by type parameters.
 pub(crate) fn require_db_lifetime(generics: &syn::Generics) -> syn::Result<()> {
-    if generics.params.is_empty() {
+    if generics.lifetimes().next().is_none() {
         return Err(syn::Error::new_spanned(
             generics,
             "this definition must have a `'db` lifetime",
@@ -35,16 +37,17 @@ pub(crate) fn require_db_lifetime(generics: &syn::Generics) -> syn::Result<()> {
     }
 
     for (param, index) in generics.params.iter().zip(0..) {
-        let error = match param {
-            syn::GenericParam::Lifetime(_) => index > 0,
-            syn::GenericParam::Type(_) | syn::GenericParam::Const(_) => true,
-        };
-
-        if error {
-            return Err(syn::Error::new_spanned(
-                param,
-                "only a single lifetime parameter is accepted",
-            ));
+        match param {
+            syn::GenericParam::Lifetime(_) => {
+                if index > 0 {
+                    return Err(syn::Error::new_spanned(
+                        param,
+                        "only a single lifetime parameter is accepted",
+                    ));
+                }
+            }
+            syn::GenericParam::Type(ty) => require_supported_type_param(ty)?,
+            syn::GenericParam::Const(_) => return Err(const_param_error(param)),
         }
     }
 
@@ -61,13 +64,73 @@ pub(crate) fn db_lifetime(generics: &syn::Generics) -> syn::Lifetime {
     }
 }
 
-pub(crate) fn require_no_generics(generics: &syn::Generics) -> syn::Result<()> {
-    if let Some(param) = generics.params.iter().next() {
+/// Require that there are no lifetime parameters; type parameters are accepted.
+pub(crate) fn require_no_lifetime(generics: &syn::Generics) -> syn::Result<()> {
+    for param in &generics.params {
+        match param {
+            syn::GenericParam::Lifetime(_) => {
+                return Err(syn::Error::new_spanned(
+                    param,
+                    "lifetime parameters not allowed here",
+                ));
+            }
+            syn::GenericParam::Type(ty) => require_supported_type_param(ty)?,
+            syn::GenericParam::Const(_) => return Err(const_param_error(param)),
+        }
+    }
+
+    Ok(())
+}
+
+fn require_supported_type_param(ty: &syn::TypeParam) -> syn::Result<()> {
+    if let Some(default) = &ty.default {
         return Err(syn::Error::new_spanned(
-            param,
-            "generic parameters not allowed here",
+            default,
+            "type parameters with defaults are not supported",
         ));
     }
 
     Ok(())
 }
+
+fn const_param_error(param: &syn::GenericParam) -> syn::Error {
+    syn::Error::new_spanned(param, "const generic parameters are not supported")
+}
+
+/// Return the names of the type parameters.
+/// The generics ought to have been checked with one of the `require` functions already.
+pub(crate) fn type_params(generics: &syn::Generics) -> Vec<&syn::Ident> {
+    generics.type_params().map(|tp| &tp.ident).collect()
+}
+
+/// Add a `'static` bound to each type parameter, matching the predicates
+/// from [`where_predicates`].
+pub(crate) fn add_static_bounds(generics: &mut syn::Generics) {
+    for tp in generics.type_params_mut() {
+        tp.bounds
+            .push(syn::TypeParamBound::Lifetime(syn::Lifetime::new(
+                "'static",
+                Span::call_site(),
+            )));
+    }
+}
+
+/// Return the where-clause predicates to put on every item generated for the
+/// (possibly generic) definition: the inline bounds of each type parameter,
+/// with `'static` added since each monomorphization gets its own ingredients,
+/// followed by the predicates of the user's where clause.
+pub(crate) fn where_predicates(generics: &syn::Generics) -> Vec<TokenStream> {
+    let inline = generics.type_params().map(|tp| {
+        let ident = &tp.ident;
+        let bounds = tp.bounds.iter();
+        quote!(#ident: #(#bounds +)* 'static)
+    });
+
+    let explicit = generics
+        .where_clause
+        .iter()
+        .flat_map(|wc| wc.predicates.iter())
+        .map(|predicate| quote!(#predicate));
+
+    inline.chain(explicit).collect()
+}
@@ -54,6 +54,20 @@ impl crate::options::AllowedOptions for InputStruct {
     const LRU: bool = false;
 
     const CONSTRUCTOR_NAME: bool = true;
+
+    const CATCH_PANIC: bool = false;
+
+    const RETURN_ARC: bool = false;
+
+    const SERDE: bool = true;
+
+    const EVICTION: bool = false;
+
+    const ESTIMATED_SIZE: bool = false;
+
+    const EQ: bool = false;
+
+    const FINGERPRINT: bool = false;
 }
 
 impl SalsaStructAllowedOptions for InputStruct {
@@ -63,6 +77,8 @@ impl SalsaStructAllowedOptions for InputStruct {
 
     const HAS_LIFETIME: bool = false;
 
+    const ALLOW_EQ: bool = false;
+
     const ALLOW_DEFAULT: bool = true;
 }
 
@@ -80,6 +96,8 @@ impl Macro {
         let attrs = &self.struct_item.attrs;
         let vis = &self.struct_item.vis;
         let struct_ident = &self.struct_item.ident;
+        let type_params = salsa_struct.type_params();
+        let where_predicates = salsa_struct.where_predicates();
         let new_fn = salsa_struct.constructor_name();
         let field_ids = salsa_struct.field_ids();
         let field_indices = salsa_struct.field_indices();
@@ -93,10 +111,12 @@ impl Macro {
         let field_durability_ids = salsa_struct.field_durability_ids();
         let is_singleton = self.args.singleton.is_some();
         let generate_debug_impl = salsa_struct.generate_debug_impl();
+        let generate_serde_impls = salsa_struct.generate_serde_impls();
 
         let zalsa = self.hygiene.ident("zalsa");
         let zalsa_struct = self.hygiene.ident("zalsa_struct");
         let Configuration = self.hygiene.ident("Configuration");
+        let HasConfiguration = self.hygiene.ident("HasConfiguration");
         let Builder = self.hygiene.ident("Builder");
         let CACHE = self.hygiene.ident("CACHE");
         let Db = self.hygiene.ident("Db");
@@ -108,6 +128,8 @@ impl Macro {
                     attrs: [#(#attrs),*],
                     vis: #vis,
                     Struct: #struct_ident,
+                    type_params: [#(#type_params),*],
+                    where_clause: [#(#where_predicates,)*],
                     new_fn: #new_fn,
                     field_options: [#(#field_options),*],
                     field_ids: [#(#field_ids),*],
@@ -120,10 +142,12 @@ impl Macro {
                     num_fields: #num_fields,
                     is_singleton: #is_singleton,
                     generate_debug_impl: #generate_debug_impl,
+                    generate_serde_impls: #generate_serde_impls,
                     unused_names: [
                         #zalsa,
                         #zalsa_struct,
                         #Configuration,
+                        #HasConfiguration,
                         #Builder,
                         #CACHE,
                         #Db,
@@ -66,6 +66,46 @@ pub(crate) struct Options<A: AllowedOptions> {
     /// If this is `Some`, the value is the `<ident>`.
     pub constructor_name: Option<syn::Ident>,
 
+    /// The `catch_panic` option is used to signal that a panic raised by a tracked
+    /// function should be memoized and re-raised until its inputs change.
+    ///
+    /// If this is `Some`, the value is the `catch_panic` identifier.
+    pub catch_panic: Option<syn::Ident>,
+
+    /// The `return_arc` option is used to signal that a *tracked function* stores its
+    /// value in an `Arc` and returns clones of that `Arc`.
+    ///
+    /// If this is `Some`, the value is the `return_arc` identifier.
+    pub return_arc: Option<syn::Ident>,
+
+    /// The `serde` option is used to signal that a salsa struct should implement
+    /// `Serialize` and `Deserialize`, using the attached database (requires the `serde` feature).
+    ///
+    /// If this is `Some`, the value is the `serde` identifier.
+    pub serde: Option<syn::Ident>,
+
+    /// The `eviction = <expr>` option is used to set the policy deciding
+    /// which memoized values of a tracked function are evicted.
+    pub eviction: Option<syn::Expr>,
+
+    /// The `estimated_size` option is used to count the values of a tracked function
+    /// against the memory budget of the database, using their `EstimatedSize` impl.
+    ///
+    /// If this is `Some`, the value is the `estimated_size` identifier.
+    pub estimated_size: Option<syn::Ident>,
+
+    /// The `eq = <path>` option is used to compare the old and new values of a tracked function
+    /// when deciding whether to backdate them, in place of `Eq`.
+    ///
+    /// If this is `Some`, the value is the `<path>`.
+    pub eq: Option<syn::Path>,
+
+    /// The `fingerprint` option is used to keep a 128-bit hash of the values of a tracked function,
+    /// so that they can be backdated even after being evicted.
+    ///
+    /// If this is `Some`, the value is the `fingerprint` identifier.
+    pub fingerprint: Option<syn::Ident>,
+
     /// Remember the `A` parameter, which plays no role after parsing.
     phantom: PhantomData<A>,
 }
@@ -82,6 +122,13 @@ impl<A: AllowedOptions> Default for Options<A> {
             recovery_fn: Default::default(),
             data: Default::default(),
             constructor_name: Default::default(),
+            catch_panic: Default::default(),
+            return_arc: Default::default(),
+            serde: Default::default(),
+            eviction: Default::default(),
+            estimated_size: Default::default(),
+            eq: Default::default(),
+            fingerprint: Default::default(),
             phantom: Default::default(),
             lru: Default::default(),
             singleton: Default::default(),
@@ -102,6 +149,13 @@ pub(crate) trait AllowedOptions {
     const RECOVERY_FN: bool;
     const LRU: bool;
     const CONSTRUCTOR_NAME: bool;
+    const CATCH_PANIC: bool;
+    const RETURN_ARC: bool;
+    const SERDE: bool;
+    const EVICTION: bool;
+    const ESTIMATED_SIZE: bool;
+    const EQ: bool;
+    const FINGERPRINT: bool;
 }
 
 type Equals = syn::Token![=];
@@ -267,6 +321,102 @@ impl<A: AllowedOptions> syn::parse::Parse for Options<A> {
                         "`constructor` option not allowed here",
                     ));
                 }
+            } else if ident == "catch_panic" {
+                if A::CATCH_PANIC {
+                    if let Some(old) = std::mem::replace(&mut options.catch_panic, Some(ident)) {
+                        return Err(syn::Error::new(
+                            old.span(),
+                            "option `catch_panic` provided twice",
+                        ));
+                    }
+                } else {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "`catch_panic` option not allowed here",
+                    ));
+                }
+            } else if ident == "return_arc" {
+                if A::RETURN_ARC {
+                    if let Some(old) = std::mem::replace(&mut options.return_arc, Some(ident)) {
+                        return Err(syn::Error::new(
+                            old.span(),
+                            "option `return_arc` provided twice",
+                        ));
+                    }
+                } else {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "`return_arc` option not allowed here",
+                    ));
+                }
+            } else if ident == "serde" {
+                if A::SERDE {
+                    if let Some(old) = std::mem::replace(&mut options.serde, Some(ident)) {
+                        return Err(syn::Error::new(old.span(), "option `serde` provided twice"));
+                    }
+                } else {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "`serde` option not allowed here",
+                    ));
+                }
+            } else if ident == "eviction" {
+                if A::EVICTION {
+                    let _eq = Equals::parse(input)?;
+                    let expr = syn::Expr::parse(input)?;
+                    if let Some(old) = std::mem::replace(&mut options.eviction, Some(expr)) {
+                        return Err(syn::Error::new_spanned(
+                            old,
+                            "option `eviction` provided twice",
+                        ));
+                    }
+                } else {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "`eviction` option not allowed here",
+                    ));
+                }
+            } else if ident == "estimated_size" {
+                if A::ESTIMATED_SIZE {
+                    if let Some(old) = std::mem::replace(&mut options.estimated_size, Some(ident)) {
+                        return Err(syn::Error::new(
+                            old.span(),
+                            "option `estimated_size` provided twice",
+                        ));
+                    }
+                } else {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "`estimated_size` option not allowed here",
+                    ));
+                }
+            } else if ident == "eq" {
+                if A::EQ {
+                    let _eq = Equals::parse(input)?;
+                    let path = syn::Path::parse(input)?;
+                    if let Some(old) = std::mem::replace(&mut options.eq, Some(path)) {
+                        return Err(syn::Error::new(old.span(), "option `eq` provided twice"));
+                    }
+                } else {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "`eq` option not allowed here",
+                    ));
+                }
+            } else if ident == "fingerprint" {
+                if A::FINGERPRINT {
+                    if let Some(old) = std::mem::replace(&mut options.fingerprint, Some(ident)) {
+                        return Err(syn::Error::new(
+                            old.span(),
+                            "option `fingerprint` provided twice",
+                        ));
+                    }
+                } else {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "`fingerprint` option not allowed here",
+                    ));
+                }
             } else {
                 return Err(syn::Error::new(
                     ident.span(),
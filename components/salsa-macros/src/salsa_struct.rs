@@ -50,6 +50,9 @@ pub(crate) trait SalsaStructAllowedOptions: AllowedOptions {
 
     /// Are `#[default]` fields allowed?
     const ALLOW_DEFAULT: bool;
+
+    /// Are `#[eq(..)]` fields allowed?
+    const ALLOW_EQ: bool;
 }
 
 pub(crate) struct SalsaField<'s> {
@@ -59,6 +62,7 @@ pub(crate) struct SalsaField<'s> {
     pub(crate) has_default_attr: bool,
     pub(crate) has_ref_attr: bool,
     pub(crate) has_no_eq_attr: bool,
+    pub(crate) eq_fn: Option<syn::Path>,
     get_name: syn::Ident,
     set_name: syn::Ident,
 }
@@ -71,6 +75,9 @@ pub(crate) const FIELD_OPTION_ATTRIBUTES: &[(&str, fn(&syn::Attribute, &mut Sals
     ("default", |_, ef| ef.has_default_attr = true),
     ("return_ref", |_, ef| ef.has_ref_attr = true),
     ("no_eq", |_, ef| ef.has_no_eq_attr = true),
+    ("eq", |attr, ef| {
+        ef.eq_fn = Some(attr.parse_args().unwrap());
+    }),
     ("get", |attr, ef| {
         ef.get_name = attr.parse_args().unwrap();
     }),
@@ -105,6 +112,7 @@ where
 
         this.maybe_disallow_id_fields()?;
         this.maybe_disallow_default_fields()?;
+        this.maybe_disallow_eq_fields()?;
 
         this.check_generics()?;
 
@@ -169,15 +177,48 @@ where
         Ok(())
     }
 
+    /// Disallow `#[eq(..)]` fields for this kind of struct, and together with `#[no_eq]`.
+    fn maybe_disallow_eq_fields(&self) -> syn::Result<()> {
+        for ef in &self.fields {
+            let Some(eq_fn) = &ef.eq_fn else {
+                continue;
+            };
+
+            if !A::ALLOW_EQ {
+                return Err(syn::Error::new_spanned(
+                    eq_fn,
+                    format!("`#[eq]` cannot be used with `#[salsa::{}]`", A::KIND),
+                ));
+            }
+
+            if ef.has_no_eq_attr {
+                return Err(syn::Error::new_spanned(
+                    eq_fn,
+                    "`#[eq]` and `#[no_eq]` cannot be used together",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Check that the generic parameters look as expected for this kind of struct.
     fn check_generics(&self) -> syn::Result<()> {
         if A::HAS_LIFETIME {
             db_lifetime::require_db_lifetime(&self.struct_item.generics)
         } else {
-            db_lifetime::require_no_generics(&self.struct_item.generics)
+            db_lifetime::require_no_lifetime(&self.struct_item.generics)
         }
     }
 
+    pub(crate) fn type_params(&self) -> Vec<&syn::Ident> {
+        db_lifetime::type_params(&self.struct_item.generics)
+    }
+
+    pub(crate) fn where_predicates(&self) -> Vec<TokenStream> {
+        db_lifetime::where_predicates(&self.struct_item.generics)
+    }
+
     pub(crate) fn field_ids(&self) -> Vec<&syn::Ident> {
         self.fields
             .iter()
@@ -252,10 +293,12 @@ where
                     syn::Ident::new("clone", Span::call_site())
                 };
 
-                let backdate_ident = if f.has_no_eq_attr {
-                    syn::Ident::new("no_backdate", Span::call_site())
+                let backdate = if let Some(eq_fn) = &f.eq_fn {
+                    quote!((backdate_with #eq_fn))
+                } else if f.has_no_eq_attr {
+                    quote!(no_backdate)
                 } else {
-                    syn::Ident::new("backdate", Span::call_site())
+                    quote!(backdate)
                 };
 
                 let default_ident = if f.has_default_attr {
@@ -264,7 +307,7 @@ where
                     syn::Ident::new("required", Span::call_site())
                 };
 
-                quote!((#clone_ident, #backdate_ident, #default_ident))
+                quote!((#clone_ident, #backdate, #default_ident))
             })
             .collect()
     }
@@ -272,6 +315,10 @@ where
     pub fn generate_debug_impl(&self) -> bool {
         self.args.no_debug.is_none()
     }
+
+    pub fn generate_serde_impls(&self) -> bool {
+        self.args.serde.is_some()
+    }
 }
 
 impl<'s> SalsaField<'s> {
@@ -296,6 +343,7 @@ impl<'s> SalsaField<'s> {
             has_ref_attr: false,
             has_default_attr: false,
             has_no_eq_attr: false,
+            eq_fn: None,
             get_name,
             set_name,
         };
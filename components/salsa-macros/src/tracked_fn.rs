@@ -43,6 +43,20 @@ impl crate::options::AllowedOptions for TrackedFn {
     const LRU: bool = true;
 
     const CONSTRUCTOR_NAME: bool = false;
+
+    const CATCH_PANIC: bool = true;
+
+    const RETURN_ARC: bool = true;
+
+    const SERDE: bool = false;
+
+    const EVICTION: bool = true;
+
+    const ESTIMATED_SIZE: bool = true;
+
+    const EQ: bool = true;
+
+    const FINGERPRINT: bool = true;
 }
 
 struct Macro {
@@ -64,16 +78,40 @@ impl Macro {
         let fn_name = &item.sig.ident;
         let vis = &item.vis;
         let db_lt = db_lifetime::db_lifetime(&item.sig.generics);
+        let type_params = db_lifetime::type_params(&item.sig.generics);
+        let where_predicates = db_lifetime::where_predicates(&item.sig.generics);
         let input_ids = self.input_ids(&item);
         let input_tys = self.input_tys(&item)?;
         let output_ty = self.output_ty(&db_lt, &item)?;
         let (cycle_recovery_fn, cycle_recovery_strategy) = self.cycle_recovery();
         let is_specifiable = self.args.specify.is_some();
+        if let (Some(_), Some(eq)) = (&self.args.no_eq, &self.args.eq) {
+            return Err(syn::Error::new_spanned(
+                eq,
+                "the `no_eq` and `eq` options cannot be used together",
+            ));
+        }
+        if let (Some(_), Some(fingerprint)) = (&self.args.no_eq, &self.args.fingerprint) {
+            return Err(syn::Error::new_spanned(
+                fingerprint,
+                "the `no_eq` and `fingerprint` options cannot be used together",
+            ));
+        }
+
         let no_eq = self.args.no_eq.is_some();
 
+        let eq = match &self.args.eq {
+            Some(path) => quote!(#path),
+            None => quote!(salsa::plumbing::should_backdate_value),
+        };
+
+        let estimated_size = self.args.estimated_size.is_some();
+        let fingerprint = self.args.fingerprint.is_some();
+
         let mut inner_fn = item.clone();
         inner_fn.vis = syn::Visibility::Inherited;
         inner_fn.sig.ident = self.hygiene.ident("inner");
+        db_lifetime::add_static_bounds(&mut inner_fn.sig.generics);
 
         let zalsa = self.hygiene.ident("zalsa");
         let Configuration = self.hygiene.ident("Configuration");
@@ -103,15 +141,42 @@ impl Macro {
             ));
         }
 
+        if let (Some(_), Some(eviction)) = (&self.args.lru, &self.args.eviction) {
+            return Err(syn::Error::new_spanned(
+                eviction,
+                "the `lru` and `eviction` options cannot be used together",
+            ));
+        }
+
+        if let (Some(eviction), Some(_)) = (&self.args.eviction, &self.args.specify) {
+            return Err(syn::Error::new_spanned(
+                eviction,
+                "the `specify` and `eviction` options cannot be used together",
+            ));
+        }
+
         let needs_interner = match function_type {
             FunctionType::Constant | FunctionType::RequiresInterning => true,
             FunctionType::SalsaStruct => false,
         };
 
+        if let (Some(_), Some(token)) = (&self.args.return_ref, &self.args.return_arc) {
+            return Err(syn::Error::new_spanned(
+                token,
+                "the `return_ref` and `return_arc` options cannot be used together",
+            ));
+        }
+
         let lru = Literal::usize_unsuffixed(self.args.lru.unwrap_or(0));
 
+        let eviction = self.args.eviction.iter();
+
         let return_ref: bool = self.args.return_ref.is_some();
 
+        let return_arc: bool = self.args.return_arc.is_some();
+
+        let catch_panic: bool = self.args.catch_panic.is_some();
+
         Ok(crate::debug::dump_tokens(
             fn_name,
             quote![salsa::plumbing::setup_tracked_fn! {
@@ -119,6 +184,8 @@ impl Macro {
                 vis: #vis,
                 fn_name: #fn_name,
                 db_lt: #db_lt,
+                type_params: [#(#type_params),*],
+                where_clause: [#(#where_predicates,)*],
                 Db: #db_path,
                 db: #db_ident,
                 input_ids: [#(#input_ids),*],
@@ -129,9 +196,15 @@ impl Macro {
                 cycle_recovery_strategy: #cycle_recovery_strategy,
                 is_specifiable: #is_specifiable,
                 no_eq: #no_eq,
+                eq: #eq,
+                estimated_size: #estimated_size,
+                fingerprint: #fingerprint,
                 needs_interner: #needs_interner,
                 lru: #lru,
+                eviction: [#(#eviction)*],
                 return_ref: #return_ref,
+                return_arc: #return_arc,
+                catch_panic: #catch_panic,
                 unused_names: [
                     #zalsa,
                     #Configuration,
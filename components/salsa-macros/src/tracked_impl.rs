@@ -282,6 +282,16 @@ impl Macro {
                 ));
             };
         }
+        if let Some(return_arc) = &args.return_arc {
+            if let syn::ReturnType::Type(_, t) = &mut sig.output {
+                **t = parse_quote!(std::sync::Arc<#t>)
+            } else {
+                return Err(syn::Error::new_spanned(
+                    return_arc,
+                    "return_arc attribute requires explicit return type",
+                ));
+            };
+        }
         Ok(())
     }
 }
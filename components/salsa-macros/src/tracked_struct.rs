@@ -50,6 +50,20 @@ impl crate::options::AllowedOptions for TrackedStruct {
     const LRU: bool = false;
 
     const CONSTRUCTOR_NAME: bool = true;
+
+    const CATCH_PANIC: bool = false;
+
+    const RETURN_ARC: bool = false;
+
+    const SERDE: bool = true;
+
+    const EVICTION: bool = false;
+
+    const ESTIMATED_SIZE: bool = false;
+
+    const EQ: bool = false;
+
+    const FINGERPRINT: bool = false;
 }
 
 impl SalsaStructAllowedOptions for TrackedStruct {
@@ -59,6 +73,8 @@ impl SalsaStructAllowedOptions for TrackedStruct {
 
     const HAS_LIFETIME: bool = true;
 
+    const ALLOW_EQ: bool = true;
+
     const ALLOW_DEFAULT: bool = false;
 }
 
@@ -77,6 +93,8 @@ impl Macro {
         let vis = &self.struct_item.vis;
         let struct_ident = &self.struct_item.ident;
         let db_lt = db_lifetime::db_lifetime(&self.struct_item.generics);
+        let type_params = salsa_struct.type_params();
+        let where_predicates = salsa_struct.where_predicates();
         let new_fn = salsa_struct.constructor_name();
         let field_ids = salsa_struct.field_ids();
         let field_vis = salsa_struct.field_vis();
@@ -87,10 +105,12 @@ impl Macro {
         let field_options = salsa_struct.field_options();
         let field_tys = salsa_struct.field_tys();
         let generate_debug_impl = salsa_struct.generate_debug_impl();
+        let generate_serde_impls = salsa_struct.generate_serde_impls();
 
         let zalsa = self.hygiene.ident("zalsa");
         let zalsa_struct = self.hygiene.ident("zalsa_struct");
         let Configuration = self.hygiene.ident("Configuration");
+        let HasConfiguration = self.hygiene.ident("HasConfiguration");
         let CACHE = self.hygiene.ident("CACHE");
         let Db = self.hygiene.ident("Db");
         let NonNull = self.hygiene.ident("NonNull");
@@ -103,6 +123,8 @@ impl Macro {
                     attrs: [#(#attrs),*],
                     vis: #vis,
                     Struct: #struct_ident,
+                    type_params: [#(#type_params),*],
+                    where_clause: [#(#where_predicates,)*],
                     db_lt: #db_lt,
                     new_fn: #new_fn,
                     field_ids: [#(#field_ids),*],
@@ -113,10 +135,12 @@ impl Macro {
                     field_options: [#(#field_options),*],
                     num_fields: #num_fields,
                     generate_debug_impl: #generate_debug_impl,
+                    generate_serde_impls: #generate_serde_impls,
                     unused_names: [
                         #zalsa,
                         #zalsa_struct,
                         #Configuration,
+                        #HasConfiguration,
                         #CACHE,
                         #Db,
                         #NonNull,
@@ -73,8 +73,19 @@ pub(crate) fn update_derive(input: syn::DeriveInput) -> syn::Result<TokenStream>
         })
         .collect();
 
+    // Each type parameter has to be `Update` for the fields that mention it to be;
+    // the fallback for other types requires `'static`, which we cannot assume here.
+    let mut generics = input.generics.clone();
+    let type_params: Vec<_> = generics.type_params().map(|tp| tp.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for ident in type_params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#ident: salsa::Update));
+    }
+
     let ident = &input.ident;
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let tokens = quote! {
         #[allow(clippy::all)]
         unsafe impl #impl_generics salsa::Update for #ident #ty_generics #where_clause {
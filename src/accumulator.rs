@@ -1,30 +1,68 @@
 //! Basic test of accumulator functionality.
 
+use parking_lot::{Mutex, RwLock};
 use std::{
     fmt::{self, Debug},
     marker::PhantomData,
+    sync::Arc,
 };
 
 use crate::{
     cycle::CycleRecoveryStrategy,
-    hash::FxDashMap,
+    hash::{FxDashMap, FxHashSet},
     ingredient::{fmt_index, Ingredient, Jar},
+    introspection::IngredientKind,
     key::DependencyIndex,
     plumbing::JarAux,
-    zalsa::IngredientIndex,
-    zalsa_local::{QueryOrigin, ZalsaLocal},
-    Database, DatabaseKeyIndex, Event, EventKind, Id, Revision,
+    zalsa::{IngredientIndex, Zalsa},
+    zalsa_local::{EdgeKind, QueryEdges, QueryOrigin},
+    Database, DatabaseKeyIndex, Durability, Event, EventKind, Id, QueryError, Revision,
 };
 
+/// Values that can be accumulated by tracked functions.
 pub trait Accumulator: Clone + Debug + Send + Sync + 'static + Sized {
     const DEBUG_NAME: &'static str;
 
+    /// True if a query that re-executed accumulated the same values as before,
+    /// in which case the queries reading them are not re-executed.
+    ///
+    /// Generated by `#[salsa::accumulator]`: compares the values with `PartialEq`
+    /// if the type implements it, otherwise the values are always considered changed.
+    fn values_eq(old: &[Self], new: &[Self]) -> bool;
+
     /// Accumulate an instance of this in the database for later retrieval.
     fn accumulate<Db>(self, db: &Db)
     where
         Db: ?Sized + Database;
 }
 
+/// This is used by the macro generated code, to compare accumulated values
+/// with `PartialEq` if it is implemented.
+///
+/// Uses the same ["method dispatch hack"](https://github.com/nvzqz/impls#how-it-works)
+/// as [`crate::update::helper`].
+pub mod helper {
+    use std::marker::PhantomData;
+
+    pub struct Dispatch<A>(PhantomData<A>);
+
+    impl<A: PartialEq> Dispatch<A> {
+        pub fn values_eq(old: &[A], new: &[A]) -> bool {
+            old == new
+        }
+    }
+
+    pub trait Fallback<A> {
+        fn values_eq(old: &[A], new: &[A]) -> bool;
+    }
+
+    impl<A> Fallback<A> for Dispatch<A> {
+        fn values_eq(_old: &[A], _new: &[A]) -> bool {
+            false
+        }
+    }
+}
+
 pub struct JarImpl<A: Accumulator> {
     phantom: PhantomData<A>,
 }
@@ -50,11 +88,113 @@ impl<A: Accumulator> Jar for JarImpl<A> {
 pub struct IngredientImpl<A: Accumulator> {
     index: IngredientIndex,
     map: FxDashMap<DatabaseKeyIndex, AccumulatedValues<A>>,
+
+    /// For each query whose accumulated values were requested (directly or as an
+    /// input of another query), the queries that accumulated them. These are revalidated
+    /// like memos, so that unchanged parts of the query graph are not walked again.
+    producers: FxDashMap<DatabaseKeyIndex, Producers>,
+
+    /// Ids for the queries whose accumulated values were read by another query,
+    /// recorded as the key of its dependency on this ingredient.
+    /// Once no memo depends on a query's values, its id is freed on sweep and reused.
+    root_ids: FxDashMap<DatabaseKeyIndex, Id>,
+    roots: RwLock<Vec<DatabaseKeyIndex>>,
+    free_root_ids: Mutex<Vec<Id>>,
 }
 
 struct AccumulatedValues<A> {
     produced_at: Revision,
     values: Vec<A>,
+
+    /// The values accumulated by the previous execution of the query,
+    /// until they are compared with `values`.
+    previous: Option<Vec<A>>,
+
+    /// Revision in which `values` last changed.
+    changed_at: Revision,
+}
+
+impl<A: Accumulator> AccumulatedValues<A> {
+    /// Returns the revision in which the values last changed, comparing them
+    /// with those of the previous execution of the query if needed.
+    ///
+    /// Must only be invoked once the query has finished executing.
+    fn changed_at(&mut self) -> Revision {
+        if let Some(previous) = self.previous.take() {
+            if !A::values_eq(&previous, &self.values) {
+                self.changed_at = self.produced_at;
+            }
+        }
+        self.changed_at
+    }
+}
+
+#[derive(Clone)]
+struct Producers {
+    /// The queries that accumulated values, in the order their values are returned.
+    queries: Arc<[DatabaseKeyIndex]>,
+
+    /// Revision in which the memo of the query was computed.
+    /// If it was recomputed since, the query may have accumulated other values
+    /// or read other inputs.
+    computed_at: Revision,
+
+    /// Minimum durability of the inputs of the query.
+    durability: Durability,
+
+    /// Last revision in which `queries` was verified to be up to date.
+    verified_at: Revision,
+
+    /// Revision in which the accumulated values last changed,
+    /// either because other queries produced them or because one of those
+    /// queries was recomputed.
+    changed_at: Revision,
+}
+
+/// Result of [`IngredientImpl::producers`].
+struct VerifiedProducers {
+    queries: Arc<[DatabaseKeyIndex]>,
+    durability: Durability,
+    changed_at: Revision,
+
+    /// False if some queries were skipped because they were already in progress.
+    complete: bool,
+}
+
+/// Result of [`IngredientImpl::start_producers`].
+enum StartProducers {
+    Done(Option<VerifiedProducers>),
+    Walk(ProducersFrame),
+}
+
+/// A query whose inputs are being visited by [`IngredientImpl::producers`].
+struct ProducersFrame {
+    query: DatabaseKeyIndex,
+    computed_at: Revision,
+    durability: Durability,
+    cached: Option<Producers>,
+
+    /// True if the query was recomputed since `cached` was verified.
+    recomputed: bool,
+
+    inputs: Vec<DatabaseKeyIndex>,
+
+    /// Number of `inputs` visited so far.
+    visited: usize,
+
+    /// The producers of the visited inputs that are memoized.
+    producers: Vec<VerifiedProducers>,
+}
+
+impl Producers {
+    fn verified(&self) -> VerifiedProducers {
+        VerifiedProducers {
+            queries: self.queries.clone(),
+            durability: self.durability,
+            changed_at: self.changed_at,
+            complete: true,
+        }
+    }
 }
 
 impl<A: Accumulator> IngredientImpl<A> {
@@ -73,6 +213,10 @@ impl<A: Accumulator> IngredientImpl<A> {
     pub fn new(index: IngredientIndex) -> Self {
         Self {
             map: FxDashMap::default(),
+            producers: FxDashMap::default(),
+            root_ids: FxDashMap::default(),
+            roots: RwLock::default(),
+            free_root_ids: Mutex::default(),
             index,
         }
     }
@@ -94,39 +238,355 @@ impl<A: Accumulator> IngredientImpl<A> {
             }
         };
 
-        let mut accumulated_values = self.map.entry(active_query).or_insert(AccumulatedValues {
-            values: vec![],
-            produced_at: current_revision,
-        });
-
         // When we call `push' in a query, we will add the accumulator to the output of the query.
         // If we find here that this accumulator is not the output of the query,
         // we can say that the accumulated values we stored for this query is out of date.
-        if !state.is_output_of_active_query(self.dependency_index()) {
-            accumulated_values.values.truncate(0);
-            accumulated_values.produced_at = current_revision;
-        }
+        // Keep them to compare them with the new values once the query has executed.
+        let mut accumulated_values = match self.map.entry(active_query) {
+            dashmap::mapref::entry::Entry::Occupied(entry) => {
+                let mut accumulated_values = entry.into_ref();
+                if !state.is_output_of_active_query(self.dependency_index()) {
+                    accumulated_values.changed_at();
+                    accumulated_values.previous =
+                        Some(std::mem::take(&mut accumulated_values.values));
+                    accumulated_values.produced_at = current_revision;
+                }
+                accumulated_values
+            }
+            dashmap::mapref::entry::Entry::Vacant(entry) => entry.insert(AccumulatedValues {
+                values: vec![],
+                produced_at: current_revision,
+                previous: None,
+                changed_at: current_revision,
+            }),
+        };
 
         state.add_output(self.dependency_index());
         accumulated_values.values.push(value);
     }
 
-    pub(crate) fn produced_by(
+    /// Returns the values accumulated by `query` and the queries it (transitively) read.
+    /// The memo for `query` must be up to date in the current revision.
+    ///
+    /// The values are recorded as read by the active query (if any), so that it only
+    /// re-executes when they change.
+    pub(crate) fn accumulated_by(&self, db: &dyn Database, query: DatabaseKeyIndex) -> Vec<A> {
+        let current_revision = db.zalsa().current_revision();
+        let mut output = vec![];
+
+        if let Some(producers) = self.producers(db, query) {
+            for &producer in producers.queries.iter() {
+                self.produced_by(current_revision, producer, &mut output);
+            }
+            self.report_read(db, query, &producers);
+        }
+
+        output
+    }
+
+    /// Like [`Self::accumulated_by`], but also returns the query that accumulated each value.
+    ///
+    /// `filter` is invoked for each query reached from `query`; if it returns false,
+    /// the values of that query and of the queries only reached through it are skipped.
+    pub(crate) fn accumulated_with_origin(
+        &self,
+        db: &dyn Database,
+        query: DatabaseKeyIndex,
+        filter: &mut dyn FnMut(DatabaseKeyIndex) -> bool,
+    ) -> Vec<(DatabaseKeyIndex, A)> {
+        let zalsa = db.zalsa();
+        let current_revision = zalsa.current_revision();
+        let mut output = vec![];
+
+        let Some(producers) = self.producers(db, query) else {
+            return output;
+        };
+        self.report_read(db, query, &producers);
+
+        // The cached lists of producers do not tell which query reached each producer,
+        // so walk the queries again, but only those that (transitively) accumulated
+        // values: bringing the lists up to date cached them for the queries walked.
+        let mut values = vec![];
+        let mut visited: FxHashSet<DatabaseKeyIndex> = FxHashSet::default();
+        let mut stack: Vec<DatabaseKeyIndex> = vec![query];
+
+        while let Some(k) = stack.pop() {
+            if !visited.insert(k) || (k != query && !filter(k)) {
+                continue;
+            }
+            if self.produced_nothing(db, k) {
+                continue;
+            }
+
+            self.produced_by(current_revision, k, &mut values);
+            output.extend(values.drain(..).map(|value| (k, value)));
+
+            let origin = zalsa
+                .lookup_ingredient(k.ingredient_index)
+                .origin(db, k.key_index);
+            let inputs = origin.iter().flat_map(|origin| origin.inputs());
+            // Careful: we want to push in execution order, so reverse order to
+            // ensure the first child that was executed will be the first child popped
+            // from the stack.
+            stack.extend(inputs.flat_map(DatabaseKeyIndex::try_from).rev());
+        }
+
+        output
+    }
+
+    /// True if `query` and its inputs are known to have accumulated no values,
+    /// because their list of producers was verified in this revision and is empty.
+    fn produced_nothing(&self, db: &dyn Database, query: DatabaseKeyIndex) -> bool {
+        let zalsa = db.zalsa();
+        let Some(producers) = self.producers.get(&query) else {
+            return false;
+        };
+        producers.verified_at == zalsa.current_revision()
+            && producers.queries.is_empty()
+            && zalsa
+                .lookup_ingredient(query.ingredient_index)
+                .computed_at(db, query.key_index)
+                .is_some_and(|(computed_at, _)| computed_at == producers.computed_at)
+    }
+
+    /// Records that the active query (if any) read the values accumulated by `query`,
+    /// so that it only re-executes when they change.
+    fn report_read(
+        &self,
+        db: &dyn Database,
+        query: DatabaseKeyIndex,
+        producers: &VerifiedProducers,
+    ) {
+        let zalsa_local = db.zalsa_local();
+        if producers.complete {
+            zalsa_local.report_tracked_read(
+                self.root_dependency_index(query),
+                producers.durability,
+                producers.changed_at,
+            );
+        } else {
+            zalsa_local.report_untracked_read(db.zalsa().current_revision());
+        }
+    }
+
+    /// The dependency of a query that read the values accumulated by `query`.
+    fn root_dependency_index(&self, query: DatabaseKeyIndex) -> DependencyIndex {
+        let id = *self.root_ids.entry(query).or_insert_with(|| {
+            let mut roots = self.roots.write();
+            match self.free_root_ids.lock().pop() {
+                Some(id) => {
+                    roots[id.as_u32() as usize] = query;
+                    id
+                }
+                None => {
+                    roots.push(query);
+                    Id::from_u32((roots.len() - 1) as u32)
+                }
+            }
+        });
+        DependencyIndex {
+            ingredient_index: self.index,
+            key_index: Some(id),
+        }
+    }
+
+    /// The query whose accumulated values were read with the dependency key `id`.
+    fn root(&self, id: Id) -> DatabaseKeyIndex {
+        self.roots.read()[id.as_u32() as usize]
+    }
+
+    /// Returns the queries that accumulated values into `query` or its inputs, in
+    /// execution order, or `None` if `query` is not memoized (e.g., it is an input field).
+    ///
+    /// Like `deep_verify_memo`, the cached list is reused if `query` has not been recomputed
+    /// and none of its inputs' lists changed since it was last verified.
+    ///
+    /// Query graphs can be very deep, so they are walked with an explicit stack of the
+    /// queries whose inputs are being visited rather than by recursion. Those queries can
+    /// occur again in the inputs of queries that recovered from a cycle.
+    fn producers(&self, db: &dyn Database, query: DatabaseKeyIndex) -> Option<VerifiedProducers> {
+        let mut in_progress: FxHashSet<DatabaseKeyIndex> = FxHashSet::default();
+        let mut stack = match self.start_producers(db, query, &in_progress) {
+            StartProducers::Done(producers) => return producers,
+            StartProducers::Walk(frame) => vec![frame],
+        };
+        in_progress.insert(query);
+
+        loop {
+            let frame = stack.last_mut().unwrap();
+            if let Some(&input) = frame.inputs.get(frame.visited) {
+                frame.visited += 1;
+                match self.start_producers(db, input, &in_progress) {
+                    StartProducers::Done(producers) => frame.producers.extend(producers),
+                    StartProducers::Walk(frame) => {
+                        in_progress.insert(input);
+                        stack.push(frame);
+                    }
+                }
+                continue;
+            }
+
+            let frame = stack.pop().unwrap();
+            in_progress.remove(&frame.query);
+            let producers = self.finish_producers(db, frame);
+            match stack.last_mut() {
+                Some(parent) => parent.producers.push(producers),
+                None => return Some(producers),
+            }
+        }
+    }
+
+    /// Returns the producers of `query` if they can be determined without visiting
+    /// its inputs, otherwise the frame to visit them.
+    fn start_producers(
+        &self,
+        db: &dyn Database,
+        query: DatabaseKeyIndex,
+        in_progress: &FxHashSet<DatabaseKeyIndex>,
+    ) -> StartProducers {
+        let zalsa = db.zalsa();
+        let current_revision = zalsa.current_revision();
+        let ingredient = zalsa.lookup_ingredient(query.ingredient_index);
+        let Some((computed_at, durability)) = ingredient.computed_at(db, query.key_index) else {
+            return StartProducers::Done(None);
+        };
+
+        if in_progress.contains(&query) {
+            return StartProducers::Done(Some(VerifiedProducers {
+                queries: Arc::new([]),
+                durability,
+                changed_at: current_revision,
+                complete: false,
+            }));
+        }
+
+        // Clone the cached entry so that we do not hold a lock on the map while walking.
+        let cached = self.producers.get(&query).map(|p| p.clone());
+        let recomputed = cached.as_ref().is_none_or(|p| p.computed_at != computed_at);
+
+        if let Some(cached) = cached.as_ref().filter(|_| !recomputed) {
+            if cached.verified_at == current_revision
+                || zalsa.last_changed_revision(cached.durability) <= cached.verified_at
+            {
+                self.mark_verified(query, current_revision);
+                return StartProducers::Done(Some(cached.verified()));
+            }
+        }
+
+        let origin = ingredient.origin(db, query.key_index);
+        let inputs = origin.iter().flat_map(|origin| origin.inputs());
+        StartProducers::Walk(ProducersFrame {
+            query,
+            computed_at,
+            durability,
+            cached,
+            recomputed,
+            inputs: inputs.flat_map(DatabaseKeyIndex::try_from).collect(),
+            visited: 0,
+            producers: vec![],
+        })
+    }
+
+    /// Returns the producers of `frame.query`, once those of its inputs are known.
+    fn finish_producers(&self, db: &dyn Database, frame: ProducersFrame) -> VerifiedProducers {
+        let current_revision = db.zalsa().current_revision();
+        let ProducersFrame {
+            query,
+            computed_at,
+            durability,
+            cached,
+            recomputed,
+            producers: inputs,
+            ..
+        } = frame;
+
+        let inputs_unchanged = |cached: &Producers| {
+            inputs
+                .iter()
+                .all(|input| input.complete && input.changed_at <= cached.verified_at)
+        };
+        if let Some(cached) = cached.as_ref().filter(|_| !recomputed) {
+            if inputs_unchanged(cached) {
+                self.mark_verified(query, current_revision);
+                return cached.verified();
+            }
+        }
+
+        // Collect the producers of the inputs, skipping those we have already seen,
+        // as the values accumulated by each query are only returned once.
+        let mut queries = vec![];
+        let mut seen = FxHashSet::default();
+        let produces = self.map.contains_key(&query);
+        if produces {
+            seen.insert(query);
+            queries.push(query);
+        }
+        let mut complete = true;
+        for input in &inputs {
+            complete &= input.complete;
+            queries.extend(input.queries.iter().filter(|&&q| seen.insert(q)));
+        }
+        let queries: Arc<[DatabaseKeyIndex]> = queries.into();
+
+        // Like a backdated memo, the values have not changed if the same queries
+        // produced them and, if the query itself was recomputed, it accumulated
+        // the same values as before.
+        let own_values_unchanged = |cached: &Producers| {
+            !recomputed
+                || self
+                    .map
+                    .get_mut(&query)
+                    .is_none_or(|mut v| v.changed_at() <= cached.verified_at)
+        };
+        let changed_at = match &cached {
+            Some(cached)
+                if cached.queries == queries
+                    && inputs_unchanged(cached)
+                    && own_values_unchanged(cached) =>
+            {
+                cached.changed_at
+            }
+            _ => current_revision,
+        };
+
+        if complete {
+            self.producers.insert(
+                query,
+                Producers {
+                    queries: queries.clone(),
+                    computed_at,
+                    durability,
+                    verified_at: current_revision,
+                    changed_at,
+                },
+            );
+        }
+
+        VerifiedProducers {
+            queries,
+            durability,
+            changed_at,
+            complete,
+        }
+    }
+
+    fn mark_verified(&self, query: DatabaseKeyIndex, current_revision: Revision) {
+        if let Some(mut p) = self.producers.get_mut(&query) {
+            p.verified_at = current_revision;
+        }
+    }
+
+    fn produced_by(
         &self,
         current_revision: Revision,
-        local_state: &ZalsaLocal,
         query: DatabaseKeyIndex,
         output: &mut Vec<A>,
     ) {
         if let Some(v) = self.map.get(&query) {
-            // FIXME: We don't currently have a good way to identify the value that was read.
-            // You can't report is as a tracked read of `query`, because the return value of query is not being read here --
-            // instead it is the set of values accumuated by `query`.
-            local_state.report_untracked_read(current_revision);
-
             let AccumulatedValues {
                 values,
                 produced_at,
+                ..
             } = v.value();
 
             if *produced_at == current_revision {
@@ -143,21 +603,55 @@ impl<A: Accumulator> Ingredient for IngredientImpl<A> {
 
     fn maybe_changed_after(
         &self,
-        _db: &dyn Database,
-        _input: Option<Id>,
-        _revision: Revision,
-    ) -> bool {
-        panic!("nothing should ever depend on an accumulator directly")
+        db: &dyn Database,
+        input: Option<Id>,
+        revision: Revision,
+    ) -> Result<bool, QueryError> {
+        let query = self.root(input.unwrap());
+        let zalsa = db.zalsa();
+        let ingredient = zalsa.lookup_ingredient(query.ingredient_index);
+
+        // First bring the memo of the query up to date, re-executing it if needed.
+        // If its value changed but it was not re-executed, the memo may be stale
+        // (e.g., if its value was evicted), so assume the accumulated values changed too.
+        if ingredient.maybe_changed_after(db, Some(query.key_index), revision)? {
+            let computed_at = ingredient.computed_at(db, query.key_index);
+            if computed_at.is_none_or(|(r, _)| r != zalsa.current_revision()) {
+                return Ok(true);
+            }
+        }
+
+        Ok(match self.producers(db, query) {
+            Some(producers) => !producers.complete || producers.changed_at > revision,
+            None => true,
+        })
     }
 
     fn cycle_recovery_strategy(&self) -> CycleRecoveryStrategy {
         CycleRecoveryStrategy::Panic
     }
 
-    fn origin(&self, _db: &dyn Database, _key_index: crate::Id) -> Option<QueryOrigin> {
-        None
+    /// A query that read the values accumulated by another query depends on
+    /// that query, so that the values it accumulated are included in its own.
+    fn origin(&self, _db: &dyn Database, key_index: crate::Id) -> Option<QueryOrigin> {
+        let query = self.root(key_index);
+        let edges = QueryEdges::new(Arc::new([(EdgeKind::Input, query.into())]));
+        Some(QueryOrigin::Derived(edges))
+    }
+
+    fn computed_at(
+        &self,
+        db: &dyn Database,
+        key_index: crate::Id,
+    ) -> Option<(Revision, Durability)> {
+        let query = self.root(key_index);
+        db.zalsa()
+            .lookup_ingredient(query.ingredient_index)
+            .computed_at(db, query.key_index)
     }
 
+    fn evict_value(&self, _zalsa: &Zalsa, _key_index: crate::Id) {}
+
     fn mark_validated_output(
         &self,
         db: &dyn Database,
@@ -168,6 +662,8 @@ impl<A: Accumulator> Ingredient for IngredientImpl<A> {
         let current_revision = db.zalsa().current_revision();
         if let Some(mut v) = self.map.get_mut(&executor) {
             // The value is still valid in the new revision.
+            // Settle any pending comparison against the revision the values were produced in.
+            v.changed_at();
             v.produced_at = current_revision;
         }
     }
@@ -205,6 +701,42 @@ impl<A: Accumulator> Ingredient for IngredientImpl<A> {
     fn debug_name(&self) -> &'static str {
         A::DEBUG_NAME
     }
+
+    fn kind(&self) -> IngredientKind {
+        IngredientKind::Accumulator
+    }
+
+    fn live_entries(&self, _zalsa: &Zalsa) -> usize {
+        self.map.iter().map(|entry| entry.values.len()).sum()
+    }
+
+    fn lru_capacity(&self) -> Option<usize> {
+        None
+    }
+
+    fn sweep(&self, db: &dyn Database, kept_inputs: &FxHashSet<DependencyIndex>) {
+        let zalsa = db.zalsa();
+        self.producers.retain(|query, _| {
+            zalsa
+                .lookup_ingredient(query.ingredient_index)
+                .computed_at(db, query.key_index)
+                .is_some()
+        });
+
+        // No memo depends on the roots that are not among the inputs of the kept memos,
+        // so their ids can be given to other queries.
+        let mut free_root_ids = self.free_root_ids.lock();
+        self.root_ids.retain(|_, &mut id| {
+            let read = kept_inputs.contains(&DependencyIndex {
+                ingredient_index: self.index,
+                key_index: Some(id),
+            });
+            if !read {
+                free_root_ids.push(id);
+            }
+            read
+        });
+    }
 }
 
 impl<A> std::fmt::Debug for IngredientImpl<A>
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use rustc_hash::FxHashMap;
 
 use crate::{
@@ -49,6 +51,9 @@ pub(crate) struct ActiveQuery {
     /// Map from tracked struct keys (which include the hash + disambiguator) to their
     /// final id.
     pub(crate) tracked_struct_ids: FxHashMap<KeyStruct, Id>,
+
+    /// Time spent executing the queries executed by this one, see `ActiveQueryGuard::pop_executed`.
+    pub(crate) nested_execution_time: Duration,
 }
 
 impl ActiveQuery {
@@ -62,6 +67,7 @@ impl ActiveQuery {
             cycle: None,
             disambiguator_map: Default::default(),
             tracked_struct_ids: Default::default(),
+            nested_execution_time: Duration::ZERO,
         }
     }
 
@@ -131,6 +137,21 @@ impl ActiveQuery {
             .extend(other.input_outputs.iter().copied());
     }
 
+    /// Adds the reads of `other`, a query that unwound while executing on behalf of `self`.
+    /// Its outputs are left out, they are not outputs of `self`.
+    pub(super) fn add_reads_from(&mut self, other: &ActiveQuery) {
+        self.changed_at = self.changed_at.max(other.changed_at);
+        self.durability = self.durability.min(other.durability);
+        self.untracked_read |= other.untracked_read;
+        self.input_outputs.extend(
+            other
+                .input_outputs
+                .iter()
+                .filter(|(kind, _)| *kind == EdgeKind::Input)
+                .copied(),
+        );
+    }
+
     /// Removes the participants in `cycle` from my dependencies.
     /// Used during cycle recovery, see [`Runtime::unblock_cycle_and_maybe_throw`].
     pub(super) fn remove_cycle_participants(&mut self, cycle: &Cycle) {
@@ -1,5 +1,5 @@
 use crate::{key::DatabaseKeyIndex, Database};
-use std::{panic::AssertUnwindSafe, sync::Arc};
+use std::sync::Arc;
 
 /// Captures the participants of a cycle that occurred when executing a query.
 ///
@@ -38,16 +38,6 @@ impl Cycle {
         std::panic::resume_unwind(Box::new(self))
     }
 
-    pub(crate) fn catch<T>(execute: impl FnOnce() -> T) -> Result<T, Cycle> {
-        match std::panic::catch_unwind(AssertUnwindSafe(execute)) {
-            Ok(v) => Ok(v),
-            Err(err) => match err.downcast::<Cycle>() {
-                Ok(cycle) => Err(*cycle),
-                Err(other) => std::panic::resume_unwind(other),
-            },
-        }
-    }
-
     /// Iterate over the [`DatabaseKeyIndex`] for each query participating
     /// in the cycle. The start point of this iteration within the cycle
     /// is arbitrary but deterministic, but the ordering is otherwise determined
@@ -1,8 +1,10 @@
-use std::{any::Any, borrow::Cow};
+use std::{any::Any, borrow::Cow, thread::JoinHandle};
 
 use crate::{
+    external,
     zalsa::{IngredientIndex, ZalsaDatabase},
-    Durability, Event, Revision,
+    Cancelled, Durability, Event, ExternalKey, IngredientInfo, InputChange, Revision, SweepOptions,
+    SweepReport, WatchId,
 };
 
 /// The trait implemented by all Salsa databases.
@@ -29,6 +31,156 @@ pub trait Database: Send + AsDynDatabase + Any + ZalsaDatabase {
     fn synthetic_write(&mut self, durability: Durability) {
         let zalsa_mut = self.zalsa_mut();
         zalsa_mut.report_tracked_write(durability);
+
+        #[cfg(feature = "serde")]
+        zalsa_mut
+            .recorder()
+            .record(|| crate::RecordedEvent::SyntheticWrite {
+                durability: durability.index() as u8,
+            });
+    }
+
+    /// Starts recording the creation of inputs, the writes to their fields and the synthetic writes,
+    /// so that they can be replayed on another database with [`Replay`](`crate::Replay`).
+    /// Only inputs with the `serde` option are recorded.
+    #[cfg(feature = "serde")]
+    fn start_recording(&self) {
+        self.zalsa().recorder().start()
+    }
+
+    /// Stops recording, and returns what was recorded since [`start_recording`](`Self::start_recording`).
+    #[cfg(feature = "serde")]
+    fn stop_recording(&self) -> crate::Recording {
+        self.zalsa().recorder().stop()
+    }
+
+    /// Limits the total size of the memoized values of tracked functions with the
+    /// `estimated_size` option to `budget` bytes (or removes the limit if `None`).
+    ///
+    /// Whenever a new revision starts and the budget is exceeded, salsa frees the values
+    /// that are the cheapest to recompute per byte, based on how long their function took
+    /// to execute, not counting the queries it executed in turn. As with LRU, the dependencies
+    /// of evicted values are kept.
+    ///
+    /// Values fetched during a revision may be referenced until it ends, so the budget is only
+    /// enforced when a new revision starts, including the one started by this method: the values
+    /// computed during a revision can exceed it until then.
+    ///
+    /// **WARNING:** Just like an ordinary write, this method triggers
+    /// cancellation.
+    fn set_memory_budget(&mut self, budget: Option<usize>) {
+        self.zalsa().memory_budget().set_budget(budget);
+
+        // Starting a new revision evicts values until the budget is respected.
+        self.zalsa_mut();
+    }
+
+    /// Enables or disables the from-scratch verification mode, meant for tests.
+    ///
+    /// In this mode, whenever the result of a tracked function is fetched from a memo
+    /// computed in an earlier revision, the function is executed again and salsa panics
+    /// if the new value differs from the memoized one, reporting the query, the chain of
+    /// queries that fetched it and the inputs it recorded. This catches functions that
+    /// are not deterministic or that read state without reporting it (e.g. with
+    /// [`report_untracked_read`](`Self::report_untracked_read`)).
+    ///
+    /// Each query is verified at most once per revision. Functions with the `no_eq` option,
+    /// or that create tracked structs or specify values, are not verified.
+    ///
+    /// **WARNING:** Just like an ordinary write, this method triggers
+    /// cancellation.
+    fn set_from_scratch_verification(&mut self, enabled: bool) {
+        let zalsa_mut = self.zalsa_mut();
+        zalsa_mut.verification().set_enabled(enabled);
+    }
+
+    /// Returns the writes to input fields made after `revision`, oldest first,
+    /// or `None` if some of them were dropped from the change log
+    /// (see [`set_change_log_capacity`](`Self::set_change_log_capacity`)).
+    fn changes_since(&self, revision: Revision) -> Option<Vec<InputChange>> {
+        self.zalsa().change_log().changes_since(revision)
+    }
+
+    /// Sets the number of writes to input fields kept in the change log (1024 by default),
+    /// dropping the oldest ones if needed.
+    ///
+    /// **WARNING:** Just like an ordinary write, this method triggers
+    /// cancellation.
+    fn set_change_log_capacity(&mut self, capacity: usize) {
+        let zalsa_mut = self.zalsa_mut();
+        zalsa_mut.change_log_mut().set_capacity(capacity);
+    }
+
+    /// Drops the memos of tracked functions that were not verified in the last
+    /// `options.older_than_revisions` revisions, along with their values and dependencies.
+    /// If a dropped memo is needed again, the function is re-executed.
+    ///
+    /// The values accumulated by a dropped memo are freed, and the tracked structs it created are
+    /// deleted, along with the memos attached to them. If some other memo may reference those
+    /// tracked structs (because it read them, or depends on the query that created them),
+    /// the memo is kept instead. Memos whose values were assigned with `specify`, or whose function
+    /// assigned values, are kept as well.
+    ///
+    /// The memos depending on a dropped memo keep their values, which are still up to date;
+    /// they are re-executed if they need to be verified again.
+    ///
+    /// **WARNING:** Just like an ordinary write, this method triggers
+    /// cancellation and starts a new revision.
+    fn sweep(&mut self, options: SweepOptions) -> SweepReport {
+        let zalsa_mut = self.zalsa_mut();
+        let (mut report, removed_memos, kept_inputs) = zalsa_mut.sweep(&options);
+
+        let db = self.as_dyn_database();
+        let zalsa = db.zalsa();
+        zalsa.discard_swept_outputs(db, removed_memos, &mut report);
+        for ingredient in zalsa.ingredients() {
+            ingredient.sweep(db, &kept_inputs);
+        }
+
+        report
+    }
+
+    /// Checks the results of the tracked functions watched with their generated `watch` function,
+    /// and passes the new result to the subscribers of those that changed since they last saw them.
+    /// The results are recomputed if needed, and the others are left alone.
+    ///
+    /// Salsa cannot tell when the inputs of a new revision have all been set,
+    /// so this should be invoked after applying each batch of changes.
+    fn notify_watchers(&self) {
+        let db = self.as_dyn_database();
+        db.zalsa().watches().notify(db)
+    }
+
+    /// Cancels a subscription created with the `watch` function of a tracked function.
+    /// Returns false if it was already cancelled.
+    fn unwatch(&self, id: WatchId) -> bool {
+        self.zalsa().watches().unwatch(id)
+    }
+
+    /// Brings the results of the hot queries (registered with the `register_hot` function
+    /// of tracked functions) up to date, re-executing them if needed, so that later
+    /// calls hit their memoized values.
+    ///
+    /// Returns `Err` as soon as a write to the database cancels this handle.
+    fn warm_hot_queries(&self) -> Result<(), Cancelled> {
+        let db = self.as_dyn_database();
+        db.zalsa().hot_queries().warm(db)
+    }
+
+    /// Like [`warm_hot_queries`](`Self::warm_hot_queries`), but on a clone of this handle
+    /// in a new thread. Invoke this after applying each batch of changes: the next write
+    /// cancels the thread, and waits until it has stopped.
+    fn warm_hot_queries_in_background(&self) -> JoinHandle<Result<(), Cancelled>>
+    where
+        Self: Sized + Clone,
+    {
+        let db = self.clone();
+        std::thread::spawn(move || db.warm_hot_queries())
+    }
+
+    /// Unregisters all hot queries.
+    fn clear_hot_queries(&self) {
+        self.zalsa().hot_queries().clear()
     }
 
     /// Reports that the query depends on some state unknown to salsa.
@@ -41,6 +193,33 @@ pub trait Database: Send + AsDynDatabase + Any + ZalsaDatabase {
         zalsa_local.report_untracked_read(db.zalsa().current_revision())
     }
 
+    /// Reports that the query depends on the state outside of the database identified by `key`
+    /// (e.g., an environment variable or the metadata of a file).
+    ///
+    /// Unlike [`report_untracked_read`](`Self::report_untracked_read`), the query is only
+    /// re-executed in the revisions following a call to [`invalidate_external`](`Self::invalidate_external`)
+    /// with the same key.
+    fn report_external_read(&self, key: &dyn ExternalKey) {
+        let db = self.as_dyn_database();
+        external::IngredientImpl::from_zalsa(db.zalsa()).report_read(db, key)
+    }
+
+    /// Reports that the state outside of the database identified by `key` has changed,
+    /// so that the queries which read it (see [`report_external_read`](`Self::report_external_read`))
+    /// are re-executed.
+    ///
+    /// **WARNING:** Just like an ordinary write, this method triggers
+    /// cancellation. If you invoke it while a snapshot exists, it
+    /// will block until that snapshot is dropped -- if that snapshot
+    /// is owned by the current thread, this could trigger deadlock.
+    fn invalidate_external(&mut self, key: &dyn ExternalKey) {
+        let zalsa_mut = self.zalsa_mut();
+        let revision = zalsa_mut.current_revision();
+        if external::IngredientImpl::from_zalsa(zalsa_mut).invalidate(key, revision) {
+            zalsa_mut.report_tracked_write(Durability::LOW);
+        }
+    }
+
     /// Return the "debug name" (i.e., the struct name, etc) for an "ingredient",
     /// which are the fine-grained components we use to track data. This is intended
     /// for debugging and the contents of the returned string are not semver-guaranteed.
@@ -54,6 +233,25 @@ pub trait Database: Send + AsDynDatabase + Any + ZalsaDatabase {
         )
     }
 
+    /// Describes each ingredient registered in the database, in the order of their indices.
+    ///
+    /// Ingredients are registered the first time they are used, e.g. when a tracked
+    /// function is first called or the first instance of a salsa struct is created.
+    fn ingredients(&self) -> Vec<IngredientInfo> {
+        let zalsa = self.zalsa();
+        zalsa
+            .ingredients()
+            .map(|ingredient| IngredientInfo {
+                index: ingredient.ingredient_index(),
+                debug_name: ingredient.debug_name(),
+                kind: ingredient.kind(),
+                live_entries: ingredient.live_entries(zalsa),
+                lru_capacity: ingredient.lru_capacity(),
+                cycle_strategy: ingredient.cycle_recovery_strategy(),
+            })
+            .collect()
+    }
+
     /// Execute `op` with the database in thread-local storage for debug print-outs.
     fn attach<R>(&self, op: impl FnOnce(&Self) -> R) -> R
     where
@@ -93,6 +291,26 @@ impl dyn Database {
     /// If the view has not been added to the database (see [`DatabaseView`][])
     #[track_caller]
     pub fn as_view<DbView: ?Sized + Database>(&self) -> &DbView {
-        self.zalsa().views().try_view_as(self).unwrap()
+        match self.try_as_view() {
+            Some(view) => view,
+            None => panic!(
+                "view `{}` was not added to the database",
+                std::any::type_name::<DbView>()
+            ),
+        }
+    }
+
+    /// Upcasts `self` to the given view, or returns `None` if the view
+    /// has not been added to the database.
+    ///
+    /// A view is added the first time an ingredient whose functions take it is created,
+    /// e.g. when a tracked function taking a `&dyn DbView` is first called.
+    pub fn try_as_view<DbView: ?Sized + Database>(&self) -> Option<&DbView> {
+        self.zalsa().views().try_view_as(self)
+    }
+
+    /// Returns the type names of the views added to the database, in the order they were added.
+    pub fn view_type_names(&self) -> Vec<&'static str> {
+        self.zalsa().views().type_names().collect()
     }
 }
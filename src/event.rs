@@ -27,6 +27,13 @@ pub enum EventKind {
         database_key: DatabaseKeyIndex,
     },
 
+    /// Occurs when a query was re-executed but produced a value equal to
+    /// its old one, so that the queries depending on it need not re-execute.
+    DidBackdateValue {
+        /// The database-key for the affected value. Implements `Debug`.
+        database_key: DatabaseKeyIndex,
+    },
+
     /// Indicates that another thread (with id `other_thread_id`) is processing the
     /// given query (`database_key`), so we will block until they
     /// finish.
@@ -0,0 +1,254 @@
+//! Policies deciding which memoized values of a tracked function are evicted.
+//!
+//! A policy is chosen with the `eviction` option, e.g.
+//! `#[salsa::tracked(eviction = salsa::eviction::Lfu::new(32))]`.
+//! The `lru = N` option is a shorthand for `eviction = Lru::new(N)`.
+//!
+//! Evicting a value only throws away the value itself: the dependencies of the memo
+//! are kept, so that the function can still be verified and its dependents backdated.
+//! If the value is needed again, the function is re-executed.
+
+use std::collections::BTreeSet;
+
+use crossbeam::atomic::AtomicCell;
+use parking_lot::Mutex;
+
+use crate::{
+    hash::{FxHashMap, FxHashSet, FxLinkedHashSet},
+    Id, Revision,
+};
+
+/// Decides which memoized values of a tracked function to evict.
+pub trait EvictionPolicy: Send + Sync + 'static {
+    /// Invoked each time the memoized value for `id` is fetched in `revision`.
+    /// Calls `evict` for each id whose value should be evicted; this must not include `id` itself,
+    /// whose value is about to be returned.
+    ///
+    /// `verified_at` returns the last revision in which the memo for an id was verified,
+    /// which is also updated when a dependent query is verified without fetching the value.
+    fn record_use(
+        &self,
+        id: Id,
+        revision: Revision,
+        verified_at: &dyn Fn(Id) -> Option<Revision>,
+        evict: &mut dyn FnMut(Id),
+    );
+
+    /// Sets the number of values to keep, for policies that have a capacity.
+    /// Invoked by the generated `set_lru_capacity` function.
+    fn set_capacity(&self, capacity: usize) {
+        let _ = capacity;
+    }
+
+    /// The number of values kept, for policies that have a capacity (`None` if no values are evicted).
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Keeps the `capacity` most recently used values.
+/// A capacity of 0 means that no values are evicted.
+#[derive(Default)]
+pub struct Lru {
+    capacity: AtomicCell<usize>,
+    set: Mutex<FxLinkedHashSet<Id>>,
+}
+
+impl Lru {
+    pub fn new(capacity: usize) -> Self {
+        let lru = Self::default();
+        lru.set_capacity(capacity);
+        lru
+    }
+}
+
+impl EvictionPolicy for Lru {
+    fn record_use(
+        &self,
+        id: Id,
+        _revision: Revision,
+        _verified_at: &dyn Fn(Id) -> Option<Revision>,
+        evict: &mut dyn FnMut(Id),
+    ) {
+        let capacity = self.capacity.load();
+
+        if capacity == 0 {
+            // LRU is disabled
+            return;
+        }
+
+        let mut set = self.set.lock();
+        set.insert(id);
+        if set.len() > capacity {
+            if let Some(evicted) = set.pop_front() {
+                evict(evicted);
+            }
+        }
+    }
+
+    fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity);
+
+        if capacity == 0 {
+            let mut set = self.set.lock();
+            *set = FxLinkedHashSet::default();
+        }
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Some(self.capacity.load()).filter(|&capacity| capacity > 0)
+    }
+}
+
+/// Keeps the `capacity` most frequently used values,
+/// evicting the least recently used one among those used equally often.
+/// A capacity of 0 means that no values are evicted.
+#[derive(Default)]
+pub struct Lfu {
+    capacity: AtomicCell<usize>,
+    state: Mutex<LfuState>,
+}
+
+#[derive(Default)]
+struct LfuState {
+    /// The number of uses of each id, and the time of its last use.
+    uses: FxHashMap<Id, (usize, u64)>,
+
+    /// The entries of `uses`, ordered from the first to the last to evict.
+    order: BTreeSet<(usize, u64, Id)>,
+
+    /// Incremented on each use.
+    clock: u64,
+}
+
+impl Lfu {
+    pub fn new(capacity: usize) -> Self {
+        let lfu = Self::default();
+        lfu.set_capacity(capacity);
+        lfu
+    }
+}
+
+impl EvictionPolicy for Lfu {
+    fn record_use(
+        &self,
+        id: Id,
+        _revision: Revision,
+        _verified_at: &dyn Fn(Id) -> Option<Revision>,
+        evict: &mut dyn FnMut(Id),
+    ) {
+        let capacity = self.capacity.load();
+
+        if capacity == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock();
+        let LfuState { uses, order, clock } = &mut *state;
+        *clock += 1;
+
+        let count = match uses.get(&id) {
+            Some(&(count, last_use)) => {
+                order.remove(&(count, last_use, id));
+                count + 1
+            }
+            None => 1,
+        };
+        uses.insert(id, (count, *clock));
+        order.insert((count, *clock, id));
+
+        while uses.len() > capacity {
+            // `id` was just used, so it may only be the first to evict if it is the least frequently used.
+            let first = *order.iter().find(|&&(_, _, other)| other != id).unwrap();
+            order.remove(&first);
+            uses.remove(&first.2);
+            evict(first.2);
+        }
+    }
+
+    fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity);
+
+        if capacity == 0 {
+            let mut state = self.state.lock();
+            *state = LfuState::default();
+        }
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Some(self.capacity.load()).filter(|&capacity| capacity > 0)
+    }
+}
+
+/// Evicts the values whose memo has not been verified in the last `revisions` revisions.
+///
+/// A memo is verified when its value is fetched, but also when a query depending on it
+/// is verified, so values that are still needed by up-to-date queries are kept.
+/// Values are only evicted when some value of the function is used,
+/// on the first use in each new revision.
+pub struct NotUsedInLast {
+    revisions: usize,
+    state: Mutex<NotUsedInLastState>,
+}
+
+struct NotUsedInLastState {
+    /// The ids whose values were used since they were last evicted.
+    used: FxHashSet<Id>,
+
+    /// The revision in which we last looked for values to evict.
+    swept_at: Option<Revision>,
+}
+
+impl NotUsedInLast {
+    /// Values are kept for `revisions` revisions after their last use.
+    ///
+    /// # Panics
+    ///
+    /// If `revisions` is zero.
+    pub fn revisions(revisions: usize) -> Self {
+        assert!(
+            revisions > 0,
+            "values must be kept for at least one revision"
+        );
+        Self {
+            revisions,
+            state: Mutex::new(NotUsedInLastState {
+                used: Default::default(),
+                swept_at: None,
+            }),
+        }
+    }
+}
+
+impl EvictionPolicy for NotUsedInLast {
+    fn record_use(
+        &self,
+        id: Id,
+        revision: Revision,
+        verified_at: &dyn Fn(Id) -> Option<Revision>,
+        evict: &mut dyn FnMut(Id),
+    ) {
+        let mut state = self.state.lock();
+        state.used.insert(id);
+
+        if state.swept_at == Some(revision) {
+            return;
+        }
+        state.swept_at = Some(revision);
+
+        state.used.retain(|&other| {
+            if other == id {
+                return true;
+            }
+            let Some(verified_at) = verified_at(other) else {
+                // There is no memo left to evict.
+                return false;
+            };
+            let keep = revision.as_usize() - verified_at.as_usize() < self.revisions;
+            if !keep {
+                evict(other);
+            }
+            keep
+        });
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    any::Any,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use append_only_vec::AppendOnlyVec;
+
+use crate::{
+    cycle::CycleRecoveryStrategy,
+    hash::{FxDashMap, FxHashSet},
+    ingredient::{fmt_index, Ingredient, Jar, JarAux},
+    introspection::IngredientKind,
+    key::DependencyIndex,
+    revision::AtomicRevision,
+    zalsa::{IngredientIndex, Zalsa},
+    zalsa_local::QueryOrigin,
+    Database, DatabaseKeyIndex, Durability, Id, QueryError, Revision,
+};
+
+/// Identifies some state outside of the database that queries can read,
+/// see [`Database::report_external_read`](`crate::Database::report_external_read`).
+///
+/// Implemented for all types that can be cloned, hashed and compared,
+/// such as strings, paths or a user-defined enum of the kinds of state.
+/// Keys of different types are never equal.
+pub trait ExternalKey: fmt::Debug + Send + Sync + 'static {
+    fn as_any(&self) -> &dyn Any;
+
+    fn dyn_eq(&self, other: &dyn ExternalKey) -> bool;
+
+    fn dyn_hash(&self, state: &mut dyn Hasher);
+
+    fn clone_box(&self) -> Box<dyn ExternalKey>;
+}
+
+impl<T> ExternalKey for T
+where
+    T: Clone + Eq + Hash + fmt::Debug + Send + Sync + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn dyn_eq(&self, other: &dyn ExternalKey) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+
+    fn dyn_hash(&self, mut state: &mut dyn Hasher) {
+        self.hash(&mut state)
+    }
+
+    fn clone_box(&self) -> Box<dyn ExternalKey> {
+        Box::new(self.clone())
+    }
+}
+
+impl PartialEq for dyn ExternalKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl Eq for dyn ExternalKey {}
+
+impl Hash for dyn ExternalKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_any().type_id().hash(state);
+        self.dyn_hash(state)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct JarImpl;
+
+impl Jar for JarImpl {
+    fn create_ingredients(
+        &self,
+        _aux: &dyn JarAux,
+        first_index: IngredientIndex,
+    ) -> Vec<Box<dyn Ingredient>> {
+        vec![Box::new(IngredientImpl::new(first_index))]
+    }
+}
+
+/// Tracks the external state read by queries: each key is assigned an [`Id`]
+/// and the revision in which it was last invalidated.
+pub(crate) struct IngredientImpl {
+    index: IngredientIndex,
+    ids: FxDashMap<Box<dyn ExternalKey>, Id>,
+    keys: AppendOnlyVec<ExternalState>,
+}
+
+struct ExternalState {
+    key: Box<dyn ExternalKey>,
+
+    /// Revision in which the state was last invalidated.
+    changed_at: AtomicRevision,
+}
+
+impl IngredientImpl {
+    /// Find the ingredient tracking external state in the database, creating it if needed.
+    pub(crate) fn from_zalsa(zalsa: &Zalsa) -> &Self {
+        let index = zalsa.add_or_lookup_jar_by_type(&JarImpl);
+        zalsa.lookup_ingredient(index).assert_type::<Self>()
+    }
+
+    fn new(index: IngredientIndex) -> Self {
+        Self {
+            index,
+            ids: FxDashMap::default(),
+            keys: AppendOnlyVec::new(),
+        }
+    }
+
+    fn id(&self, key: &dyn ExternalKey) -> Id {
+        if let Some(id) = self.ids.get(key) {
+            return *id;
+        }
+        *self.ids.entry(key.clone_box()).or_insert_with(|| {
+            let state = ExternalState {
+                key: key.clone_box(),
+                changed_at: AtomicRevision::start(),
+            };
+            Id::from_u32(self.keys.push(state) as u32)
+        })
+    }
+
+    /// Registers that the active query (if any) read the state identified by `key`.
+    pub(crate) fn report_read(&self, db: &dyn Database, key: &dyn ExternalKey) {
+        let id = self.id(key);
+        let changed_at = self.keys[id.as_u32() as usize].changed_at.load();
+        db.zalsa_local().report_tracked_read(
+            DependencyIndex {
+                ingredient_index: self.index,
+                key_index: Some(id),
+            },
+            Durability::LOW,
+            changed_at,
+        );
+    }
+
+    /// Marks the state identified by `key` as changed in `revision`.
+    /// Returns false if no query ever read it.
+    pub(crate) fn invalidate(&self, key: &dyn ExternalKey, revision: Revision) -> bool {
+        let Some(id) = self.ids.get(key).map(|id| *id) else {
+            return false;
+        };
+        self.keys[id.as_u32() as usize].changed_at.store(revision);
+        true
+    }
+}
+
+impl Ingredient for IngredientImpl {
+    fn ingredient_index(&self) -> IngredientIndex {
+        self.index
+    }
+
+    fn maybe_changed_after(
+        &self,
+        _db: &dyn Database,
+        input: Option<Id>,
+        revision: Revision,
+    ) -> Result<bool, QueryError> {
+        let id = input.unwrap();
+        Ok(self.keys[id.as_u32() as usize].changed_at.load() > revision)
+    }
+
+    fn cycle_recovery_strategy(&self) -> CycleRecoveryStrategy {
+        CycleRecoveryStrategy::Panic
+    }
+
+    fn origin(&self, _db: &dyn Database, _key_index: Id) -> Option<QueryOrigin> {
+        None
+    }
+
+    fn computed_at(&self, _db: &dyn Database, _key_index: Id) -> Option<(Revision, Durability)> {
+        None
+    }
+
+    fn evict_value(&self, _zalsa: &Zalsa, _key_index: Id) {}
+
+    fn mark_validated_output(
+        &self,
+        _db: &dyn Database,
+        executor: DatabaseKeyIndex,
+        output_key: Option<Id>,
+    ) {
+        unreachable!("mark_validated_output({executor:?}, {output_key:?}): external state is never an output")
+    }
+
+    fn remove_stale_output(
+        &self,
+        _db: &dyn Database,
+        executor: DatabaseKeyIndex,
+        stale_output_key: Option<Id>,
+    ) {
+        unreachable!("remove_stale_output({executor:?}, {stale_output_key:?}): external state is never an output")
+    }
+
+    fn requires_reset_for_new_revision(&self) -> bool {
+        false
+    }
+
+    fn reset_for_new_revision(&mut self) {
+        panic!("unexpected reset on external state")
+    }
+
+    fn debug_name(&self) -> &'static str {
+        "external"
+    }
+
+    fn kind(&self) -> IngredientKind {
+        IngredientKind::External
+    }
+
+    fn live_entries(&self, _zalsa: &Zalsa) -> usize {
+        self.keys.len()
+    }
+
+    fn lru_capacity(&self) -> Option<usize> {
+        None
+    }
+
+    fn sweep(&self, _db: &dyn Database, _kept_inputs: &FxHashSet<DependencyIndex>) {}
+
+    fn fmt_index(&self, index: Option<Id>, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match index {
+            Some(id) => write!(fmt, "external({:?})", self.keys[id.as_u32() as usize].key),
+            None => fmt_index(self.debug_name(), index, fmt),
+        }
+    }
+}
+
+impl fmt::Debug for IngredientImpl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("index", &self.index)
+            .finish()
+    }
+}
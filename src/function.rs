@@ -1,14 +1,22 @@
-use std::{any::Any, fmt, sync::Arc};
+use std::{
+    any::Any,
+    fmt,
+    hash::{BuildHasher, Hash, RandomState},
+    sync::{Arc, OnceLock},
+};
 
 use crate::{
     cycle::CycleRecoveryStrategy,
+    eviction::{EvictionPolicy, Lru},
+    hash::FxHashSet,
     ingredient::fmt_index,
-    key::DatabaseKeyIndex,
+    introspection::IngredientKind,
+    key::{DatabaseKeyIndex, DependencyIndex},
     plumbing::JarAux,
     salsa_struct::SalsaStructInDb,
     zalsa::{IngredientIndex, MemoIngredientIndex, Zalsa},
     zalsa_local::QueryOrigin,
-    Cycle, Database, Id, Revision,
+    Cycle, Database, Durability, Id, QueryError, Revision,
 };
 
 use self::delete::DeletedEntries;
@@ -22,10 +30,11 @@ mod diff_outputs;
 mod execute;
 mod fetch;
 mod inputs;
-mod lru;
 mod maybe_changed_after;
 mod memo;
+mod panic;
 mod specify;
+mod verify;
 
 pub trait Configuration: Any {
     const DEBUG_NAME: &'static str;
@@ -48,6 +57,13 @@ pub trait Configuration: Any {
     /// (and, if so, how).
     const CYCLE_STRATEGY: CycleRecoveryStrategy;
 
+    /// If true, a panic raised by `execute` is memoized along with the dependencies
+    /// read so far, and re-raised on each fetch until one of those dependencies changes.
+    const CATCH_PANIC: bool;
+
+    /// If true (the `no_eq` option), values are never compared with [`should_backdate_value`](`Self::should_backdate_value`).
+    const NO_EQ: bool;
+
     /// Invokes after a new result `new_value`` has been computed for which an older memoized
     /// value existed `old_value`. Returns true if the new value is equal to the older one
     /// and hence should be "backdated" (i.e., marked as having last changed in an older revision,
@@ -56,6 +72,18 @@ pub trait Configuration: Any {
     /// This invokes user's code in form of the `Eq` impl.
     fn should_backdate_value(old_value: &Self::Output<'_>, new_value: &Self::Output<'_>) -> bool;
 
+    /// If the function counts against the memory budget of the database (the `estimated_size` option),
+    /// returns the estimated size of `value` in bytes.
+    ///
+    /// This invokes user's code in form of the `EstimatedSize` impl.
+    fn estimated_size(value: &Self::Output<'_>) -> Option<usize>;
+
+    /// If the function keeps fingerprints of its values (the `fingerprint` option),
+    /// returns the fingerprint of `value`, used to backdate it once the old value was evicted.
+    ///
+    /// This invokes user's code in form of the `Hash` impl.
+    fn fingerprint(value: &Self::Output<'_>) -> Option<u128>;
+
     /// Convert from the id used internally to the value that execute is expecting.
     /// This is a no-op if the input to the function is a salsa struct.
     fn id_to_input(db: &Self::DbView, key: Id) -> Self::Input<'_>;
@@ -96,8 +124,8 @@ pub struct IngredientImpl<C: Configuration> {
     /// The index for the memo/sync tables
     memo_ingredient_index: MemoIngredientIndex,
 
-    /// Used to find memos to throw out when we have too many memoized values.
-    lru: lru::Lru,
+    /// Decides which memoized values to throw out.
+    eviction: Box<dyn EvictionPolicy>,
 
     /// When `fetch` and friends executes, they return a reference to the
     /// value stored in the memo that is extended to live as long as the `&self`
@@ -121,6 +149,17 @@ pub fn should_backdate_value<V: Eq>(old_value: &V, new_value: &V) -> bool {
     old_value == new_value
 }
 
+/// A 128-bit hash of `value`, invoked by the generated code for `fingerprint`.
+/// Two values with the same fingerprint are considered equal.
+///
+/// The SipHash keys are chosen randomly once per process, so fingerprints
+/// cannot be persisted and compared across processes.
+pub fn fingerprint<V: Hash + ?Sized>(value: &V) -> u128 {
+    static KEYS: OnceLock<(RandomState, RandomState)> = OnceLock::new();
+    let (k0, k1) = KEYS.get_or_init(|| (RandomState::new(), RandomState::new()));
+    (u128::from(k0.hash_one(value)) << 64) | u128::from(k1.hash_one(value))
+}
+
 impl<C> IngredientImpl<C>
 where
     C: Configuration,
@@ -129,7 +168,7 @@ where
         Self {
             index,
             memo_ingredient_index: aux.next_memo_ingredient_index(index),
-            lru: Default::default(),
+            eviction: Box::new(Lru::default()),
             deleted_entries: Default::default(),
         }
     }
@@ -142,22 +181,25 @@ where
     }
 
     pub fn set_capacity(&self, capacity: usize) {
-        self.lru.set_capacity(capacity);
+        self.eviction.set_capacity(capacity);
+    }
+
+    pub fn set_eviction_policy(&mut self, policy: impl EvictionPolicy) {
+        self.eviction = Box::new(policy);
     }
 
-    /// Returns a reference to the memo value that lives as long as self.
+    /// Returns a reference to the memo that lives as long as self.
     /// This is UNSAFE: the caller is responsible for ensuring that the
     /// memo will not be released so long as the `&self` is valid.
     /// This is done by (a) ensuring the memo is present in the memo-map
     /// when this function is called and (b) ensuring that any entries
     /// removed from the memo-map are added to `deleted_entries`, which is
     /// only cleared with `&mut self`.
-    unsafe fn extend_memo_lifetime<'this, 'memo>(
+    unsafe fn extend_memo_lifetime<'this>(
         &'this self,
-        memo: &'memo memo::Memo<C::Output<'this>>,
-    ) -> Option<&'this C::Output<'this>> {
-        let memo_value: Option<&'memo C::Output<'this>> = memo.value.as_ref();
-        std::mem::transmute(memo_value)
+        memo: &memo::Memo<C::Output<'this>>,
+    ) -> &'this memo::Memo<C::Output<'this>> {
+        std::mem::transmute(memo)
     }
 
     fn insert_memo<'db>(
@@ -165,9 +207,9 @@ where
         zalsa: &'db Zalsa,
         id: Id,
         memo: memo::Memo<C::Output<'db>>,
-    ) -> Option<&C::Output<'db>> {
+    ) -> &'db memo::Memo<C::Output<'db>> {
         let memo = Arc::new(memo);
-        let value = unsafe {
+        let db_memo = unsafe {
             // Unsafety conditions: memo must be in the map (it's not yet, but it will be by the time this
             // value is returned) and anything removed from map is added to deleted entries (ensured elsewhere).
             self.extend_memo_lifetime(&memo)
@@ -177,7 +219,7 @@ where
             // in the deleted entries. This will get cleared when a new revision starts.
             self.deleted_entries.push(old_value);
         }
-        value
+        db_memo
     }
 }
 
@@ -194,7 +236,7 @@ where
         db: &dyn Database,
         input: Option<Id>,
         revision: Revision,
-    ) -> bool {
+    ) -> Result<bool, QueryError> {
         let key = input.unwrap();
         let db = db.as_view::<C::DbView>();
         self.maybe_changed_after(db, key, revision)
@@ -208,6 +250,14 @@ where
         self.origin(db.zalsa(), key)
     }
 
+    fn computed_at(&self, db: &dyn Database, key: Id) -> Option<(Revision, Durability)> {
+        self.computed_at(db.zalsa(), key)
+    }
+
+    fn evict_value(&self, zalsa: &Zalsa, key_index: Id) {
+        self.evict_value_from_memo_for(zalsa, key_index);
+    }
+
     fn mark_validated_output(
         &self,
         db: &dyn Database,
@@ -244,6 +294,24 @@ where
     fn debug_name(&self) -> &'static str {
         C::DEBUG_NAME
     }
+
+    fn kind(&self) -> IngredientKind {
+        IngredientKind::Function
+    }
+
+    fn live_entries(&self, zalsa: &Zalsa) -> usize {
+        let mut memos = 0;
+        zalsa.table().for_each_memo_table(|_, table| {
+            memos += usize::from(table.contains(self.memo_ingredient_index));
+        });
+        memos
+    }
+
+    fn lru_capacity(&self) -> Option<usize> {
+        self.eviction.capacity()
+    }
+
+    fn sweep(&self, _db: &dyn Database, _kept_inputs: &FxHashSet<DependencyIndex>) {}
 }
 
 impl<C> std::fmt::Debug for IngredientImpl<C>
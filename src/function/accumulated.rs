@@ -1,6 +1,4 @@
-use crate::{
-    accumulator, hash::FxHashSet, zalsa::ZalsaDatabase, AsDynDatabase, DatabaseKeyIndex, Id,
-};
+use crate::{accumulator, zalsa::ZalsaDatabase, AsDynDatabase, DatabaseKeyIndex, Id};
 
 use super::{Configuration, IngredientImpl};
 
@@ -14,42 +12,47 @@ where
     where
         A: accumulator::Accumulator,
     {
-        let zalsa = db.zalsa();
-        let zalsa_local = db.zalsa_local();
-        let current_revision = zalsa.current_revision();
-
         let Some(accumulator) = <accumulator::IngredientImpl<A>>::from_db(db) else {
             return vec![];
         };
-        let mut output = vec![];
 
-        // First ensure the result is up to date
-        self.fetch(db, key);
+        self.refresh_for_accumulated(db, key);
+        accumulator.accumulated_by(db.as_dyn_database(), self.database_key_index(key))
+    }
 
-        let db = db.as_dyn_database();
-        let db_key = self.database_key_index(key);
-        let mut visited: FxHashSet<DatabaseKeyIndex> = FxHashSet::default();
-        let mut stack: Vec<DatabaseKeyIndex> = vec![db_key];
+    /// Like [`Self::accumulated_by`], but also returns the query that accumulated each value.
+    /// The values of the queries for which `filter` returns false, and of their inputs,
+    /// are skipped (unless those are reached through another query).
+    pub fn accumulated_with_origin<A>(
+        &self,
+        db: &C::DbView,
+        key: Id,
+        mut filter: impl FnMut(DatabaseKeyIndex) -> bool,
+    ) -> Vec<(DatabaseKeyIndex, A)>
+    where
+        A: accumulator::Accumulator,
+    {
+        let Some(accumulator) = <accumulator::IngredientImpl<A>>::from_db(db) else {
+            return vec![];
+        };
 
-        while let Some(k) = stack.pop() {
-            if visited.insert(k) {
-                accumulator.produced_by(current_revision, zalsa_local, k, &mut output);
+        self.refresh_for_accumulated(db, key);
+        accumulator.accumulated_with_origin(
+            db.as_dyn_database(),
+            self.database_key_index(key),
+            &mut filter,
+        )
+    }
 
-                let origin = zalsa
-                    .lookup_ingredient(k.ingredient_index)
-                    .origin(db, k.key_index);
-                let inputs = origin.iter().flat_map(|origin| origin.inputs());
-                // Careful: we want to push in execution order, so reverse order to
-                // ensure the first child that was executed will be the first child popped
-                // from the stack.
-                stack.extend(
-                    inputs
-                        .flat_map(|input| TryInto::<DatabaseKeyIndex>::try_into(input).into_iter())
-                        .rev(),
-                );
-            }
+    /// First ensure the result is up to date. This does not report a read of the
+    /// result, since the caller only depends on the accumulated values.
+    fn refresh_for_accumulated(&self, db: &C::DbView, key: Id) {
+        let memo = match self.refresh_memo(db, key) {
+            Ok(memo) => memo,
+            Err(error) => error.throw(db.zalsa_local()),
+        };
+        if let Some(panic) = &memo.panic {
+            self.resume_memoized_panic(db, key, memo, panic);
         }
-
-        output
     }
 }
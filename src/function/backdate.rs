@@ -1,4 +1,4 @@
-use crate::zalsa_local::QueryRevisions;
+use crate::{zalsa_local::QueryRevisions, Database, DatabaseKeyIndex, Event, EventKind};
 
 use super::{memo::Memo, Configuration, IngredientImpl};
 
@@ -9,28 +9,42 @@ where
     /// If the value/durability of this memo is equal to what is found in `revisions`/`value`,
     /// then updates `revisions.changed_at` to match `self.revisions.changed_at`. This is invoked
     /// on an old memo when a new memo has been produced to check whether there have been changed.
+    ///
+    /// If the old value was evicted, the fingerprints of the values are compared instead, if any.
     pub(super) fn backdate_if_appropriate(
         &self,
+        db: &C::DbView,
+        database_key_index: DatabaseKeyIndex,
         old_memo: &Memo<C::Output<'_>>,
         revisions: &mut QueryRevisions,
         value: &C::Output<'_>,
+        fingerprint: Option<u128>,
     ) {
-        if let Some(old_value) = &old_memo.value {
-            // Careful: if the value became less durable than it
-            // used to be, that is a "breaking change" that our
-            // consumers must be aware of. Becoming *more* durable
-            // is not. See the test `constant_to_non_constant`.
-            if revisions.durability >= old_memo.revisions.durability
-                && C::should_backdate_value(old_value, value)
-            {
-                tracing::debug!(
-                    "value is equal, back-dating to {:?}",
-                    old_memo.revisions.changed_at,
-                );
+        let is_equal = || match (&old_memo.value, old_memo.fingerprint, fingerprint) {
+            (Some(old_value), _, _) => C::should_backdate_value(old_value, value),
+            (None, Some(old_fingerprint), Some(fingerprint)) => old_fingerprint == fingerprint,
+            (None, _, _) => false,
+        };
 
-                assert!(old_memo.revisions.changed_at <= revisions.changed_at);
-                revisions.changed_at = old_memo.revisions.changed_at;
-            }
+        // Careful: if the value became less durable than it
+        // used to be, that is a "breaking change" that our
+        // consumers must be aware of. Becoming *more* durable
+        // is not. See the test `constant_to_non_constant`.
+        if revisions.durability >= old_memo.revisions.durability && is_equal() {
+            tracing::debug!(
+                "value is equal, back-dating to {:?}",
+                old_memo.revisions.changed_at,
+            );
+
+            assert!(old_memo.revisions.changed_at <= revisions.changed_at);
+            revisions.changed_at = old_memo.revisions.changed_at;
+
+            db.salsa_event(&|| Event {
+                thread_id: std::thread::current().id(),
+                kind: EventKind::DidBackdateValue {
+                    database_key: database_key_index,
+                },
+            });
         }
     }
 }
@@ -1,11 +1,12 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
 use crate::{
-    runtime::StampedValue, zalsa::ZalsaDatabase, zalsa_local::ActiveQueryGuard, Cycle, Database,
-    Event, EventKind,
+    zalsa::ZalsaDatabase,
+    zalsa_local::{ActiveQueryGuard, QueryOrigin},
+    Database, Event, EventKind, QueryError,
 };
 
-use super::{memo::Memo, Configuration, IngredientImpl};
+use super::{memo::Memo, panic::MemoizedPanic, Configuration, IngredientImpl};
 
 impl<C> IngredientImpl<C>
 where
@@ -15,6 +16,12 @@ where
     /// a new memo with the result, backdated if possible. Once this completes,
     /// the query will have been popped off the active query stack.
     ///
+    /// If the query is `catch_panic` and its function panics, the returned memo
+    /// holds the panic rather than a value; it is up to the caller to re-raise it.
+    ///
+    /// Fails if the function unwinds with a cycle that this query does not recover
+    /// from, or with a cancellation, i.e., if a plain call it made failed.
+    ///
     /// # Parameters
     ///
     /// * `db`, the database.
@@ -25,7 +32,7 @@ where
         db: &'db C::DbView,
         active_query: ActiveQueryGuard<'_>,
         opt_old_memo: Option<Arc<Memo<C::Output<'_>>>>,
-    ) -> StampedValue<&C::Output<'db>> {
+    ) -> Result<&'db Memo<C::Output<'db>>, QueryError> {
         let zalsa = db.zalsa();
         let revision_now = zalsa.current_revision();
         let database_key_index = active_query.database_key_index;
@@ -49,48 +56,88 @@ where
         // stale, or value is absent. Let's execute!
         let database_key_index = active_query.database_key_index;
         let id = database_key_index.key_index;
-        let value = match Cycle::catch(|| C::execute(db, C::id_to_input(db, id))) {
-            Ok(v) => v,
-            Err(cycle) => {
+        let started_at = Instant::now();
+        let result = QueryError::catch(|| {
+            let execute = || C::execute(db, C::id_to_input(db, id));
+            if C::CATCH_PANIC {
+                MemoizedPanic::catch(execute)
+            } else {
+                Ok(execute())
+            }
+        });
+        let result = match result {
+            Ok(result) => result,
+            Err(QueryError::Cycle(cycle)) => {
                 tracing::debug!(
                     "{database_key_index:?}: caught cycle {cycle:?}, have strategy {:?}",
                     C::CYCLE_STRATEGY
                 );
                 match C::CYCLE_STRATEGY {
-                    crate::cycle::CycleRecoveryStrategy::Panic => cycle.throw(),
+                    crate::cycle::CycleRecoveryStrategy::Panic => return Err(cycle.into()),
                     crate::cycle::CycleRecoveryStrategy::Fallback => {
                         if let Some(c) = active_query.take_cycle() {
                             assert!(c.is(&cycle));
-                            C::recover_from_cycle(db, &cycle, C::id_to_input(db, id))
+                            Ok(C::recover_from_cycle(db, &cycle, C::id_to_input(db, id)))
                         } else {
                             // we are not a participant in this cycle
                             debug_assert!(!cycle
                                 .participant_keys()
                                 .any(|k| k == database_key_index));
-                            cycle.throw()
+                            return Err(cycle.into());
                         }
                     }
                 }
             }
+            Err(error) => return Err(error),
         };
-        let mut revisions = active_query.pop();
+        let (mut revisions, execution_time) = active_query.pop_executed(started_at.elapsed());
+        let fingerprint = result.as_ref().ok().and_then(C::fingerprint);
 
         // If the new value is equal to the old one, then it didn't
         // really change, even if some of its inputs have. So we can
         // "backdate" its `changed_at` revision to be the same as the
         // old value.
         if let Some(old_memo) = &opt_old_memo {
-            self.backdate_if_appropriate(old_memo, &mut revisions, &value);
+            if let Ok(value) = &result {
+                self.backdate_if_appropriate(
+                    db,
+                    database_key_index,
+                    old_memo,
+                    &mut revisions,
+                    value,
+                    fingerprint,
+                );
+            }
             self.diff_outputs(db, database_key_index, old_memo, &revisions);
         }
 
         tracing::debug!("{database_key_index:?}: read_upgrade: result.revisions = {revisions:#?}");
 
-        let stamp_template = revisions.stamp_template();
-        let value = self
-            .insert_memo(zalsa, id, Memo::new(Some(value), revision_now, revisions))
-            .unwrap();
+        // Count the value against the memory budget, unless it cannot be evicted
+        // (see `evict_value_from_memo_for`).
+        let estimated_size = match (&result, &revisions.origin) {
+            (Ok(value), QueryOrigin::Derived(_)) => C::estimated_size(value),
+            _ => None,
+        };
+        match estimated_size {
+            Some(size) => zalsa
+                .memory_budget()
+                .record(database_key_index, size, execution_time),
+            None => {
+                zalsa.memory_budget().forget(database_key_index);
+            }
+        }
 
-        stamp_template.stamp(value)
+        let memo = match result {
+            Ok(value) => Memo {
+                fingerprint,
+                ..Memo::new(Some(value), revision_now, revisions)
+            },
+            Err(panic) => {
+                tracing::debug!("{database_key_index:?}: memoizing panic {panic:?}");
+                Memo::panicked(panic, revision_now, revisions)
+            }
+        };
+        Ok(self.insert_memo(zalsa, id, memo))
     }
 }
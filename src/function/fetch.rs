@@ -1,60 +1,105 @@
-use crate::{runtime::StampedValue, zalsa::ZalsaDatabase, AsDynDatabase as _, Id};
+use crate::{runtime::StampedValue, zalsa::ZalsaDatabase, AsDynDatabase as _, Id, QueryError};
 
-use super::{Configuration, IngredientImpl};
+use super::{memo::Memo, Configuration, IngredientImpl};
 
 impl<C> IngredientImpl<C>
 where
     C: Configuration,
 {
     pub fn fetch<'db>(&'db self, db: &'db C::DbView, id: Id) -> &C::Output<'db> {
-        let (zalsa, zalsa_local) = db.zalsas();
+        let zalsa_local = db.zalsa_local();
         zalsa_local.unwind_if_revision_cancelled(db.as_dyn_database());
 
+        match self.fetch_value(db, id) {
+            Ok(value) => value,
+            Err(error) => error.throw(zalsa_local),
+        }
+    }
+
+    /// Like [`fetch`](`Self::fetch`), but returns unrecoverable cycles and cancellation
+    /// as an `Err` rather than unwinding.
+    ///
+    /// If there is an active query, it has to be executed again in the next revision,
+    /// since it did not get a value. If it is a cycle participant with recovery, it
+    /// unwinds nonetheless, so that the cycle can be recovered from.
+    pub fn try_fetch<'db>(
+        &'db self,
+        db: &'db C::DbView,
+        id: Id,
+    ) -> Result<&'db C::Output<'db>, QueryError> {
+        let (zalsa, zalsa_local) = db.zalsas();
+        let result = zalsa_local
+            .check_revision_cancelled(db.as_dyn_database())
+            .map_err(QueryError::from)
+            .and_then(|()| self.fetch_value(db, id));
+        if result.is_err() {
+            zalsa_local.report_failed_read(zalsa.current_revision());
+        }
+        result
+    }
+
+    fn fetch_value<'db>(
+        &'db self,
+        db: &'db C::DbView,
+        id: Id,
+    ) -> Result<&'db C::Output<'db>, QueryError> {
+        let (zalsa, zalsa_local) = db.zalsas();
+        let memo = self.refresh_memo(db, id)?;
         let StampedValue {
-            value,
+            value: (),
             durability,
             changed_at,
-        } = self.compute_value(db, id);
+        } = memo.revisions.stamped_value(());
+
+        self.eviction.record_use(
+            id,
+            zalsa.current_revision(),
+            &|other| {
+                self.get_memo_from_table_for(zalsa, other)
+                    .map(|memo| memo.verified_at.load())
+            },
+            &mut |evicted| self.evict_value_from_memo_for(zalsa, evicted),
+        );
 
-        if let Some(evicted) = self.lru.record_use(id) {
-            self.evict_value_from_memo_for(zalsa, evicted);
+        if let Some(panic) = &memo.panic {
+            self.resume_memoized_panic(db, id, memo, panic);
         }
 
+        self.verify_from_scratch(db, id, memo);
+
         zalsa_local.report_tracked_read(self.database_key_index(id).into(), durability, changed_at);
 
-        value
+        Ok(memo.value.as_ref().unwrap())
     }
 
     #[inline]
-    fn compute_value<'db>(
+    pub(super) fn refresh_memo<'db>(
         &'db self,
         db: &'db C::DbView,
         id: Id,
-    ) -> StampedValue<&'db C::Output<'db>> {
+    ) -> Result<&'db Memo<C::Output<'db>>, QueryError> {
         loop {
-            if let Some(value) = self.fetch_hot(db, id).or_else(|| self.fetch_cold(db, id)) {
-                return value;
+            if let Some(memo) = self.fetch_hot(db, id) {
+                return Ok(memo);
+            }
+            if let Some(memo) = self.fetch_cold(db, id)? {
+                return Ok(memo);
             }
         }
     }
 
     #[inline]
-    fn fetch_hot<'db>(
-        &'db self,
-        db: &'db C::DbView,
-        id: Id,
-    ) -> Option<StampedValue<&'db C::Output<'db>>> {
+    fn fetch_hot<'db>(&'db self, db: &'db C::DbView, id: Id) -> Option<&'db Memo<C::Output<'db>>> {
         let zalsa = db.zalsa();
         let memo_guard = self.get_memo_from_table_for(zalsa, id);
         if let Some(memo) = &memo_guard {
-            if memo.value.is_some()
+            if memo.has_result()
                 && self.shallow_verify_memo(db, zalsa, self.database_key_index(id), memo)
             {
-                let value = unsafe {
+                return Some(unsafe {
                     // Unsafety invariant: memo is present in memo_map
-                    self.extend_memo_lifetime(memo).unwrap()
-                };
-                return Some(memo.revisions.stamped_value(value));
+                    self.extend_memo_lifetime(memo)
+                });
             }
         }
         None
@@ -64,17 +109,20 @@ where
         &'db self,
         db: &'db C::DbView,
         id: Id,
-    ) -> Option<StampedValue<&'db C::Output<'db>>> {
+    ) -> Result<Option<&'db Memo<C::Output<'db>>>, QueryError> {
         let (zalsa, zalsa_local) = db.zalsas();
         let database_key_index = self.database_key_index(id);
 
         // Try to claim this query: if someone else has claimed it already, go back and start again.
-        let _claim_guard = zalsa.sync_table_for(id).claim(
+        let Some(mut claim_guard) = zalsa.sync_table_for(id).claim(
             db.as_dyn_database(),
             zalsa_local,
             database_key_index,
             self.memo_ingredient_index,
-        )?;
+        )?
+        else {
+            return Ok(None);
+        };
 
         // Push the query on the stack.
         let active_query = zalsa_local.push_query(database_key_index);
@@ -83,15 +131,20 @@ where
         let zalsa = db.zalsa();
         let opt_old_memo = self.get_memo_from_table_for(zalsa, id);
         if let Some(old_memo) = &opt_old_memo {
-            if old_memo.value.is_some() && self.deep_verify_memo(db, old_memo, &active_query) {
-                let value = unsafe {
+            if old_memo.has_result()
+                && self
+                    .deep_verify_memo(db, old_memo, &active_query)
+                    .map_err(|error| claim_guard.fail(error))?
+            {
+                return Ok(Some(unsafe {
                     // Unsafety invariant: memo is present in memo_map.
-                    self.extend_memo_lifetime(old_memo).unwrap()
-                };
-                return Some(old_memo.revisions.stamped_value(value));
+                    self.extend_memo_lifetime(old_memo)
+                }));
             }
         }
 
-        Some(self.execute(db, active_query, opt_old_memo))
+        self.execute(db, active_query, opt_old_memo)
+            .map(Some)
+            .map_err(|error| claim_guard.fail(error))
     }
 }
@@ -1,4 +1,4 @@
-use crate::{zalsa::Zalsa, zalsa_local::QueryOrigin, Id};
+use crate::{zalsa::Zalsa, zalsa_local::QueryOrigin, Durability, Id, Revision};
 
 use super::{Configuration, IngredientImpl};
 
@@ -10,4 +10,9 @@ where
         self.get_memo_from_table_for(zalsa, key)
             .map(|m| m.revisions.origin.clone())
     }
+
+    pub(super) fn computed_at(&self, zalsa: &Zalsa, key: Id) -> Option<(Revision, Durability)> {
+        self.get_memo_from_table_for(zalsa, key)
+            .map(|m| (m.computed_at, m.revisions.durability))
+    }
 }
@@ -1,9 +1,8 @@
 use crate::{
     key::DatabaseKeyIndex,
-    runtime::StampedValue,
     zalsa::{Zalsa, ZalsaDatabase},
     zalsa_local::{ActiveQueryGuard, EdgeKind, QueryOrigin},
-    AsDynDatabase as _, Id, Revision,
+    AsDynDatabase as _, Id, QueryError, Revision,
 };
 
 use super::{memo::Memo, Configuration, IngredientImpl};
@@ -17,9 +16,9 @@ where
         db: &'db C::DbView,
         id: Id,
         revision: Revision,
-    ) -> bool {
+    ) -> Result<bool, QueryError> {
         let (zalsa, zalsa_local) = db.zalsas();
-        zalsa_local.unwind_if_revision_cancelled(db.as_dyn_database());
+        zalsa_local.check_revision_cancelled(db.as_dyn_database())?;
 
         loop {
             let database_key_index = self.database_key_index(id);
@@ -30,17 +29,17 @@ where
             let memo_guard = self.get_memo_from_table_for(zalsa, id);
             if let Some(memo) = &memo_guard {
                 if self.shallow_verify_memo(db, zalsa, database_key_index, memo) {
-                    return memo.revisions.changed_at > revision;
+                    return Ok(memo.revisions.changed_at > revision);
                 }
                 drop(memo_guard); // release the arc-swap guard before cold path
-                if let Some(mcs) = self.maybe_changed_after_cold(db, id, revision) {
-                    return mcs;
+                if let Some(mcs) = self.maybe_changed_after_cold(db, id, revision)? {
+                    return Ok(mcs);
                 } else {
                     // We failed to claim, have to retry.
                 }
             } else {
                 // No memo? Assume has changed.
-                return true;
+                return Ok(true);
             }
         }
     }
@@ -50,21 +49,24 @@ where
         db: &'db C::DbView,
         key_index: Id,
         revision: Revision,
-    ) -> Option<bool> {
+    ) -> Result<Option<bool>, QueryError> {
         let (zalsa, zalsa_local) = db.zalsas();
         let database_key_index = self.database_key_index(key_index);
 
-        let _claim_guard = zalsa.sync_table_for(key_index).claim(
+        let Some(mut claim_guard) = zalsa.sync_table_for(key_index).claim(
             db.as_dyn_database(),
             zalsa_local,
             database_key_index,
             self.memo_ingredient_index,
-        )?;
+        )?
+        else {
+            return Ok(None);
+        };
         let active_query = zalsa_local.push_query(database_key_index);
 
         // Load the current memo, if any.
         let Some(old_memo) = self.get_memo_from_table_for(zalsa, key_index) else {
-            return Some(true);
+            return Ok(Some(true));
         };
 
         tracing::debug!(
@@ -74,21 +76,26 @@ where
         );
 
         // Check if the inputs are still valid and we can just compare `changed_at`.
-        if self.deep_verify_memo(db, &old_memo, &active_query) {
-            return Some(old_memo.revisions.changed_at > revision);
+        if self
+            .deep_verify_memo(db, &old_memo, &active_query)
+            .map_err(|error| claim_guard.fail(error))?
+        {
+            return Ok(Some(old_memo.revisions.changed_at > revision));
         }
 
-        // If inputs have changed, but we have an old value, we can re-execute.
-        // It is possible the result will be equal to the old value and hence
-        // backdated. In that case, although we will have computed a new memo,
-        // the value has not logically changed.
-        if old_memo.value.is_some() {
-            let StampedValue { changed_at, .. } = self.execute(db, active_query, Some(old_memo));
-            return Some(changed_at > revision);
+        // If inputs have changed, but we have an old value (or its fingerprint),
+        // we can re-execute. It is possible the result will be equal to the old
+        // value and hence backdated. In that case, although we will have computed
+        // a new memo, the value has not logically changed.
+        if old_memo.value.is_some() || old_memo.fingerprint.is_some() {
+            let memo = self
+                .execute(db, active_query, Some(old_memo))
+                .map_err(|error| claim_guard.fail(error))?;
+            return Ok(Some(memo.revisions.changed_at > revision));
         }
 
         // Otherwise, nothing for it: have to consider the value to have changed.
-        Some(true)
+        Ok(Some(true))
     }
 
     /// True if the memo's value and `changed_at` time is still valid in this revision.
@@ -133,12 +140,14 @@ where
     /// walks dependencies of `old_memo` and may even execute them to see if their
     /// outputs have changed. As that could lead to cycles, it is important that the
     /// query is on the stack.
+    ///
+    /// Fails if verifying one of the dependencies does.
     pub(super) fn deep_verify_memo(
         &self,
         db: &C::DbView,
         old_memo: &Memo<C::Output<'_>>,
         active_query: &ActiveQueryGuard<'_>,
-    ) -> bool {
+    ) -> Result<bool, QueryError> {
         let zalsa = db.zalsa();
         let database_key_index = active_query.database_key_index;
 
@@ -148,7 +157,7 @@ where
         );
 
         if self.shallow_verify_memo(db, zalsa, database_key_index, old_memo) {
-            return true;
+            return Ok(true);
         }
 
         match &old_memo.revisions.origin {
@@ -164,15 +173,15 @@ where
                 // Conditionally specified queries
                 // where the value is specified
                 // in rev 1 but not in rev 2.
-                return false;
+                return Ok(false);
             }
             QueryOrigin::BaseInput => {
                 // This value was `set` by the mutator thread -- ie, it's a base input and it cannot be out of date.
-                return true;
+                return Ok(true);
             }
             QueryOrigin::DerivedUntracked(_) => {
                 // Untracked inputs? Have to assume that it changed.
-                return false;
+                return Ok(false);
             }
             QueryOrigin::Derived(edges) => {
                 // Fully tracked inputs? Iterate over the inputs and check them, one by one.
@@ -186,9 +195,9 @@ where
                     match edge_kind {
                         EdgeKind::Input => {
                             if dependency_index
-                                .maybe_changed_after(db.as_dyn_database(), last_verified_at)
+                                .maybe_changed_after(db.as_dyn_database(), last_verified_at)?
                             {
-                                return false;
+                                return Ok(false);
                             }
                         }
                         EdgeKind::Output => {
@@ -221,6 +230,6 @@ where
             zalsa.current_revision(),
             database_key_index,
         );
-        true
+        Ok(true)
     }
 }
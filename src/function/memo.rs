@@ -11,7 +11,7 @@ use crate::{
     Revision,
 };
 
-use super::{Configuration, IngredientImpl};
+use super::{panic::MemoizedPanic, Configuration, IngredientImpl};
 
 #[allow(type_alias_bounds)]
 pub(super) type ArcMemo<'lt, C: Configuration> = Arc<Memo<<C as Configuration>::Output<'lt>>>;
@@ -76,13 +76,18 @@ impl<C: Configuration> IngredientImpl<C> {
             }
 
             QueryOrigin::Derived(_) => {
-                let memo_evicted = Arc::new(Memo::new(
-                    None::<C::Output<'_>>,
-                    memo.verified_at.load(),
-                    memo.revisions.clone(),
-                ));
+                let memo_evicted = Arc::new(Memo {
+                    computed_at: memo.computed_at,
+                    fingerprint: memo.fingerprint,
+                    ..Memo::new(
+                        None::<C::Output<'_>>,
+                        memo.verified_at.load(),
+                        memo.revisions.clone(),
+                    )
+                });
 
                 self.insert_memo_into_table_for(zalsa, id, memo_evicted);
+                zalsa.memory_budget().forget(self.database_key_index(id));
             }
         }
     }
@@ -93,10 +98,21 @@ pub(super) struct Memo<V> {
     /// The result of the query, if we decide to memoize it.
     pub(super) value: Option<V>,
 
+    /// The panic raised by a `catch_panic` query, in place of a value.
+    pub(super) panic: Option<MemoizedPanic>,
+
     /// Last revision when this memo was verified; this begins
     /// as the current revision.
     pub(super) verified_at: AtomicCell<Revision>,
 
+    /// Revision in which the value (or panic) was computed; unlike
+    /// `verified_at`, this is not updated when the memo is verified.
+    pub(super) computed_at: Revision,
+
+    /// Fingerprint of the value, for functions with the `fingerprint` option;
+    /// kept when the value is evicted, so that a recomputed value can still be backdated.
+    pub(super) fingerprint: Option<u128>,
+
     /// Revision information
     pub(super) revisions: QueryRevisions,
 }
@@ -105,10 +121,35 @@ impl<V> Memo<V> {
     pub(super) fn new(value: Option<V>, revision_now: Revision, revisions: QueryRevisions) -> Self {
         Memo {
             value,
+            panic: None,
+            verified_at: AtomicCell::new(revision_now),
+            computed_at: revision_now,
+            fingerprint: None,
+            revisions,
+        }
+    }
+
+    pub(super) fn panicked(
+        panic: MemoizedPanic,
+        revision_now: Revision,
+        revisions: QueryRevisions,
+    ) -> Self {
+        Memo {
+            value: None,
+            panic: Some(panic),
             verified_at: AtomicCell::new(revision_now),
+            computed_at: revision_now,
+            fingerprint: None,
             revisions,
         }
     }
+
+    /// True if this memo holds either a value or a memoized panic,
+    /// i.e. if it can be returned from a fetch once verified.
+    pub(super) fn has_result(&self) -> bool {
+        self.value.is_some() || self.panic.is_some()
+    }
+
     /// True if this memo is known not to have changed based on its durability.
     pub(super) fn check_durability(&self, zalsa: &Zalsa) -> bool {
         let last_changed = zalsa.last_changed_revision(self.revisions.durability);
@@ -166,7 +207,10 @@ impl<V> Memo<V> {
                             &"None"
                         },
                     )
+                    .field("panic", &self.memo.panic)
                     .field("verified_at", &self.memo.verified_at)
+                    .field("computed_at", &self.memo.computed_at)
+                    .field("fingerprint", &self.memo.fingerprint)
                     .field("revisions", &self.memo.revisions)
                     .finish()
             }
@@ -180,4 +224,12 @@ impl<V: Debug + Send + Sync + Any> crate::table::memo::Memo for Memo<V> {
     fn origin(&self) -> &QueryOrigin {
         &self.revisions.origin
     }
+
+    fn verified_at(&self) -> Revision {
+        self.verified_at.load()
+    }
+
+    fn has_value(&self) -> bool {
+        self.value.is_some()
+    }
 }
@@ -0,0 +1,100 @@
+use std::{any::Any, panic::AssertUnwindSafe, sync::Arc};
+
+use parking_lot::Mutex;
+
+use crate::{zalsa::ZalsaDatabase, Cancelled, Cycle, Id};
+
+use super::{memo::Memo, Configuration, IngredientImpl};
+
+/// A panic raised by the function of a `catch_panic` query.
+///
+/// It is stored in the memo along with the dependencies the function
+/// read before panicking, and re-raised whenever the memo is fetched.
+/// This includes the dependencies of the queries that unwound with the panic.
+#[derive(Clone)]
+pub(super) struct MemoizedPanic {
+    payload: Payload,
+}
+
+/// The payload of a memoized panic. Panic payloads cannot be cloned, so we keep the
+/// types raised by `panic!` and re-raise a copy of them every time.
+#[derive(Clone)]
+enum Payload {
+    Str(&'static str),
+    String(Arc<str>),
+
+    /// Any other payload, e.g. from `std::panic::panic_any`. It can only be re-raised as is
+    /// once, normally by the call that executed the query; afterwards the panic is re-raised
+    /// with a `String` payload.
+    Other(Arc<Mutex<Option<Box<dyn Any + Send>>>>),
+}
+
+impl MemoizedPanic {
+    /// Runs `f`, capturing any panic it raises. Cycles and cancellation
+    /// are not panics from the user's point of view, so they keep unwinding.
+    pub(super) fn catch<T>(f: impl FnOnce() -> T) -> Result<T, MemoizedPanic> {
+        match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(v) => Ok(v),
+            Err(payload) if payload.is::<Cycle>() || payload.is::<Cancelled>() => {
+                std::panic::resume_unwind(payload)
+            }
+            Err(payload) => {
+                let payload = match payload.downcast::<&'static str>() {
+                    Ok(s) => Payload::Str(*s),
+                    Err(payload) => match payload.downcast::<String>() {
+                        Ok(s) => Payload::String((*s).into()),
+                        Err(payload) => Payload::Other(Arc::new(Mutex::new(Some(payload)))),
+                    },
+                };
+                Err(MemoizedPanic { payload })
+            }
+        }
+    }
+
+    /// Re-raises the panic with a payload of the original type, see [`Payload`].
+    /// We use resume and not panic so that the panic hook does not run again.
+    pub(super) fn resume(&self) -> ! {
+        let payload: Box<dyn Any + Send> = match &self.payload {
+            Payload::Str(s) => Box::new(*s),
+            Payload::String(s) => Box::new(s.to_string()),
+            Payload::Other(payload) => match payload.lock().take() {
+                Some(payload) => payload,
+                None => Box::new(String::from("Box<dyn Any>")),
+            },
+        };
+        std::panic::resume_unwind(payload)
+    }
+}
+
+impl std::fmt::Debug for MemoizedPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match &self.payload {
+            Payload::Str(s) => s,
+            Payload::String(s) => &**s,
+            Payload::Other(_) => "Box<dyn Any>",
+        };
+        f.debug_tuple("MemoizedPanic").field(&message).finish()
+    }
+}
+
+impl<C> IngredientImpl<C>
+where
+    C: Configuration,
+{
+    /// Re-raises the panic stored in `memo`, after recording the read
+    /// so that a `catch_panic` caller depends on this query.
+    pub(super) fn resume_memoized_panic(
+        &self,
+        db: &C::DbView,
+        id: Id,
+        memo: &Memo<C::Output<'_>>,
+        panic: &MemoizedPanic,
+    ) -> ! {
+        db.zalsa_local().report_tracked_read(
+            self.database_key_index(id).into(),
+            memo.revisions.durability,
+            memo.revisions.changed_at,
+        );
+        panic.resume()
+    }
+}
@@ -72,13 +72,23 @@ where
         };
 
         if let Some(old_memo) = self.get_memo_from_table_for(zalsa, key) {
-            self.backdate_if_appropriate(&old_memo, &mut revisions, &value);
+            self.backdate_if_appropriate(
+                db,
+                self.database_key_index(key),
+                &old_memo,
+                &mut revisions,
+                &value,
+                None,
+            );
             self.diff_outputs(db, database_key_index, &old_memo, &revisions);
         }
 
         let memo = Memo {
             value: Some(value),
+            panic: None,
             verified_at: AtomicCell::new(revision),
+            computed_at: revision,
+            fingerprint: None,
             revisions,
         };
 
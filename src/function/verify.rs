@@ -0,0 +1,65 @@
+use crate::{zalsa::ZalsaDatabase, zalsa_local::QueryOrigin, Id};
+
+use super::{memo::Memo, Configuration, IngredientImpl};
+
+impl<C> IngredientImpl<C>
+where
+    C: Configuration,
+{
+    /// In from-scratch verification mode, re-executes the function for a memo computed in
+    /// an earlier revision and panics if the new value differs from the memoized one.
+    /// Each query is verified at most once per revision.
+    ///
+    /// The function is executed in a query frame of its own, whose dependencies are discarded.
+    /// Memos that have outputs (e.g., tracked structs or specified values) are not verified,
+    /// since executing their function again would write to the database, and neither are
+    /// memos of functions with the `no_eq` option, which cannot be compared.
+    pub(super) fn verify_from_scratch<'db>(
+        &'db self,
+        db: &'db C::DbView,
+        id: Id,
+        memo: &Memo<C::Output<'db>>,
+    ) {
+        let (zalsa, zalsa_local) = db.zalsas();
+        let verification = zalsa.verification();
+        if !verification.is_enabled() || C::NO_EQ {
+            return;
+        }
+
+        let current_revision = zalsa.current_revision();
+        let Some(old_value) = &memo.value else {
+            return;
+        };
+        if memo.computed_at == current_revision
+            || !matches!(
+                memo.revisions.origin,
+                QueryOrigin::Derived(_) | QueryOrigin::DerivedUntracked(_)
+            )
+            || memo.revisions.origin.outputs().next().is_some()
+        {
+            return;
+        }
+
+        let database_key_index = self.database_key_index(id);
+        if !verification.start(database_key_index, current_revision) {
+            return;
+        }
+
+        tracing::debug!("{database_key_index:?}: verifying from scratch");
+        let active_query = zalsa_local.push_query(database_key_index);
+        let new_value = C::execute(db, C::id_to_input(db, id));
+        active_query.pop();
+
+        if !C::should_backdate_value(old_value, &new_value) {
+            let mut chain = zalsa_local.active_query_keys();
+            chain.push(database_key_index);
+            let inputs: Vec<_> = memo.revisions.origin.inputs().collect();
+            panic!(
+                "from-scratch verification failed: the memoized value of `{database_key_index:?}` \
+                 differs from the value of a new execution\n  \
+                 dependency chain: {chain:?}\n  \
+                 recorded inputs: {inputs:?}"
+            );
+        }
+    }
+}
@@ -6,6 +6,7 @@ pub(crate) type FxIndexMap<K, V> = indexmap::IndexMap<K, V, FxHasher>;
 pub(crate) type FxDashMap<K, V> = dashmap::DashMap<K, V, FxHasher>;
 pub(crate) type FxLinkedHashSet<K> = hashlink::LinkedHashSet<K, FxHasher>;
 pub(crate) type FxHashSet<K> = std::collections::HashSet<K, FxHasher>;
+pub(crate) type FxHashMap<K, V> = std::collections::HashMap<K, V, FxHasher>;
 
 pub(crate) fn hash<T: Hash>(t: &T) -> u64 {
     FxHasher::default().hash_one(t)
@@ -0,0 +1,45 @@
+use std::{panic::AssertUnwindSafe, sync::Arc};
+
+use parking_lot::Mutex;
+
+use crate::{Cancelled, Database, DatabaseKeyIndex};
+
+type Warm = Arc<dyn Fn(&dyn Database) + Send + Sync>;
+
+/// The queries registered with the `register_hot` function generated for tracked functions,
+/// which are brought up to date by [`Database::warm_hot_queries`](`crate::Database::warm_hot_queries`).
+#[derive(Default)]
+pub(crate) struct HotQueries {
+    queries: Mutex<Vec<(DatabaseKeyIndex, Warm)>>,
+}
+
+/// Registers the query `database_key` as hot; `warm` fetches its result.
+/// Registering a query twice has no effect.
+pub fn register_hot(
+    db: &dyn Database,
+    database_key: DatabaseKeyIndex,
+    warm: impl Fn(&dyn Database) + Send + Sync + 'static,
+) {
+    let mut queries = db.zalsa().hot_queries().queries.lock();
+    if queries.iter().all(|(key, _)| *key != database_key) {
+        queries.push((database_key, Arc::new(warm)));
+    }
+}
+
+impl HotQueries {
+    pub(crate) fn clear(&self) {
+        self.queries.lock().clear();
+    }
+
+    /// Fetches the result of each hot query, in the order they were registered,
+    /// stopping at the first cancellation.
+    pub(crate) fn warm(&self, db: &dyn Database) -> Result<(), Cancelled> {
+        // Hot queries may register others, so do not hold the lock while fetching them.
+        let queries: Vec<(DatabaseKeyIndex, Warm)> = self.queries.lock().clone();
+        for (database_key, warm) in queries {
+            tracing::debug!("warming {database_key:?}");
+            Cancelled::catch(AssertUnwindSafe(|| warm(db)))?;
+        }
+        Ok(())
+    }
+}
@@ -5,9 +5,12 @@ use std::{
 
 use crate::{
     cycle::CycleRecoveryStrategy,
-    zalsa::{IngredientIndex, MemoIngredientIndex},
+    hash::FxHashSet,
+    introspection::IngredientKind,
+    key::DependencyIndex,
+    zalsa::{IngredientIndex, MemoIngredientIndex, Zalsa},
     zalsa_local::QueryOrigin,
-    Database, DatabaseKeyIndex, Id,
+    Database, DatabaseKeyIndex, Durability, Id, QueryError,
 };
 
 use super::Revision;
@@ -32,16 +35,27 @@ pub trait Ingredient: Any + std::fmt::Debug + Send + Sync {
     fn debug_name(&self) -> &'static str;
 
     /// Has the value for `input` in this ingredient changed after `revision`?
+    ///
+    /// Verifying a memoized value may execute queries, which fails
+    /// if they are cancelled or form a cycle that nobody recovers from.
     fn maybe_changed_after<'db>(
         &'db self,
         db: &'db dyn Database,
         input: Option<Id>,
         revision: Revision,
-    ) -> bool;
+    ) -> Result<bool, QueryError>;
 
     /// What were the inputs (if any) that were used to create the value at `key_index`.
     fn origin(&self, db: &dyn Database, key_index: Id) -> Option<QueryOrigin>;
 
+    /// If a value is memoized for `key_index`, the revision in which it was computed
+    /// (as opposed to last verified) and the durability of its inputs.
+    fn computed_at(&self, db: &dyn Database, key_index: Id) -> Option<(Revision, Durability)>;
+
+    /// Throws away the memoized value for `key_index`, if any, keeping its dependencies.
+    /// Invoked to fit the memory budget of the database.
+    fn evict_value(&self, zalsa: &Zalsa, key_index: Id);
+
     /// Invoked when the value `output_key` should be marked as valid in the current revision.
     /// This occurs because the value for `executor`, which generated it, was marked as valid
     /// in the current revision.
@@ -88,6 +102,22 @@ pub trait Ingredient: Any + std::fmt::Debug + Send + Sync {
     fn reset_for_new_revision(&mut self);
 
     fn fmt_index(&self, index: Option<crate::Id>, fmt: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// What this ingredient stores, see [`Database::ingredients`](`crate::Database::ingredients`).
+    fn kind(&self) -> IngredientKind;
+
+    /// The number of entries currently stored by this ingredient,
+    /// e.g. the structs of a salsa struct or the memos of a tracked function.
+    fn live_entries(&self, zalsa: &Zalsa) -> usize;
+
+    /// The capacity of the eviction policy of this ingredient, if it has one.
+    fn lru_capacity(&self) -> Option<usize>;
+
+    /// Invoked by [`Database::sweep`](`crate::Database::sweep`) once memos were dropped,
+    /// to free what this ingredient keeps for queries that no longer have a memo.
+    /// `kept_inputs` are the inputs of the memos that were kept which belong to
+    /// accumulator ingredients.
+    fn sweep(&self, db: &dyn Database, kept_inputs: &FxHashSet<DependencyIndex>);
 }
 
 impl dyn Ingredient {
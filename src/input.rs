@@ -9,14 +9,17 @@ use parking_lot::Mutex;
 
 use crate::{
     cycle::CycleRecoveryStrategy,
+    hash::FxHashSet,
     id::{AsId, FromId},
     ingredient::{fmt_index, Ingredient},
+    introspection::IngredientKind,
     key::{DatabaseKeyIndex, DependencyIndex},
     plumbing::{Jar, JarAux, Stamp},
+    runtime::change_log::InputChange,
     table::{memo::MemoTable, sync::SyncTable, Slot, Table},
     zalsa::{IngredientIndex, Zalsa},
     zalsa_local::QueryOrigin,
-    Database, Durability, Id, Revision, Runtime,
+    Database, Durability, Id, QueryError, Revision, Runtime,
 };
 
 pub trait Configuration: Any {
@@ -24,6 +27,9 @@ pub trait Configuration: Any {
     const FIELD_DEBUG_NAMES: &'static [&'static str];
     const IS_SINGLETON: bool;
 
+    /// True if the writes to this input are recorded (the `serde` option).
+    const IS_RECORDED: bool;
+
     /// The input struct (which wraps an `Id`)
     type Struct: FromId + 'static + Send + Sync;
 
@@ -66,6 +72,10 @@ pub struct IngredientImpl<C: Configuration> {
     ingredient_index: IngredientIndex,
     singleton_index: AtomicCell<Option<Id>>,
     singleton_lock: Mutex<()>,
+
+    /// The revision in which the last input was created, if any.
+    created_at: AtomicCell<Option<Revision>>,
+
     _phantom: std::marker::PhantomData<C::Struct>,
 }
 
@@ -75,6 +85,7 @@ impl<C: Configuration> IngredientImpl<C> {
             ingredient_index: index,
             singleton_index: AtomicCell::new(None),
             singleton_lock: Default::default(),
+            created_at: AtomicCell::new(None),
             _phantom: std::marker::PhantomData,
         }
     }
@@ -119,6 +130,13 @@ impl<C: Configuration> IngredientImpl<C> {
             },
         );
 
+        self.created_at.store(Some(zalsa.current_revision()));
+
+        #[cfg(feature = "serde")]
+        if !C::IS_RECORDED {
+            zalsa.recorder().record_unrecorded(C::DEBUG_NAME);
+        }
+
         if C::IS_SINGLETON {
             self.singleton_index.store(Some(id));
             drop(guard);
@@ -159,6 +177,13 @@ impl<C: Configuration> IngredientImpl<C> {
 
         stamp.durability = durability.unwrap_or(stamp.durability);
         stamp.changed_at = runtime.current_revision();
+        runtime.change_log_mut().record(InputChange {
+            revision: stamp.changed_at,
+            ingredient_index: self.ingredient_index.successor(field_index),
+            id,
+            field_index,
+            durability: stamp.durability,
+        });
         setter(&mut r.fields)
     }
 
@@ -171,6 +196,25 @@ impl<C: Configuration> IngredientImpl<C> {
         self.singleton_index.load().map(FromId::from_id)
     }
 
+    /// Iterates over all the inputs created so far.
+    ///
+    /// When invoked from a tracked function, the function is re-executed once a new revision
+    /// starts if inputs were created since. Since creating an input does not start a new revision,
+    /// inputs created in the revision in which the function was executed are only seen after
+    /// the next write to the database.
+    pub fn all<'db>(&'db self, db: &'db dyn Database) -> impl Iterator<Item = C::Struct> + 'db {
+        let (zalsa, zalsa_local) = db.zalsas();
+        zalsa_local.report_tracked_read(
+            DependencyIndex::for_table(self.ingredient_index),
+            Durability::LOW,
+            self.created_at.load().unwrap_or_else(Revision::start),
+        );
+        zalsa
+            .table()
+            .slots::<Value<C>>(self.ingredient_index)
+            .map(|(id, _)| FromId::from_id(id))
+    }
+
     /// Access field of an input.
     /// Note that this function returns the entire tuple of value fields.
     /// The caller is responible for selecting the appropriate element.
@@ -214,12 +258,20 @@ impl<C: Configuration> Ingredient for IngredientImpl<C> {
     fn maybe_changed_after(
         &self,
         _db: &dyn Database,
-        _input: Option<Id>,
-        _revision: Revision,
-    ) -> bool {
-        // Input ingredients are just a counter, they store no data, they are immortal.
-        // Their *fields* are stored in function ingredients elsewhere.
-        false
+        input: Option<Id>,
+        revision: Revision,
+    ) -> Result<bool, QueryError> {
+        Ok(match input {
+            // Inputs are immortal. Their *fields* are stored in field ingredients elsewhere.
+            Some(_) => false,
+
+            // The set of inputs, see `all`. Inputs created in `revision` itself may have been
+            // created after the read, so they count as a change.
+            None => self
+                .created_at
+                .load()
+                .is_some_and(|created_at| created_at >= revision),
+        })
     }
 
     fn cycle_recovery_strategy(&self) -> CycleRecoveryStrategy {
@@ -230,6 +282,12 @@ impl<C: Configuration> Ingredient for IngredientImpl<C> {
         None
     }
 
+    fn computed_at(&self, _db: &dyn Database, _key_index: Id) -> Option<(Revision, Durability)> {
+        None
+    }
+
+    fn evict_value(&self, _zalsa: &Zalsa, _key_index: crate::Id) {}
+
     fn mark_validated_output(
         &self,
         _db: &dyn Database,
@@ -269,6 +327,20 @@ impl<C: Configuration> Ingredient for IngredientImpl<C> {
     fn debug_name(&self) -> &'static str {
         C::DEBUG_NAME
     }
+
+    fn kind(&self) -> IngredientKind {
+        IngredientKind::Input
+    }
+
+    fn live_entries(&self, zalsa: &Zalsa) -> usize {
+        zalsa.table().allocated_slots(self.ingredient_index)
+    }
+
+    fn lru_capacity(&self) -> Option<usize> {
+        None
+    }
+
+    fn sweep(&self, _db: &dyn Database, _kept_inputs: &FxHashSet<DependencyIndex>) {}
 }
 
 impl<C: Configuration> std::fmt::Debug for IngredientImpl<C> {
@@ -313,4 +385,12 @@ where
     unsafe fn syncs(&self, _current_revision: Revision) -> &SyncTable {
         &self.syncs
     }
+
+    fn peek_memos(&self) -> &crate::table::memo::MemoTable {
+        &self.memos
+    }
+
+    fn memos_mut(&mut self) -> &mut crate::table::memo::MemoTable {
+        &mut self.memos
+    }
 }
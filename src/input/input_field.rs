@@ -1,9 +1,12 @@
 use crate::cycle::CycleRecoveryStrategy;
+use crate::hash::FxHashSet;
 use crate::ingredient::{fmt_index, Ingredient};
 use crate::input::Configuration;
-use crate::zalsa::IngredientIndex;
+use crate::introspection::IngredientKind;
+use crate::key::DependencyIndex;
+use crate::zalsa::{IngredientIndex, Zalsa};
 use crate::zalsa_local::QueryOrigin;
-use crate::{Database, DatabaseKeyIndex, Id, Revision};
+use crate::{Database, DatabaseKeyIndex, Durability, Id, QueryError, Revision};
 use std::fmt;
 use std::marker::PhantomData;
 
@@ -54,17 +57,23 @@ where
         db: &dyn Database,
         input: Option<Id>,
         revision: Revision,
-    ) -> bool {
+    ) -> Result<bool, QueryError> {
         let zalsa = db.zalsa();
         let input = input.unwrap();
         let value = <IngredientImpl<C>>::data(zalsa, input);
-        value.stamps[self.field_index].changed_at > revision
+        Ok(value.stamps[self.field_index].changed_at > revision)
     }
 
     fn origin(&self, _db: &dyn Database, _key_index: Id) -> Option<QueryOrigin> {
         None
     }
 
+    fn computed_at(&self, _db: &dyn Database, _key_index: Id) -> Option<(Revision, Durability)> {
+        None
+    }
+
+    fn evict_value(&self, _zalsa: &Zalsa, _key_index: crate::Id) {}
+
     fn mark_validated_output(
         &self,
         _db: &dyn Database,
@@ -96,6 +105,22 @@ where
     fn debug_name(&self) -> &'static str {
         C::FIELD_DEBUG_NAMES[self.field_index]
     }
+
+    fn kind(&self) -> IngredientKind {
+        IngredientKind::InputField
+    }
+
+    fn live_entries(&self, zalsa: &Zalsa) -> usize {
+        // The fields are stored along with the input, whose ingredient precedes those of its fields.
+        let struct_index = IngredientIndex::from(self.index.as_usize() - self.field_index - 1);
+        zalsa.lookup_ingredient(struct_index).live_entries(zalsa)
+    }
+
+    fn lru_capacity(&self) -> Option<usize> {
+        None
+    }
+
+    fn sweep(&self, _db: &dyn Database, _kept_inputs: &FxHashSet<DependencyIndex>) {}
 }
 
 impl<C> std::fmt::Debug for FieldIngredientImpl<C>
@@ -10,6 +10,10 @@ pub trait Setter: Sized {
     fn to(self, value: Self::FieldTy) -> Self::FieldTy;
 }
 
+/// Serializes a field value for a recording.
+#[cfg(feature = "serde")]
+type SerializeFn<F> = fn(&F) -> Result<String, String>;
+
 #[must_use]
 pub struct SetterImpl<'setter, C: Configuration, S, F> {
     runtime: &'setter mut Runtime,
@@ -18,6 +22,8 @@ pub struct SetterImpl<'setter, C: Configuration, S, F> {
     durability: Option<Durability>,
     field_index: usize,
     setter: S,
+    #[cfg(feature = "serde")]
+    recorded: Option<(&'static str, SerializeFn<F>)>,
     phantom: PhantomData<fn(F)>,
 }
 
@@ -40,9 +46,19 @@ where
             ingredient,
             durability: None,
             setter,
+            #[cfg(feature = "serde")]
+            recorded: None,
             phantom: PhantomData,
         }
     }
+
+    /// Records the value with `serialize` under the name `input` when the database is recording
+    /// (`serde` option).
+    #[cfg(feature = "serde")]
+    pub fn recorded_with(mut self, input: &'static str, serialize: SerializeFn<F>) -> Self {
+        self.recorded = Some((input, serialize));
+        self
+    }
 }
 
 impl<'setter, C, S, F> Setter for SetterImpl<'setter, C, S, F>
@@ -65,9 +81,26 @@ where
             durability,
             field_index,
             setter,
+            #[cfg(feature = "serde")]
+            recorded,
             phantom: _,
         } = self;
 
+        #[cfg(feature = "serde")]
+        if runtime.recorder().is_recording() {
+            match recorded {
+                Some((input, serialize)) => crate::record::record_set_field(
+                    runtime.recorder(),
+                    input,
+                    crate::id::AsId::as_id(&id),
+                    field_index,
+                    serialize(&value),
+                    durability,
+                ),
+                None => runtime.recorder().record_unrecorded(C::DEBUG_NAME),
+            }
+        }
+
         ingredient.set_field(runtime, id, field_index, durability, |tuple| {
             setter(tuple, value)
         })
@@ -2,19 +2,22 @@ use std::fmt;
 use std::hash::Hash;
 use std::marker::PhantomData;
 
+use crossbeam::atomic::AtomicCell;
+
 use crate::durability::Durability;
 use crate::id::AsId;
 use crate::ingredient::fmt_index;
+use crate::introspection::IngredientKind;
 use crate::key::DependencyIndex;
 use crate::plumbing::{Jar, JarAux};
 use crate::table::memo::MemoTable;
 use crate::table::sync::SyncTable;
 use crate::table::Slot;
-use crate::zalsa::IngredientIndex;
+use crate::zalsa::{IngredientIndex, Zalsa};
 use crate::zalsa_local::QueryOrigin;
-use crate::{Database, DatabaseKeyIndex, Id};
+use crate::{Database, DatabaseKeyIndex, Id, QueryError};
 
-use super::hash::FxDashMap;
+use super::hash::{FxDashMap, FxHashSet};
 use super::ingredient::Ingredient;
 use super::Revision;
 
@@ -64,6 +67,9 @@ pub struct IngredientImpl<C: Configuration> {
     /// but that will make anything dependent on those entries dirty and in need
     /// of being recomputed.
     reset_at: Revision,
+
+    /// The revision in which the last value was interned, if any.
+    created_at: AtomicCell<Option<Revision>>,
 }
 
 /// Struct storing the interned fields.
@@ -103,6 +109,7 @@ where
             ingredient_index,
             key_map: Default::default(),
             reset_at: Revision::start(),
+            created_at: AtomicCell::new(None),
         }
     }
 
@@ -129,19 +136,29 @@ where
         data: C::Data<'db>,
     ) -> C::Struct<'db> {
         let zalsa_local = db.zalsa_local();
+        let id = self.intern_data(db, data);
+
+        // The value stays interned until the table is reset, see `maybe_changed_after`.
+        // The `None` key of the table is the set of interned values instead, see `all`.
         zalsa_local.report_tracked_read(
-            DependencyIndex::for_table(self.ingredient_index),
+            DependencyIndex {
+                ingredient_index: self.ingredient_index,
+                key_index: Some(id),
+            },
             Durability::MAX,
             self.reset_at,
         );
+        C::struct_from_id(id)
+    }
 
+    fn intern_data<'db>(&'db self, db: &'db dyn crate::Database, data: C::Data<'db>) -> Id {
         // Optimisation to only get read lock on the map if the data has already
         // been interned.
         let internal_data = unsafe { self.to_internal_data(data) };
         if let Some(guard) = self.key_map.get(&internal_data) {
             let id = *guard;
             drop(guard);
-            return C::struct_from_id(id);
+            return id;
         }
 
         match self.key_map.entry(internal_data.clone()) {
@@ -149,12 +166,12 @@ where
             dashmap::mapref::entry::Entry::Occupied(entry) => {
                 let id = *entry.get();
                 drop(entry);
-                C::struct_from_id(id)
+                id
             }
 
             // We won any races so should intern the data
             dashmap::mapref::entry::Entry::Vacant(entry) => {
-                let zalsa = db.zalsa();
+                let (zalsa, zalsa_local) = db.zalsas();
                 let table = zalsa.table();
                 let next_id = zalsa_local.allocate(
                     table,
@@ -166,7 +183,8 @@ where
                     },
                 );
                 entry.insert(next_id);
-                C::struct_from_id(next_id)
+                self.created_at.store(Some(zalsa.current_revision()));
+                next_id
             }
         }
     }
@@ -185,6 +203,32 @@ where
         self.data(db, C::deref_struct(s))
     }
 
+    /// Iterates over all the values interned so far (since the last reset).
+    ///
+    /// When invoked from a tracked function, the function is re-executed once a new revision
+    /// starts if values were interned since. Values are interned while executing tracked functions,
+    /// so those interned later in the same revision, e.g. by queries executed after this one,
+    /// are only seen in the next revision.
+    pub fn all<'db>(
+        &'db self,
+        db: &'db dyn Database,
+    ) -> impl Iterator<Item = C::Struct<'db>> + 'db {
+        let (zalsa, zalsa_local) = db.zalsas();
+        zalsa_local.report_tracked_read(
+            DependencyIndex::for_table(self.ingredient_index),
+            Durability::LOW,
+            self.created_at
+                .load()
+                .map_or(self.reset_at, |created_at| created_at.max(self.reset_at)),
+        );
+        zalsa
+            .table()
+            .slots::<Value<C>>(self.ingredient_index)
+            // Values interned before a reset stay allocated, but are no longer in `key_map`.
+            .filter(|(id, value)| self.key_map.get(&value.data).is_some_and(|e| *e == *id))
+            .map(|(id, _)| C::struct_from_id(id))
+    }
+
     pub fn reset(&mut self, revision: Revision) {
         assert!(revision > self.reset_at);
         self.reset_at = revision;
@@ -203,10 +247,22 @@ where
     fn maybe_changed_after(
         &self,
         _db: &dyn Database,
-        _input: Option<Id>,
+        input: Option<Id>,
         revision: Revision,
-    ) -> bool {
-        revision < self.reset_at
+    ) -> Result<bool, QueryError> {
+        Ok(match input {
+            Some(_) => revision < self.reset_at,
+
+            // The set of interned values, see `all`. Values interned in `revision` itself
+            // may have been interned after the read, so they count as a change.
+            None => {
+                revision < self.reset_at
+                    || self
+                        .created_at
+                        .load()
+                        .is_some_and(|created_at| created_at >= revision)
+            }
+        })
     }
 
     fn cycle_recovery_strategy(&self) -> crate::cycle::CycleRecoveryStrategy {
@@ -217,6 +273,16 @@ where
         None
     }
 
+    fn computed_at(
+        &self,
+        _db: &dyn Database,
+        _key_index: crate::Id,
+    ) -> Option<(Revision, Durability)> {
+        None
+    }
+
+    fn evict_value(&self, _zalsa: &Zalsa, _key_index: crate::Id) {}
+
     fn mark_validated_output(
         &self,
         _db: &dyn Database,
@@ -259,6 +325,20 @@ where
     fn debug_name(&self) -> &'static str {
         C::DEBUG_NAME
     }
+
+    fn kind(&self) -> IngredientKind {
+        IngredientKind::Interned
+    }
+
+    fn live_entries(&self, _zalsa: &Zalsa) -> usize {
+        self.key_map.len()
+    }
+
+    fn lru_capacity(&self) -> Option<usize> {
+        None
+    }
+
+    fn sweep(&self, _db: &dyn Database, _kept_inputs: &FxHashSet<DependencyIndex>) {}
 }
 
 impl<C> std::fmt::Debug for IngredientImpl<C>
@@ -283,4 +363,12 @@ where
     unsafe fn syncs(&self, _current_revision: Revision) -> &crate::table::sync::SyncTable {
         &self.syncs
     }
+
+    fn peek_memos(&self) -> &MemoTable {
+        &self.memos
+    }
+
+    fn memos_mut(&mut self) -> &mut MemoTable {
+        &mut self.memos
+    }
 }
@@ -0,0 +1,49 @@
+use crate::{cycle::CycleRecoveryStrategy, IngredientIndex};
+
+/// The kind of an ingredient, see [`Database::ingredients`](`crate::Database::ingredients`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum IngredientKind {
+    /// The structs declared with `#[salsa::input]`.
+    Input,
+
+    /// A field of an input.
+    InputField,
+
+    /// The structs declared with `#[salsa::interned]`.
+    Interned,
+
+    /// The structs declared with `#[salsa::tracked]`.
+    TrackedStruct,
+
+    /// A field of a tracked struct.
+    TrackedField,
+
+    /// A tracked function.
+    Function,
+
+    /// The values pushed to an accumulator.
+    Accumulator,
+
+    /// The external state reported with [`Database::report_external_read`](`crate::Database::report_external_read`).
+    External,
+}
+
+/// Describes an ingredient of a database, see [`Database::ingredients`](`crate::Database::ingredients`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IngredientInfo {
+    pub index: IngredientIndex,
+
+    /// The name of the struct, field or function, as in [`Database::ingredient_debug_name`](`crate::Database::ingredient_debug_name`).
+    pub debug_name: &'static str,
+
+    pub kind: IngredientKind,
+
+    /// The number of entries stored by the ingredient: structs for inputs, interned and tracked
+    /// structs and their fields, memos for functions and values for accumulators.
+    pub live_entries: usize,
+
+    /// The capacity of the eviction policy of a function (e.g. its `lru` option), if any.
+    pub lru_capacity: Option<usize>,
+
+    pub cycle_strategy: CycleRecoveryStrategy,
+}
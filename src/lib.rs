@@ -8,41 +8,66 @@ mod database;
 mod database_impl;
 mod durability;
 mod event;
+pub mod eviction;
+mod external;
 mod function;
 mod hash;
+mod hot;
 mod id;
 mod ingredient;
 mod input;
 mod interned;
+mod introspection;
 mod key;
+mod memory_budget;
 mod nonce;
+mod query_error;
+#[cfg(feature = "serde")]
+mod record;
 mod revision;
 mod runtime;
 mod salsa_struct;
+#[cfg(feature = "serde")]
+mod serialization;
 mod storage;
+mod sweep;
 mod table;
+pub mod testing;
 mod tracked_struct;
 mod update;
+mod verification;
 mod views;
+mod watch;
 mod zalsa;
 mod zalsa_local;
 
 pub use self::accumulator::Accumulator;
 pub use self::cancelled::Cancelled;
 pub use self::cycle::Cycle;
+pub use self::cycle::CycleRecoveryStrategy;
 pub use self::database::AsDynDatabase;
 pub use self::database::Database;
 pub use self::database_impl::DatabaseImpl;
 pub use self::durability::Durability;
 pub use self::event::Event;
 pub use self::event::EventKind;
+pub use self::external::ExternalKey;
 pub use self::id::Id;
 pub use self::input::setter::Setter;
+pub use self::introspection::{IngredientInfo, IngredientKind};
 pub use self::key::DatabaseKeyIndex;
+pub use self::memory_budget::EstimatedSize;
+pub use self::query_error::QueryError;
+#[cfg(feature = "serde")]
+pub use self::record::{RecordedEvent, Recording, Replay, ReplayError, ReplayStep};
 pub use self::revision::Revision;
+pub use self::runtime::change_log::InputChange;
 pub use self::runtime::Runtime;
 pub use self::storage::Storage;
+pub use self::sweep::SweepOptions;
+pub use self::sweep::SweepReport;
 pub use self::update::Update;
+pub use self::watch::WatchId;
 pub use self::zalsa::IngredientIndex;
 pub use crate::attach::with_attached_database;
 pub use salsa_macros::accumulator;
@@ -64,6 +89,8 @@ pub mod prelude {
 ///
 /// The contents of this module are NOT subject to semver.
 pub mod plumbing {
+    pub use crate::accumulator::helper::Dispatch as AccumulatorDispatch;
+    pub use crate::accumulator::helper::Fallback as AccumulatorFallback;
     pub use crate::accumulator::Accumulator;
     pub use crate::array::Array;
     pub use crate::attach::attach;
@@ -72,7 +99,9 @@ pub mod plumbing {
     pub use crate::cycle::CycleRecoveryStrategy;
     pub use crate::database::current_revision;
     pub use crate::database::Database;
+    pub use crate::function::fingerprint;
     pub use crate::function::should_backdate_value;
+    pub use crate::hot::register_hot;
     pub use crate::id::AsId;
     pub use crate::id::FromId;
     pub use crate::id::Id;
@@ -80,12 +109,23 @@ pub mod plumbing {
     pub use crate::ingredient::Jar;
     pub use crate::ingredient::JarAux;
     pub use crate::key::DatabaseKeyIndex;
+    pub use crate::memory_budget::EstimatedSize;
+    #[cfg(feature = "serde")]
+    pub use crate::record::{
+        from_json, is_recording, is_recording_ids, record_new_input, replayed_id, to_json,
+        ReplayInput,
+    };
     pub use crate::revision::Revision;
     pub use crate::runtime::stamp;
     pub use crate::runtime::Runtime;
     pub use crate::runtime::Stamp;
     pub use crate::runtime::StampedValue;
     pub use crate::salsa_struct::SalsaStructInDb;
+    #[cfg(feature = "serde")]
+    pub use crate::serialization::{
+        deserialize_without_database, serialize_without_database, DeserializeFields,
+        SerializeFields,
+    };
     pub use crate::storage::HasStorage;
     pub use crate::storage::Storage;
     pub use crate::tracked_struct::TrackedStructInDb;
@@ -93,13 +133,18 @@ pub mod plumbing {
     pub use crate::update::helper::Dispatch as UpdateDispatch;
     pub use crate::update::helper::Fallback as UpdateFallback;
     pub use crate::update::Update;
+    pub use crate::watch::watch;
     pub use crate::zalsa::views;
+    pub use crate::zalsa::GenericIngredientCache;
     pub use crate::zalsa::IngredientCache;
     pub use crate::zalsa::IngredientIndex;
     pub use crate::zalsa::Zalsa;
     pub use crate::zalsa::ZalsaDatabase;
     pub use crate::zalsa_local::ZalsaLocal;
 
+    #[cfg(feature = "serde")]
+    pub use serde;
+
     pub use salsa_macro_rules::macro_if;
     pub use salsa_macro_rules::maybe_backdate;
     pub use salsa_macro_rules::maybe_clone;
@@ -107,6 +152,7 @@ pub mod plumbing {
     pub use salsa_macro_rules::maybe_default;
     pub use salsa_macro_rules::maybe_default_tt;
     pub use salsa_macro_rules::setup_accumulator_impl;
+    pub use salsa_macro_rules::setup_ingredient_cache;
     pub use salsa_macro_rules::setup_input_struct;
     pub use salsa_macro_rules::setup_interned_struct;
     pub use salsa_macro_rules::setup_method_body;
@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crossbeam::atomic::AtomicCell;
+
+use crate::{hash::FxDashMap, zalsa::Zalsa, DatabaseKeyIndex};
+
+/// Estimates the memory used by a value, in bytes.
+///
+/// Implemented for the return types of tracked functions that use the
+/// `estimated_size` option, so that their memoized values count against
+/// the memory budget of the database (see [`Database::set_memory_budget`](`crate::Database::set_memory_budget`)).
+/// The estimate should include the memory owned by the value on the heap.
+pub trait EstimatedSize {
+    fn estimated_size(&self) -> usize;
+}
+
+/// The memoized values counted against the memory budget of the database.
+#[derive(Default)]
+pub(crate) struct MemoryBudget {
+    /// The maximum total size of the values, if any.
+    budget: AtomicCell<Option<usize>>,
+
+    /// The size of each memoized value and the time it took to compute it.
+    values: FxDashMap<DatabaseKeyIndex, ValueCost>,
+
+    /// The total size of `values`.
+    used: AtomicUsize,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct ValueCost {
+    size: usize,
+    execution_time: Duration,
+}
+
+impl ValueCost {
+    /// Orders values from the cheapest to the most expensive to recompute per byte.
+    fn cmp_per_byte(&self, other: &Self) -> std::cmp::Ordering {
+        let lhs = self.execution_time.as_nanos() * other.size as u128;
+        let rhs = other.execution_time.as_nanos() * self.size as u128;
+        lhs.cmp(&rhs)
+    }
+}
+
+impl MemoryBudget {
+    pub(crate) fn budget(&self) -> Option<usize> {
+        self.budget.load()
+    }
+
+    pub(crate) fn set_budget(&self, budget: Option<usize>) {
+        self.budget.store(budget);
+    }
+
+    /// Records that a value of `size` bytes was memoized for `key`, after executing
+    /// its function for `execution_time` (not counting the queries it executed).
+    pub(crate) fn record(&self, key: DatabaseKeyIndex, size: usize, execution_time: Duration) {
+        let old = self.values.insert(
+            key,
+            ValueCost {
+                size,
+                execution_time,
+            },
+        );
+        self.used.fetch_add(size, Ordering::Relaxed);
+        if let Some(old) = old {
+            self.used.fetch_sub(old.size, Ordering::Relaxed);
+        }
+    }
+
+    /// Records that the value for `key` is no longer memoized, returning its size if it was recorded.
+    pub(crate) fn forget(&self, key: DatabaseKeyIndex) -> Option<usize> {
+        let (_, cost) = self.values.remove(&key)?;
+        self.used.fetch_sub(cost.size, Ordering::Relaxed);
+        Some(cost.size)
+    }
+
+    /// Evicts values until their total size fits within the budget,
+    /// starting with those that are the cheapest to recompute per byte.
+    ///
+    /// Values fetched during a revision may be referenced until it ends, so the budget
+    /// can only be enforced when a new revision starts, with an `&mut`-reference to the
+    /// database. The evicted values are dropped right away; this is invoked before the
+    /// deleted entries of the ingredients are cleared, so that no evicted memo outlives it.
+    pub(crate) fn evict_over_budget(&self, zalsa: &Zalsa) {
+        let Some(budget) = self.budget() else {
+            return;
+        };
+
+        let mut used = self.used.load(Ordering::Relaxed);
+        if used <= budget {
+            return;
+        }
+
+        let mut values: Vec<(DatabaseKeyIndex, ValueCost)> = self
+            .values
+            .iter()
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect();
+
+        values.sort_by(|(_, a), (_, b)| a.cmp_per_byte(b));
+        for (key, cost) in values {
+            if used <= budget {
+                break;
+            }
+            tracing::debug!("evicting {key:?} ({cost:?}) to fit the memory budget");
+            zalsa
+                .lookup_ingredient(key.ingredient_index)
+                .evict_value(zalsa, key.key_index);
+            self.forget(key);
+            used -= cost.size;
+        }
+    }
+}
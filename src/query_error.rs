@@ -0,0 +1,87 @@
+use std::{fmt, panic::AssertUnwindSafe};
+
+use crate::{zalsa_local::ZalsaLocal, Cancelled, Cycle};
+
+/// The error returned by the `try_call` variant of a tracked function
+/// when the query could not produce a value.
+///
+/// Without `try_call`, both of these conditions are reported by unwinding
+/// with the [`Cycle`] or [`Cancelled`] value as the panic payload.
+/// With `try_call`, they are returned as a value, without unwinding through the
+/// caller, and so are the errors of the `try_call`s made by the queries it executes.
+/// The queries it executes with plain calls still unwind up to the query that made
+/// the call, whose own caller gets the error.
+///
+/// A query that gets an error from `try_call` is executed again in the next revision.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum QueryError {
+    /// The query participated in a cycle and one or more of the
+    /// participants had no cycle recovery function.
+    Cycle(Cycle),
+
+    /// The query was cancelled, either because of a pending write
+    /// or because a query it was blocked on panicked.
+    Cancelled(Cancelled),
+}
+
+impl QueryError {
+    /// Runs the function of a query, converting a salsa cycle or cancellation
+    /// unwinding from a plain call into an error. Any other panic is resumed.
+    pub(crate) fn catch<T>(f: impl FnOnce() -> T) -> Result<T, QueryError> {
+        match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(v) => Ok(v),
+            Err(payload) => match payload.downcast::<Cycle>() {
+                Ok(cycle) => Err(QueryError::Cycle(*cycle)),
+                Err(payload) => match payload.downcast::<Cancelled>() {
+                    Ok(cancelled) => Err(QueryError::Cancelled(*cancelled)),
+                    Err(payload) => std::panic::resume_unwind(payload),
+                },
+            },
+        }
+    }
+
+    /// Reports the error like a plain call does, by unwinding.
+    ///
+    /// If no query is active, an unrecoverable cycle is reported by panicking with the
+    /// [`Cycle`], so that the panic hook runs; otherwise, the panic is only resumed.
+    pub(crate) fn throw(self, zalsa_local: &ZalsaLocal) -> ! {
+        match self {
+            QueryError::Cycle(cycle) if zalsa_local.active_query().is_none() => {
+                std::panic::panic_any(cycle)
+            }
+            QueryError::Cycle(cycle) => cycle.throw(),
+            QueryError::Cancelled(cancelled) => cancelled.throw(),
+        }
+    }
+}
+
+impl From<Cycle> for QueryError {
+    fn from(cycle: Cycle) -> Self {
+        QueryError::Cycle(cycle)
+    }
+}
+
+impl From<Cancelled> for QueryError {
+    fn from(cancelled: Cancelled) -> Self {
+        QueryError::Cancelled(cancelled)
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Cycle(cycle) => write!(f, "query cycle: {cycle:?}"),
+            QueryError::Cancelled(cancelled) => fmt::Display::fmt(cancelled, f),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            QueryError::Cycle(_) => None,
+            QueryError::Cancelled(cancelled) => Some(cancelled),
+        }
+    }
+}
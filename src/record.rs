@@ -0,0 +1,425 @@
+//! Recording the writes to inputs, and replaying them on another database.
+//!
+//! Only inputs declared with the `serde` option are recorded: their field values
+//! are serialized as JSON when the input is created or one of its fields is set.
+//! The other inputs that were created or written to are listed in
+//! [`Recording::unrecorded_inputs`], as the replay may not reproduce their effects.
+//!
+//! Field values referring to other inputs record their id rather than their fields,
+//! so that the replay refers to the input created in place of that one.
+//! Those inputs must therefore be created while recording.
+
+use std::{cell::RefCell, fmt};
+
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{hash::FxHashMap, id::FromId, Database, Durability, Id};
+
+/// The writes to inputs recorded between [`Database::start_recording`](`crate::Database::start_recording`)
+/// and [`Database::stop_recording`](`crate::Database::stop_recording`), in order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recording {
+    pub events: Vec<RecordedEvent>,
+
+    /// The inputs without the `serde` option that were created or written to while recording.
+    #[serde(default)]
+    pub unrecorded_inputs: Vec<String>,
+
+    /// The writes that were left out of the recording because a field value could not be serialized.
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+/// A write to the database. Field values are serialized as JSON,
+/// and durabilities as their level (0 for `LOW` to 2 for `HIGH`).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    /// An input was created.
+    NewInput {
+        input: String,
+        id: u32,
+        fields: Vec<String>,
+        durabilities: Vec<u8>,
+    },
+
+    /// A field of an input was set.
+    SetField {
+        input: String,
+        id: u32,
+        field_index: usize,
+        value: String,
+        durability: Option<u8>,
+    },
+
+    /// [`Database::synthetic_write`](`crate::Database::synthetic_write`) was invoked.
+    SyntheticWrite { durability: u8 },
+}
+
+/// Error returned by [`Replay::run`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The recording refers to an input type that was not registered with [`Replay::input`].
+    UnknownInput(String),
+
+    /// The recording sets a field of an input that it did not create.
+    UnknownId(u32),
+
+    /// The recording creates an input with a different number of fields than its type has.
+    FieldCount {
+        input: String,
+        expected: usize,
+        found: usize,
+    },
+
+    /// The recording sets a field that the input type does not have.
+    UnknownField { input: String, field_index: usize },
+
+    /// A field value could not be deserialized.
+    Deserialize(String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::UnknownInput(input) => write!(f, "unknown input `{input}`"),
+            ReplayError::UnknownId(id) => write!(f, "unknown input id {id}"),
+            ReplayError::FieldCount {
+                input,
+                expected,
+                found,
+            } => write!(f, "`{input}` has {expected} fields, found {found}"),
+            ReplayError::UnknownField { input, field_index } => {
+                write!(f, "`{input}` has no field {field_index}")
+            }
+            ReplayError::Deserialize(error) => write!(f, "cannot deserialize field: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// Implemented by inputs with the `serde` option, so that their creation
+/// and field writes can be replayed.
+pub trait ReplayInput {
+    /// The name of the input in recordings, qualified with the path of its module
+    /// so that inputs with the same name in different modules are told apart.
+    const RECORDED_NAME: &'static str;
+
+    const FIELD_COUNT: usize;
+
+    fn replay_new(
+        db: &dyn Database,
+        fields: &[String],
+        durabilities: &[Durability],
+    ) -> Result<Id, ReplayError>;
+
+    fn replay_set(
+        db: &mut dyn Database,
+        id: Id,
+        field_index: usize,
+        value: &str,
+        durability: Option<Durability>,
+    ) -> Result<(), ReplayError>;
+}
+
+/// The state of a replay after an event, see [`Replay::run`].
+pub struct ReplayStep<'a> {
+    /// Index of the event in the recording.
+    pub index: usize,
+
+    /// The event that was just replayed.
+    pub event: &'a RecordedEvent,
+
+    ids: &'a FxHashMap<u32, Id>,
+}
+
+impl ReplayStep<'_> {
+    /// The input created in place of the input `recorded_id` of the recording, if any.
+    pub fn input<I: FromId>(&self, recorded_id: u32) -> Option<I> {
+        self.ids.get(&recorded_id).copied().map(I::from_id)
+    }
+}
+
+/// Replays a [`Recording`] on a database.
+pub struct Replay<'r> {
+    recording: &'r Recording,
+    inputs: FxHashMap<&'static str, ReplayFns>,
+}
+
+type ReplayNew = fn(&dyn Database, &[String], &[Durability]) -> Result<Id, ReplayError>;
+
+type ReplaySet =
+    fn(&mut dyn Database, Id, usize, &str, Option<Durability>) -> Result<(), ReplayError>;
+
+struct ReplayFns {
+    field_count: usize,
+    new: ReplayNew,
+    set: ReplaySet,
+}
+
+impl<'r> Replay<'r> {
+    pub fn new(recording: &'r Recording) -> Self {
+        Self {
+            recording,
+            inputs: FxHashMap::default(),
+        }
+    }
+
+    /// Registers the input type `I`, which the recording may create and write to.
+    ///
+    /// # Panics
+    ///
+    /// If `I` was already registered.
+    pub fn input<I: ReplayInput>(mut self) -> Self {
+        let previous = self.inputs.insert(
+            I::RECORDED_NAME,
+            ReplayFns {
+                field_count: I::FIELD_COUNT,
+                new: I::replay_new,
+                set: I::replay_set,
+            },
+        );
+        assert!(
+            previous.is_none(),
+            "input `{}` is registered twice",
+            I::RECORDED_NAME
+        );
+        self
+    }
+
+    /// Applies the recorded events to `db`, in order. After each event, `check` is invoked,
+    /// e.g. to compare the results of some queries with the expected ones.
+    pub fn run<Db: Database>(
+        &self,
+        db: &mut Db,
+        mut check: impl FnMut(&Db, &ReplayStep<'_>),
+    ) -> Result<(), ReplayError> {
+        // The ids of the inputs created by the recording, in the database it was recorded on.
+        let mut ids: FxHashMap<u32, Id> = FxHashMap::default();
+
+        for (index, event) in self.recording.events.iter().enumerate() {
+            match event {
+                RecordedEvent::NewInput {
+                    input,
+                    id,
+                    fields,
+                    durabilities,
+                } => {
+                    let fns = self.fns(input)?;
+                    for found in [fields.len(), durabilities.len()] {
+                        if found != fns.field_count {
+                            return Err(ReplayError::FieldCount {
+                                input: input.clone(),
+                                expected: fns.field_count,
+                                found,
+                            });
+                        }
+                    }
+                    let durabilities: Vec<Durability> =
+                        durabilities.iter().map(|&d| durability(d)).collect();
+                    let new_id = replaying(&mut ids, || (fns.new)(db, fields, &durabilities))?;
+                    ids.insert(*id, new_id);
+                }
+                RecordedEvent::SetField {
+                    input,
+                    id,
+                    field_index,
+                    value,
+                    durability: d,
+                } => {
+                    let fns = self.fns(input)?;
+                    if *field_index >= fns.field_count {
+                        return Err(ReplayError::UnknownField {
+                            input: input.clone(),
+                            field_index: *field_index,
+                        });
+                    }
+                    let new_id = *ids.get(id).ok_or(ReplayError::UnknownId(*id))?;
+                    replaying(&mut ids, || {
+                        (fns.set)(db, new_id, *field_index, value, d.map(durability))
+                    })?;
+                }
+                RecordedEvent::SyntheticWrite { durability: d } => {
+                    db.synthetic_write(durability(*d));
+                }
+            }
+
+            check(
+                db,
+                &ReplayStep {
+                    index,
+                    event,
+                    ids: &ids,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    fn fns(&self, input: &str) -> Result<&ReplayFns, ReplayError> {
+        self.inputs
+            .get(input)
+            .ok_or_else(|| ReplayError::UnknownInput(input.to_string()))
+    }
+}
+
+/// What was recorded so far, if recording.
+#[derive(Default)]
+pub(crate) struct Recorder {
+    recording: Mutex<Option<Recording>>,
+}
+
+impl Recorder {
+    pub(crate) fn start(&self) {
+        *self.recording.lock() = Some(Recording::default());
+    }
+
+    pub(crate) fn stop(&self) -> Recording {
+        self.recording.lock().take().unwrap_or_default()
+    }
+
+    pub(crate) fn is_recording(&self) -> bool {
+        self.recording.lock().is_some()
+    }
+
+    pub(crate) fn record(&self, event: impl FnOnce() -> RecordedEvent) {
+        if let Some(recording) = &mut *self.recording.lock() {
+            recording.events.push(event());
+        }
+    }
+
+    /// Records that `input`, which has no `serde` option, was created or written to.
+    pub(crate) fn record_unrecorded(&self, input: &'static str) {
+        if let Some(recording) = &mut *self.recording.lock() {
+            if !recording.unrecorded_inputs.iter().any(|i| i == input) {
+                recording.unrecorded_inputs.push(input.to_string());
+            }
+        }
+    }
+
+    fn record_error(&self, error: impl FnOnce() -> String) {
+        if let Some(recording) = &mut *self.recording.lock() {
+            recording.errors.push(error());
+        }
+    }
+}
+
+thread_local! {
+    /// Set while serializing field values for a recording, or deserializing them for a replay.
+    static RECORDED_IDS: RefCell<Option<RecordedIds>> = const { RefCell::new(None) };
+}
+
+enum RecordedIds {
+    Recording,
+
+    /// The inputs created by the replay in place of those of the recorded database.
+    Replaying(FxHashMap<u32, Id>),
+}
+
+fn with_recorded_ids<R>(recorded_ids: RecordedIds, op: impl FnOnce() -> R) -> (R, RecordedIds) {
+    let previous = RECORDED_IDS.with(|ids| ids.replace(Some(recorded_ids)));
+    let result = op();
+    let recorded_ids = RECORDED_IDS.with(|ids| ids.replace(previous)).unwrap();
+    (result, recorded_ids)
+}
+
+fn replaying<R>(ids: &mut FxHashMap<u32, Id>, op: impl FnOnce() -> R) -> R {
+    let (result, recorded_ids) = with_recorded_ids(RecordedIds::Replaying(std::mem::take(ids)), op);
+    if let RecordedIds::Replaying(replayed_ids) = recorded_ids {
+        *ids = replayed_ids;
+    }
+    result
+}
+
+/// True while serializing field values for a recording, or deserializing them for a replay.
+/// Inputs are then represented by their id in the recorded database.
+pub fn is_recording_ids() -> bool {
+    RECORDED_IDS.with(|ids| ids.borrow().is_some())
+}
+
+/// The input created by the replay in place of the input `recorded_id` of the recording.
+pub fn replayed_id<E: serde::de::Error>(recorded_id: u32) -> Result<Id, E> {
+    RECORDED_IDS
+        .with(|ids| match &*ids.borrow() {
+            Some(RecordedIds::Replaying(ids)) => ids.get(&recorded_id).copied(),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            E::custom(format_args!(
+                "input {recorded_id} was not created by the recording"
+            ))
+        })
+}
+
+fn durability(level: u8) -> Durability {
+    match level {
+        0 => Durability::LOW,
+        1 => Durability::MEDIUM,
+        _ => Durability::HIGH,
+    }
+}
+
+/// True if the writes to the inputs of `db` are being recorded.
+pub fn is_recording(db: &dyn Database) -> bool {
+    db.zalsa().recorder().is_recording()
+}
+
+/// Records that the input `id` was created. Invoked by the generated code.
+pub fn record_new_input(
+    db: &dyn Database,
+    input: &'static str,
+    id: Id,
+    fields: Result<Vec<String>, String>,
+    durabilities: impl Iterator<Item = Durability>,
+) {
+    let recorder = db.zalsa().recorder();
+    match fields {
+        Ok(fields) => recorder.record(|| RecordedEvent::NewInput {
+            input: input.to_string(),
+            id: id.as_u32(),
+            fields,
+            durabilities: durabilities.map(|d| d.index() as u8).collect(),
+        }),
+        Err(error) => recorder
+            .record_error(|| format!("cannot record new `{input}` {}: {error}", id.as_u32())),
+    }
+}
+
+pub(crate) fn record_set_field(
+    recorder: &Recorder,
+    input: &'static str,
+    id: Id,
+    field_index: usize,
+    value: Result<String, String>,
+    durability: Option<Durability>,
+) {
+    match value {
+        Ok(value) => recorder.record(|| RecordedEvent::SetField {
+            input: input.to_string(),
+            id: id.as_u32(),
+            field_index,
+            value,
+            durability: durability.map(|d| d.index() as u8),
+        }),
+        Err(error) => recorder.record_error(|| {
+            format!(
+                "cannot record field {field_index} of `{input}` {}: {error}",
+                id.as_u32()
+            )
+        }),
+    }
+}
+
+/// Serializes a field value for a recording.
+pub fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    with_recorded_ids(RecordedIds::Recording, || serde_json::to_string(value))
+        .0
+        .map_err(|error| error.to_string())
+}
+
+/// Deserializes a field value from a recording.
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, ReplayError> {
+    serde_json::from_str(json).map_err(|error| ReplayError::Deserialize(error.to_string()))
+}
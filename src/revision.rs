@@ -31,7 +31,7 @@ impl Revision {
         Self::from(self.generation.get() + 1)
     }
 
-    fn as_usize(self) -> usize {
+    pub(crate) fn as_usize(self) -> usize {
         self.generation.get()
     }
 }
@@ -1,5 +1,4 @@
 use std::{
-    panic::panic_any,
     sync::{atomic::AtomicUsize, Arc},
     thread::ThreadId,
 };
@@ -10,11 +9,13 @@ use parking_lot::Mutex;
 use crate::{
     active_query::ActiveQuery, cycle::CycleRecoveryStrategy, durability::Durability,
     key::DatabaseKeyIndex, revision::AtomicRevision, table::Table, zalsa_local::ZalsaLocal,
-    Cancelled, Cycle, Database, Event, EventKind, Revision,
+    Cancelled, Cycle, Database, Event, EventKind, QueryError, Revision,
 };
 
+use self::change_log::ChangeLog;
 use self::dependency_graph::DependencyGraph;
 
+pub(crate) mod change_log;
 mod dependency_graph;
 
 pub struct Runtime {
@@ -43,6 +44,13 @@ pub struct Runtime {
 
     /// Data for instances
     table: Table,
+
+    /// The most recent writes to input fields.
+    change_log: ChangeLog,
+
+    /// The writes to inputs recorded since `Database::start_recording`.
+    #[cfg(feature = "serde")]
+    recorder: crate::record::Recorder,
 }
 
 #[derive(Clone, Debug)]
@@ -88,6 +96,9 @@ impl Default for Runtime {
             revision_canceled: Default::default(),
             dependency_graph: Default::default(),
             table: Default::default(),
+            change_log: Default::default(),
+            #[cfg(feature = "serde")]
+            recorder: Default::default(),
         }
     }
 }
@@ -142,6 +153,23 @@ impl Runtime {
         &self.table
     }
 
+    pub(crate) fn table_mut(&mut self) -> &mut Table {
+        &mut self.table
+    }
+
+    pub(crate) fn change_log(&self) -> &ChangeLog {
+        &self.change_log
+    }
+
+    pub(crate) fn change_log_mut(&mut self) -> &mut ChangeLog {
+        &mut self.change_log
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn recorder(&self) -> &crate::record::Recorder {
+        &self.recorder
+    }
+
     /// Increments the "current revision" counter and clears
     /// the cancellation flag.
     ///
@@ -155,7 +183,7 @@ impl Runtime {
     }
 
     /// Block until `other_id` completes executing `database_key`;
-    /// panic or unwind in the case of a cycle.
+    /// return an error or unwind in the case of a cycle.
     ///
     /// `query_mutex_guard` is the guard for the current query's state;
     /// it will be dropped after we have successfully registered the
@@ -164,17 +192,16 @@ impl Runtime {
     /// # Propagating panics
     ///
     /// If the thread `other_id` panics, then our thread is considered
-    /// cancelled, so this function will panic with a `Cancelled` value.
+    /// cancelled, so this function will return a `Cancelled` error.
     ///
     /// # Cycle handling
     ///
     /// If the thread `other_id` already depends on the current thread,
     /// and hence there is a cycle in the query graph, then this function
-    /// will unwind instead of returning normally. The method of unwinding
-    /// depends on the [`Self::mutual_cycle_recovery_strategy`]
+    /// will not block. What happens instead depends on the [`CycleRecoveryStrategy`]
     /// of the cycle participants:
     ///
-    /// * [`CycleRecoveryStrategy::Panic`]: panic with the [`Cycle`] as the value.
+    /// * [`CycleRecoveryStrategy::Panic`]: return the [`Cycle`] as an error.
     /// * [`CycleRecoveryStrategy::Fallback`]: initiate unwinding with [`CycleParticipant::unwind`].
     pub(crate) fn block_on_or_unwind<QueryMutexGuard>(
         &self,
@@ -183,12 +210,12 @@ impl Runtime {
         database_key: DatabaseKeyIndex,
         other_id: ThreadId,
         query_mutex_guard: QueryMutexGuard,
-    ) {
+    ) -> Result<(), QueryError> {
         let mut dg = self.dependency_graph.lock();
         let thread_id = std::thread::current().id();
 
         if dg.depends_on(other_id, thread_id) {
-            self.unblock_cycle_and_maybe_throw(db, local_state, &mut dg, database_key, other_id);
+            self.unblock_cycle_and_maybe_throw(db, local_state, &mut dg, database_key, other_id)?;
 
             // If the above fn returns, then (via cycle recovery) it has unblocked the
             // cycle, so we can continue.
@@ -217,12 +244,12 @@ impl Runtime {
         local_state.restore_query_stack(stack);
 
         match result {
-            WaitResult::Completed => (),
+            WaitResult::Completed => Ok(()),
 
             // If the other thread panicked, then we consider this thread
             // cancelled. The assumption is that the panic will be detected
             // by the other thread and responded to appropriately.
-            WaitResult::Panicked => Cancelled::PropagatedPanic.throw(),
+            WaitResult::Panicked => Err(Cancelled::PropagatedPanic.into()),
 
             WaitResult::Cycle(c) => c.throw(),
         }
@@ -232,10 +259,10 @@ impl Runtime {
     /// current thread tried to block on `database_key_index` which is being
     /// executed by `to_id`. If this function returns, then `to_id` no longer
     /// depends on the current thread, and so we should continue executing
-    /// as normal. Otherwise, the function will throw a `Cycle` which is expected
-    /// to be caught by some frame on our stack. This occurs either if there is
-    /// a frame on our stack with cycle recovery (possibly the top one!) or if there
-    /// is no cycle recovery at all.
+    /// as normal. If there is a frame on our stack with cycle recovery (possibly
+    /// the top one!), the function will throw a `Cycle` which is expected to be
+    /// caught by that frame. If there is no cycle recovery at all, the `Cycle`
+    /// is returned as an error.
     fn unblock_cycle_and_maybe_throw(
         &self,
         db: &dyn Database,
@@ -243,7 +270,7 @@ impl Runtime {
         dg: &mut DependencyGraph,
         database_key_index: DatabaseKeyIndex,
         to_id: ThreadId,
-    ) {
+    ) -> Result<(), Cycle> {
         tracing::debug!(
             "unblock_cycle_and_maybe_throw(database_key={:?})",
             database_key_index
@@ -336,9 +363,10 @@ impl Runtime {
             cycle.throw()
         } else if others_recovered {
             // If other threads have recovery but we didn't: return and we will block on them.
+            Ok(())
         } else {
-            // if nobody has recover, then we panic
-            panic_any(cycle);
+            // If nobody has recovery, then the query fails with the cycle.
+            Err(cycle)
         }
     }
 
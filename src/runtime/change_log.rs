@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+
+use crate::{zalsa::IngredientIndex, Durability, Id, Revision};
+
+/// Number of changes kept by default, see [`Database::set_change_log_capacity`](`crate::Database::set_change_log_capacity`).
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// A write to a field of an input, see [`Database::changes_since`](`crate::Database::changes_since`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InputChange {
+    /// The revision in which the field was written.
+    pub revision: Revision,
+
+    /// The ingredient of the field.
+    pub ingredient_index: IngredientIndex,
+
+    /// The input whose field was written.
+    pub id: Id,
+
+    /// The index of the field among the fields of the input.
+    pub field_index: usize,
+
+    /// The durability of the field after the write.
+    pub durability: Durability,
+}
+
+/// The most recent writes to input fields, oldest first.
+#[derive(Debug)]
+pub(crate) struct ChangeLog {
+    capacity: usize,
+    changes: VecDeque<InputChange>,
+
+    /// Revision of the most recent change dropped to respect the capacity, if any.
+    dropped_at: Option<Revision>,
+}
+
+impl Default for ChangeLog {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            changes: VecDeque::new(),
+            dropped_at: None,
+        }
+    }
+}
+
+impl ChangeLog {
+    pub(crate) fn record(&mut self, change: InputChange) {
+        self.changes.push_back(change);
+        self.truncate();
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.truncate();
+    }
+
+    fn truncate(&mut self) {
+        while self.changes.len() > self.capacity {
+            let dropped = self.changes.pop_front().unwrap();
+            self.dropped_at = Some(dropped.revision);
+        }
+    }
+
+    /// Returns the changes made after `revision`, or `None` if some of them were dropped.
+    pub(crate) fn changes_since(&self, revision: Revision) -> Option<Vec<InputChange>> {
+        if self
+            .dropped_at
+            .is_some_and(|dropped_at| dropped_at > revision)
+        {
+            return None;
+        }
+
+        Some(
+            self.changes
+                .iter()
+                .filter(|change| change.revision > revision)
+                .copied()
+                .collect(),
+        )
+    }
+}
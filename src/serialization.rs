@@ -0,0 +1,183 @@
+//! Support for (de)serializing the fields of salsa structs.
+//!
+//! The generated `Serialize`/`Deserialize` impls read the fields of a struct
+//! from the attached database and create (or re-intern) the struct on load.
+//! The fields are handled as a tuple, which is written as a struct with the
+//! given field names.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeStruct, Serializer},
+    Deserialize, Serialize,
+};
+
+/// A tuple of field values that can be serialized as a struct.
+pub trait SerializeFields {
+    fn serialize_fields<S: Serializer>(
+        &self,
+        serializer: S,
+        name: &'static str,
+        field_names: &'static [&'static str],
+    ) -> Result<S::Ok, S::Error>;
+}
+
+/// A tuple of field values that can be deserialized from a struct.
+pub trait DeserializeFields<'de>: Sized {
+    fn deserialize_fields<D: Deserializer<'de>>(
+        deserializer: D,
+        name: &'static str,
+        field_names: &'static [&'static str],
+    ) -> Result<Self, D::Error>;
+}
+
+/// Error for serializing a salsa struct when no database is attached.
+pub fn serialize_without_database<E: serde::ser::Error>(name: &str) -> E {
+    E::custom(format_args!(
+        "cannot serialize `{name}` without an attached database"
+    ))
+}
+
+/// Error for deserializing a salsa struct when no database is attached.
+pub fn deserialize_without_database<E: de::Error>(name: &str) -> E {
+    E::custom(format_args!(
+        "cannot deserialize `{name}` without an attached database"
+    ))
+}
+
+/// Deserializes a field name (or index) into the index of the field,
+/// or `None` for an unknown field.
+struct FieldIndex(&'static [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for FieldIndex {
+    type Value = Option<usize>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FieldIndex {
+    type Value = Option<usize>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a field identifier")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(usize::try_from(v).ok().filter(|&i| i < self.0.len()))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(self.0.iter().position(|name| *name == v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(self.0.iter().position(|name| name.as_bytes() == v))
+    }
+}
+
+struct FieldsVisitor<T> {
+    field_names: &'static [&'static str],
+    phantom: PhantomData<fn() -> T>,
+}
+
+macro_rules! tuple_impl {
+    ($($t:ident $v:ident $i:tt),*) => {
+        impl<$($t),*> SerializeFields for ($($t,)*)
+        where
+            $($t: Serialize,)*
+        {
+            fn serialize_fields<Ser: Serializer>(
+                &self,
+                serializer: Ser,
+                name: &'static str,
+                field_names: &'static [&'static str],
+            ) -> Result<Ser::Ok, Ser::Error> {
+                #[allow(unused_mut)]
+                let mut state = serializer.serialize_struct(name, field_names.len())?;
+                $(
+                    state.serialize_field(field_names[$i], &self.$i)?;
+                )*
+                state.end()
+            }
+        }
+
+        impl<'de, $($t),*> DeserializeFields<'de> for ($($t,)*)
+        where
+            $($t: Deserialize<'de>,)*
+        {
+            fn deserialize_fields<De: Deserializer<'de>>(
+                deserializer: De,
+                name: &'static str,
+                field_names: &'static [&'static str],
+            ) -> Result<Self, De::Error> {
+                let visitor = FieldsVisitor::<Self> {
+                    field_names,
+                    phantom: PhantomData,
+                };
+                deserializer.deserialize_struct(name, field_names, visitor)
+            }
+        }
+
+        impl<'de, $($t),*> Visitor<'de> for FieldsVisitor<($($t,)*)>
+        where
+            $($t: Deserialize<'de>,)*
+        {
+            type Value = ($($t,)*);
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a struct with fields {:?}", self.field_names)
+            }
+
+            #[allow(unused_mut, unused_variables)]
+            fn visit_seq<Seq: SeqAccess<'de>>(self, mut seq: Seq) -> Result<Self::Value, Seq::Error> {
+                Ok(($(
+                    seq.next_element::<$t>()?
+                        .ok_or_else(|| de::Error::invalid_length($i, &self))?,
+                )*))
+            }
+
+            #[allow(unused_mut)]
+            fn visit_map<Map: MapAccess<'de>>(self, mut map: Map) -> Result<Self::Value, Map::Error> {
+                $(
+                    let mut $v: Option<$t> = None;
+                )*
+                while let Some(index) = map.next_key_seed(FieldIndex(self.field_names))? {
+                    match index {
+                        $(
+                            Some($i) => {
+                                if $v.is_some() {
+                                    return Err(de::Error::duplicate_field(self.field_names[$i]));
+                                }
+                                $v = Some(map.next_value()?);
+                            }
+                        )*
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok(($(
+                    $v.ok_or_else(|| de::Error::missing_field(self.field_names[$i]))?,
+                )*))
+            }
+        }
+    };
+}
+
+// Create implementations for tuples up to arity 12
+tuple_impl!();
+tuple_impl!(A a 0);
+tuple_impl!(A a 0, B b 1);
+tuple_impl!(A a 0, B b 1, C c 2);
+tuple_impl!(A a 0, B b 1, C c 2, D d 3);
+tuple_impl!(A a 0, B b 1, C c 2, D d 3, E e 4);
+tuple_impl!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5);
+tuple_impl!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6);
+tuple_impl!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6, H h 7);
+tuple_impl!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6, H h 7, I i 8);
+tuple_impl!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6, H h 7, I i 8, J j 9);
+tuple_impl!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6, H h 7, I i 8, J j 9, K k 10);
+tuple_impl!(A a 0, B b 1, C c 2, D d 3, E e 4, F f 5, G g 6, H h 7, I i 8, J j 9, K k 10, L l 11);
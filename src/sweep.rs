@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use crate::{table::memo::Memo, DatabaseKeyIndex, IngredientIndex};
+
+/// Options for [`Database::sweep`](`crate::Database::sweep`).
+#[derive(Clone, Debug, Default)]
+pub struct SweepOptions {
+    /// Memos that were last verified more than this many revisions
+    /// before the current one are swept.
+    pub older_than_revisions: usize,
+
+    /// If `Some`, only the memos of these ingredients are swept.
+    pub ingredients: Option<Vec<IngredientIndex>>,
+}
+
+/// What was freed by [`Database::sweep`](`crate::Database::sweep`).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SweepReport {
+    /// Number of memos that were dropped.
+    pub memos: usize,
+
+    /// Number of values held by those memos.
+    pub values: usize,
+
+    /// Number of dependency edges recorded by those memos.
+    pub edges: usize,
+
+    /// Number of tracked structs that were deleted, because the memos that created them
+    /// were dropped and no other memo could reference them.
+    pub tracked_structs: usize,
+
+    /// Estimated number of bytes freed by dropping the memos: their own size, their edges,
+    /// and the size of their values for functions with the `estimated_size` option.
+    pub bytes: usize,
+}
+
+/// The memos dropped by a sweep, whose outputs remain to be discarded.
+pub(crate) type SweptMemos = Vec<(DatabaseKeyIndex, Arc<dyn Memo>)>;
@@ -40,11 +40,23 @@ pub(crate) trait TablePage: Any + Send + Sync {
     ///
     /// The `current_revision` MUST be the current revision of the database owning this table page.
     unsafe fn syncs(&self, slot: SlotIndex, current_revision: Revision) -> &SyncTable;
+
+    /// The ingredient whose entries are stored on this page.
+    fn ingredient(&self) -> IngredientIndex;
+
+    /// The number of allocated slots.
+    fn allocated(&self) -> usize;
+
+    /// Invokes `f` with the [`MemoTable`][] of each allocated slot, given that this is page `page`.
+    /// See [`Slot::peek_memos`][].
+    fn for_each_memo_table(&self, page: PageIndex, f: &mut dyn FnMut(Id, &MemoTable));
+
+    /// Invokes `f` with the [`MemoTable`][] of each allocated slot, given that this is page `page`.
+    fn for_each_memo_table_mut(&mut self, page: PageIndex, f: &mut dyn FnMut(Id, &mut MemoTable));
 }
 
 pub(crate) struct Page<T: Slot> {
     /// The ingredient for elements on this page.
-    #[allow(dead_code)] // pretty sure we'll need this
     ingredient: IngredientIndex,
 
     /// Number of elements of `data` that are initialized.
@@ -79,6 +91,13 @@ pub(crate) trait Slot: Any + Send + Sync {
     ///
     /// The current revision MUST be the current revision of the database containing this slot.
     unsafe fn syncs(&self, current_revision: Revision) -> &SyncTable;
+
+    /// Access the [`MemoTable`][] for this slot without any synchronization
+    /// with the current revision, e.g. to count the memos it holds.
+    fn peek_memos(&self) -> &MemoTable;
+
+    /// Access the [`MemoTable`][] for this slot, given that nobody else can.
+    fn memos_mut(&mut self) -> &mut MemoTable;
 }
 
 unsafe impl<T: Slot> Send for Page<T> {}
@@ -164,6 +183,47 @@ impl Table {
         let (page, slot) = split_id(id);
         self.pages[page.0].syncs(slot, current_revision)
     }
+
+    /// The number of slots allocated for the entries of `ingredient`.
+    pub(crate) fn allocated_slots(&self, ingredient: IngredientIndex) -> usize {
+        self.pages
+            .iter()
+            .filter(|page| page.ingredient() == ingredient)
+            .map(|page| page.allocated())
+            .sum()
+    }
+
+    /// Iterates over the allocated slots of `ingredient`, which must have type `T`, in id order.
+    /// Slots allocated while iterating may or may not be visited.
+    pub(crate) fn slots<T: Slot>(
+        &self,
+        ingredient: IngredientIndex,
+    ) -> impl Iterator<Item = (Id, &T)> + '_ {
+        self.pages
+            .iter()
+            .enumerate()
+            .filter(move |(_, page)| page.ingredient() == ingredient)
+            .flat_map(|(page, data)| {
+                let page = PageIndex(page);
+                let data = data.assert_type::<Page<T>>();
+                (0..data.allocated())
+                    .map(move |slot| (make_id(page, SlotIndex(slot)), data.get(SlotIndex(slot))))
+            })
+    }
+
+    /// Invokes `f` with the [`MemoTable`][] of each allocated slot, see [`Slot::peek_memos`][].
+    pub(crate) fn for_each_memo_table(&self, mut f: impl FnMut(Id, &MemoTable)) {
+        for (page, data) in self.pages.iter().enumerate() {
+            data.for_each_memo_table(PageIndex(page), &mut f);
+        }
+    }
+
+    /// Invokes `f` with the memo table associated with each allocated id.
+    pub(crate) fn for_each_memo_table_mut(&mut self, mut f: impl FnMut(Id, &mut MemoTable)) {
+        for page in 0..self.pages.len() {
+            self.pages[page].for_each_memo_table_mut(PageIndex(page), &mut f);
+        }
+    }
 }
 
 impl<T: Slot> Page<T> {
@@ -245,6 +305,31 @@ impl<T: Slot> TablePage for Page<T> {
     unsafe fn syncs(&self, slot: SlotIndex, current_revision: Revision) -> &SyncTable {
         self.get(slot).syncs(current_revision)
     }
+
+    fn ingredient(&self) -> IngredientIndex {
+        self.ingredient
+    }
+
+    fn allocated(&self) -> usize {
+        self.allocated.load()
+    }
+
+    fn for_each_memo_table(&self, page: PageIndex, f: &mut dyn FnMut(Id, &MemoTable)) {
+        let len = self.allocated.load();
+        for slot in 0..len {
+            f(
+                make_id(page, SlotIndex(slot)),
+                self.get(SlotIndex(slot)).peek_memos(),
+            );
+        }
+    }
+
+    fn for_each_memo_table_mut(&mut self, page: PageIndex, f: &mut dyn FnMut(Id, &mut MemoTable)) {
+        let len = self.allocated.load();
+        for (slot, data) in self.data[..len].iter_mut().enumerate() {
+            f(make_id(page, SlotIndex(slot)), data.get_mut().memos_mut());
+        }
+    }
 }
 
 impl<T: Slot> Drop for Page<T> {
@@ -7,7 +7,7 @@ use std::{
 use arc_swap::ArcSwap;
 use parking_lot::RwLock;
 
-use crate::{zalsa::MemoIngredientIndex, zalsa_local::QueryOrigin};
+use crate::{zalsa::MemoIngredientIndex, zalsa_local::QueryOrigin, Revision};
 
 /// The "memo table" stores the memoized results of tracked function calls.
 /// Every tracked function must take a salsa struct as its first argument
@@ -20,6 +20,12 @@ pub(crate) struct MemoTable {
 pub(crate) trait Memo: Any + Send + Sync + Debug {
     /// Returns the `origin` of this memo
     fn origin(&self) -> &QueryOrigin;
+
+    /// Returns the last revision in which this memo was verified
+    fn verified_at(&self) -> Revision;
+
+    /// Returns true if this memo holds a value (as opposed to a panic or an evicted value)
+    fn has_value(&self) -> bool;
 }
 
 /// Wraps the data stored for a memoized entry.
@@ -163,6 +169,53 @@ impl MemoTable {
         unsafe { Some(Self::from_dummy(arc_swap.load_full())) }
     }
 
+    /// Returns true if a memo is stored for `memo_ingredient_index`.
+    pub(crate) fn contains(&self, memo_ingredient_index: MemoIngredientIndex) -> bool {
+        self.memos
+            .read()
+            .get(memo_ingredient_index.as_usize())
+            .is_some_and(|entry| entry.data.is_some())
+    }
+
+    /// Invokes `f` with each memo, given that nobody else can access them.
+    pub(crate) fn for_each(&mut self, mut f: impl FnMut(MemoIngredientIndex, Arc<dyn Memo>)) {
+        for (index, entry) in self.memos.get_mut().iter().enumerate() {
+            if let Some(data) = &entry.data {
+                f(
+                    MemoIngredientIndex::from_usize(index),
+                    (data.to_dyn_fn)(data.arc_swap.load_full()),
+                );
+            }
+        }
+    }
+
+    /// Removes the memos for which `remove` returns true and returns them.
+    pub(crate) fn remove_if(
+        &mut self,
+        mut remove: impl FnMut(MemoIngredientIndex, &dyn Memo) -> bool,
+    ) -> Vec<(MemoIngredientIndex, Arc<dyn Memo>)> {
+        let mut removed = vec![];
+        for (index, entry) in self.memos.get_mut().iter_mut().enumerate() {
+            let memo_ingredient_index = MemoIngredientIndex::from_usize(index);
+            let Some(data) = &entry.data else {
+                continue;
+            };
+            if !remove(
+                memo_ingredient_index,
+                &*(data.to_dyn_fn)(data.arc_swap.load_full()),
+            ) {
+                continue;
+            }
+            let MemoEntryData {
+                type_id: _,
+                to_dyn_fn,
+                arc_swap,
+            } = entry.data.take().unwrap();
+            removed.push((memo_ingredient_index, to_dyn_fn(arc_swap.into_inner())));
+        }
+        removed
+    }
+
     pub(crate) fn into_memos(
         mut self,
     ) -> impl Iterator<Item = (MemoIngredientIndex, Arc<dyn Memo>)> {
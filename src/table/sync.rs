@@ -10,7 +10,7 @@ use crate::{
     runtime::WaitResult,
     zalsa::{MemoIngredientIndex, Zalsa},
     zalsa_local::ZalsaLocal,
-    Database,
+    Database, QueryError,
 };
 
 use super::util;
@@ -31,13 +31,18 @@ struct SyncState {
 }
 
 impl SyncTable {
+    /// Claims the query `database_key_index` for the current thread.
+    ///
+    /// If another thread has claimed it already, blocks until that thread completes
+    /// and returns `Ok(None)`; the caller should then try again. Fails if blocking
+    /// would form a cycle that nobody recovers from, or if the other thread panicked.
     pub(crate) fn claim<'me>(
         &'me self,
         db: &'me dyn Database,
         zalsa_local: &ZalsaLocal,
         database_key_index: DatabaseKeyIndex,
         memo_ingredient_index: MemoIngredientIndex,
-    ) -> Option<ClaimGuard<'me>> {
+    ) -> Result<Option<ClaimGuard<'me>>, QueryError> {
         let mut syncs = self.syncs.write();
         let zalsa = db.zalsa();
         let thread_id = std::thread::current().id();
@@ -50,12 +55,13 @@ impl SyncTable {
                     id: thread_id,
                     anyone_waiting: AtomicBool::new(false),
                 });
-                Some(ClaimGuard {
+                Ok(Some(ClaimGuard {
                     database_key_index,
                     memo_ingredient_index,
                     zalsa,
                     sync_table: self,
-                })
+                    failed: false,
+                }))
             }
             Some(SyncState {
                 id: other_id,
@@ -68,8 +74,8 @@ impl SyncTable {
                 // boolean is to decide *whether* to acquire the lock,
                 // not to gate future atomic reads.
                 anyone_waiting.store(true, Ordering::Relaxed);
-                zalsa.block_on_or_unwind(db, zalsa_local, database_key_index, *other_id, syncs);
-                None
+                zalsa.block_on_or_unwind(db, zalsa_local, database_key_index, *other_id, syncs)?;
+                Ok(None)
             }
         }
     }
@@ -83,9 +89,20 @@ pub(crate) struct ClaimGuard<'me> {
     memo_ingredient_index: MemoIngredientIndex,
     zalsa: &'me Zalsa,
     sync_table: &'me SyncTable,
+
+    /// Set if the query failed with a [`QueryError`] without unwinding.
+    failed: bool,
 }
 
 impl<'me> ClaimGuard<'me> {
+    /// Records that the query failed with `error`, which the caller is returning.
+    /// Once the claim is released, the queries blocked on it are cancelled,
+    /// as if it had panicked.
+    pub(crate) fn fail(&mut self, error: QueryError) -> QueryError {
+        self.failed = true;
+        error
+    }
+
     fn remove_from_map_and_unblock_queries(&self, wait_result: WaitResult) {
         let mut syncs = self.sync_table.syncs.write();
 
@@ -103,7 +120,7 @@ impl<'me> ClaimGuard<'me> {
 
 impl<'me> Drop for ClaimGuard<'me> {
     fn drop(&mut self) {
-        let wait_result = if std::thread::panicking() {
+        let wait_result = if self.failed || std::thread::panicking() {
             WaitResult::Panicked
         } else {
             WaitResult::Completed
@@ -0,0 +1,178 @@
+//! Utilities to check which queries a database executed, reused or backdated.
+//!
+//! Use [`TestDatabase`], or embed an [`EventLog`] in your own database and record
+//! the events from [`Database::salsa_event`], then check the queries with
+//! [`assert_executed!`], [`assert_reused!`] and [`assert_backdated!`]:
+//!
+//! ```
+//! use salsa::testing::{assert_executed, assert_reused, TestDatabase};
+//! use salsa::Setter;
+//!
+//! #[salsa::input]
+//! struct File {
+//!     text: String,
+//! }
+//!
+//! #[salsa::tracked]
+//! fn len(db: &dyn salsa::Database, file: File) -> usize {
+//!     file.text(db).len()
+//! }
+//!
+//! let mut db = TestDatabase::default();
+//! let file = File::new(&db, "hello".to_string());
+//! len(&db, file);
+//! assert_executed!(db, [len(file)]);
+//!
+//! file.set_text(&mut db).to("world".to_string());
+//! len(&db, file);
+//! assert_executed!(db, [len(file)]);
+//!
+//! len(&db, file);
+//! assert_executed!(db, []);
+//! ```
+//!
+//! [`IncrementalEquivalence`] checks that a database updated incrementally computes
+//! the same results as one built from scratch, across random edits of its inputs.
+
+mod equivalence;
+
+use parking_lot::Mutex;
+
+use crate::{self as salsa, Database, DatabaseKeyIndex, Event, EventKind, Storage};
+
+pub use self::equivalence::{IncrementalEquivalence, TestRng};
+pub use crate::{assert_backdated, assert_executed, assert_reused};
+
+/// What happened to a query, as recorded by an [`EventLog`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The query function was executed, see [`EventKind::WillExecute`].
+    Executed,
+
+    /// The memoized value was reused, see [`EventKind::DidValidateMemoizedValue`].
+    Reused,
+
+    /// The query was re-executed but its value was unchanged,
+    /// see [`EventKind::DidBackdateValue`].
+    Backdated,
+}
+
+/// Records the outcome of the queries executed on a database.
+#[derive(Default)]
+pub struct EventLog {
+    events: Mutex<Vec<(Outcome, DatabaseKeyIndex)>>,
+}
+
+impl EventLog {
+    /// Records `event`, if it is the outcome of a query. Invoke from [`Database::salsa_event`].
+    pub fn record(&self, event: &dyn Fn() -> Event) {
+        let (outcome, database_key) = match event().kind {
+            EventKind::WillExecute { database_key } => (Outcome::Executed, database_key),
+            EventKind::DidValidateMemoizedValue { database_key } => (Outcome::Reused, database_key),
+            EventKind::DidBackdateValue { database_key } => (Outcome::Backdated, database_key),
+            _ => return,
+        };
+        self.events.lock().push((outcome, database_key));
+    }
+
+    /// Removes the recorded queries with the given outcome and returns them, oldest first.
+    pub fn take(&self, outcome: Outcome) -> Vec<DatabaseKeyIndex> {
+        let mut taken = vec![];
+        self.events.lock().retain(|&(o, database_key)| {
+            if o == outcome {
+                taken.push(database_key);
+            }
+            o != outcome
+        });
+        taken
+    }
+
+    /// Removes all the recorded queries.
+    pub fn clear(&self) {
+        self.events.lock().clear();
+    }
+}
+
+/// Implemented by the databases that can be checked with [`assert_executed!`] and friends.
+pub trait HasEventLog: Database {
+    fn event_log(&self) -> &EventLog;
+}
+
+/// A database that records the outcome of its queries in an [`EventLog`].
+#[salsa::db]
+#[derive(Default)]
+pub struct TestDatabase {
+    storage: Storage<Self>,
+    event_log: EventLog,
+}
+
+#[salsa::db]
+impl Database for TestDatabase {
+    fn salsa_event(&self, event: &dyn Fn() -> Event) {
+        self.event_log.record(event);
+    }
+}
+
+impl HasEventLog for TestDatabase {
+    fn event_log(&self) -> &EventLog {
+        &self.event_log
+    }
+}
+
+/// Checks that the queries with `outcome` recorded in `db` since the last check
+/// are `expected`, in any order. Invoked by [`assert_executed!`] and friends.
+#[track_caller]
+pub fn assert_outcome<Db>(db: &Db, outcome: Outcome, expected: Vec<DatabaseKeyIndex>)
+where
+    Db: ?Sized + HasEventLog,
+{
+    let mut actual = db.event_log().take(outcome);
+    let mut expected = expected;
+    actual.sort();
+    expected.sort();
+    if actual != expected {
+        // Attach the database so that the keys are printed with the names of the queries.
+        let (actual, expected) =
+            crate::attach::attach(db, || (format!("{actual:?}"), format!("{expected:?}")));
+        panic!("unexpected {outcome:?} queries\n  expected: {expected}\n    actual: {actual}");
+    }
+}
+
+/// Asserts that exactly the given queries were executed since the last `assert_executed!`,
+/// e.g. `assert_executed!(db, [parse(file), lex(file)])`. The database must implement [`HasEventLog`].
+#[macro_export]
+macro_rules! assert_executed {
+    ($db:expr, [$($($query:ident)::+ ($($arg:expr),* $(,)?)),* $(,)?]) => {
+        $crate::testing::assert_outcome(
+            &$db,
+            $crate::testing::Outcome::Executed,
+            ::std::vec![$($($query)::+::database_key(&$db, $($arg),*)),*],
+        )
+    };
+}
+
+/// Asserts that exactly the given queries had their memoized value reused since the last
+/// `assert_reused!`, see [`assert_executed!`].
+#[macro_export]
+macro_rules! assert_reused {
+    ($db:expr, [$($($query:ident)::+ ($($arg:expr),* $(,)?)),* $(,)?]) => {
+        $crate::testing::assert_outcome(
+            &$db,
+            $crate::testing::Outcome::Reused,
+            ::std::vec![$($($query)::+::database_key(&$db, $($arg),*)),*],
+        )
+    };
+}
+
+/// Asserts that exactly the given queries were re-executed to an unchanged value since the
+/// last `assert_backdated!`, see [`assert_executed!`].
+#[macro_export]
+macro_rules! assert_backdated {
+    ($db:expr, [$($($query:ident)::+ ($($arg:expr),* $(,)?)),* $(,)?]) => {
+        $crate::testing::assert_outcome(
+            &$db,
+            $crate::testing::Outcome::Backdated,
+            ::std::vec![$($($query)::+::database_key(&$db, $($arg),*)),*],
+        )
+    };
+}
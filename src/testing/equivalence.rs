@@ -0,0 +1,149 @@
+use std::fmt;
+
+use crate::Database;
+
+/// A small deterministic random number generator for [`IncrementalEquivalence`],
+/// so that a failing run can be reproduced from its seed.
+#[derive(Clone, Debug)]
+pub struct TestRng {
+    state: u64,
+}
+
+impl TestRng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift requires a non-zero state.
+        Self {
+            state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        // xorshift64*
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a number in `0..n`. Panics if `n` is zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "empty range");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn gen_bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    /// Returns a random element of `items`, if any.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.below(items.len())])
+        }
+    }
+}
+
+type Build<S, Db, H> = Box<dyn Fn(&S) -> (Db, H)>;
+
+type Generate<S, E> = Box<dyn Fn(&mut TestRng, &S) -> E>;
+
+type Apply<S, Db, H, E> = Box<dyn Fn(&mut S, &mut Db, &mut H, &E)>;
+
+/// Compares the result of a root query in the incremental and the fresh database,
+/// returning both results if they differ.
+type CompareRoot<Db, H> = Box<dyn Fn(&Db, &H, &Db, &H) -> Option<(String, String)>>;
+
+/// Checks that a database updated incrementally computes the same results
+/// as a database built from scratch.
+///
+/// The inputs are described by a state `S`, from which `build` creates a database along
+/// with the handles `H` to its inputs (e.g. a `Vec` of input structs). For each seed,
+/// [`run`](`Self::run`) builds a database from the initial state, then repeatedly generates
+/// a random edit `E`, applies it to the state and the database, and compares the result
+/// of each root query with the one of a new database built from the edited state.
+pub struct IncrementalEquivalence<S, Db, H, E> {
+    build: Build<S, Db, H>,
+    generate: Generate<S, E>,
+    apply: Apply<S, Db, H, E>,
+    roots: Vec<(&'static str, CompareRoot<Db, H>)>,
+}
+
+impl<S, Db, H, E> IncrementalEquivalence<S, Db, H, E>
+where
+    S: Clone,
+    Db: Database,
+    E: fmt::Debug,
+{
+    /// Creates a harness where `build` creates a database from a state, `generate`
+    /// returns a random edit of a state and `apply` applies an edit to both a state
+    /// and a database.
+    pub fn new(
+        build: impl Fn(&S) -> (Db, H) + 'static,
+        generate: impl Fn(&mut TestRng, &S) -> E + 'static,
+        apply: impl Fn(&mut S, &mut Db, &mut H, &E) + 'static,
+    ) -> Self {
+        Self {
+            build: Box::new(build),
+            generate: Box::new(generate),
+            apply: Box::new(apply),
+            roots: vec![],
+        }
+    }
+
+    /// Adds a root query, whose results are compared after each edit.
+    ///
+    /// The results must not refer to the database, e.g. they should not contain
+    /// tracked structs, since those of the two databases cannot be compared.
+    pub fn root<R>(mut self, name: &'static str, query: impl Fn(&Db, &H) -> R + 'static) -> Self
+    where
+        R: PartialEq + fmt::Debug,
+    {
+        self.roots.push((
+            name,
+            Box::new(move |db, handles, fresh_db, fresh_handles| {
+                let incremental = query(db, handles);
+                let from_scratch = query(fresh_db, fresh_handles);
+                (incremental != from_scratch)
+                    .then(|| (format!("{incremental:#?}"), format!("{from_scratch:#?}")))
+            }),
+        ));
+        self
+    }
+
+    /// For each seed, applies `steps` random edits to a database built from `initial`,
+    /// checking the root queries after each one.
+    ///
+    /// Panics on the first root query whose results differ, reporting the seed and the edits.
+    #[track_caller]
+    pub fn run(&self, initial: &S, seeds: impl IntoIterator<Item = u64>, steps: usize) {
+        for seed in seeds {
+            let mut rng = TestRng::new(seed);
+            let mut state = initial.clone();
+            let (mut db, mut handles) = (self.build)(&state);
+            let mut edits = vec![];
+
+            for _ in 0..steps {
+                let edit = (self.generate)(&mut rng, &state);
+                (self.apply)(&mut state, &mut db, &mut handles, &edit);
+                edits.push(edit);
+
+                let (fresh_db, fresh_handles) = (self.build)(&state);
+                for (name, compare) in &self.roots {
+                    if let Some((incremental, from_scratch)) =
+                        compare(&db, &handles, &fresh_db, &fresh_handles)
+                    {
+                        panic!(
+                            "root `{name}` differs with seed {seed} after {} edits\n\
+                             edits: {edits:#?}\n\
+                             incremental: {incremental}\n\
+                             from scratch: {from_scratch}",
+                            edits.len(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,14 @@
-use std::{fmt, hash::Hash, marker::PhantomData, ops::DerefMut};
+use std::{collections::BTreeSet, fmt, hash::Hash, marker::PhantomData, ops::DerefMut};
 
 use crossbeam::{atomic::AtomicCell, queue::SegQueue};
+use parking_lot::Mutex;
 use tracked_field::FieldIngredientImpl;
 
 use crate::{
     cycle::CycleRecoveryStrategy,
+    hash::FxHashSet,
     ingredient::{fmt_index, Ingredient, Jar, JarAux},
+    introspection::IngredientKind,
     key::{DatabaseKeyIndex, DependencyIndex},
     plumbing::ZalsaLocal,
     runtime::StampedValue,
@@ -13,7 +16,7 @@ use crate::{
     table::{memo::MemoTable, sync::SyncTable, Slot, Table},
     zalsa::{IngredientIndex, Zalsa},
     zalsa_local::QueryOrigin,
-    Database, Durability, Event, EventKind, Id, Revision,
+    Database, Durability, Event, EventKind, Id, QueryError, Revision,
 };
 
 pub mod tracked_field;
@@ -140,6 +143,13 @@ where
 
     /// Store freed ids
     free_list: SegQueue<Id>,
+
+    /// The ids of the tracked structs that have not been deleted. Kept apart from the
+    /// values, which are overwritten when their id is reused.
+    live: Mutex<BTreeSet<Id>>,
+
+    /// The revision in which the last tracked struct was created or deleted, if any.
+    created_or_deleted_at: AtomicCell<Option<Revision>>,
 }
 
 /// Defines the identity of a tracked struct.
@@ -237,6 +247,8 @@ where
             ingredient_index: index,
             phantom: PhantomData,
             free_list: Default::default(),
+            live: Default::default(),
+            created_or_deleted_at: AtomicCell::new(None),
         }
     }
 
@@ -257,8 +269,7 @@ where
 
         let data_hash = crate::hash::hash(&C::id_fields(&fields));
 
-        let (current_deps, disambiguator) =
-            zalsa_local.disambiguate(self.ingredient_index, Revision::start(), data_hash);
+        let (current_deps, disambiguator) = zalsa_local.disambiguate(data_hash);
 
         let key_struct = KeyStruct {
             disambiguator,
@@ -292,6 +303,7 @@ where
         current_deps: &StampedValue<()>,
         fields: C::Fields<'db>,
     ) -> Id {
+        self.created_or_deleted_at.store(Some(current_revision));
         let value = || Value {
             updated_at: AtomicCell::new(Some(current_revision)),
             durability: current_deps.durability,
@@ -301,7 +313,7 @@ where
             syncs: Default::default(),
         };
 
-        if let Some(id) = self.free_list.pop() {
+        let id = if let Some(id) = self.free_list.pop() {
             let data_raw = Self::data_raw(zalsa.table(), id);
             assert!(
                 unsafe { (*data_raw).updated_at.load().is_none() },
@@ -317,7 +329,9 @@ where
             id
         } else {
             zalsa_local.allocate::<Value<C>>(zalsa.table(), self.ingredient_index, value())
-        }
+        };
+        self.live.lock().insert(id);
+        id
     }
 
     /// Get mutable access to the data for `id` -- this holds a write lock for the duration
@@ -474,6 +488,9 @@ where
             }
         }
 
+        self.live.lock().remove(&id);
+        self.created_or_deleted_at.store(Some(current_revision));
+
         // Take the memo table. This is safe because we have modified `data_ref.updated_at` to `None`
         // and the code that references the memo-table has a read-lock.
         let memo_table = unsafe { (*data).take_memo_table() };
@@ -501,6 +518,32 @@ where
         self.free_list.push(id);
     }
 
+    /// Iterates over all the tracked structs that have not been deleted, in the order of their ids.
+    ///
+    /// When invoked from a tracked function, the function is re-executed once a new revision
+    /// starts if tracked structs were created or deleted since. Tracked structs are created and
+    /// deleted while executing the tracked functions creating them, so:
+    ///
+    /// * the structs created or deleted later in the same revision, e.g. by queries executed
+    ///   after this one, are only seen in the next revision;
+    /// * a struct created in an earlier revision is included until the query that created it
+    ///   re-executes without creating it again. To only see up-to-date structs, invoke the queries
+    ///   creating them first.
+    pub fn all<'db>(
+        &'db self,
+        db: &'db dyn Database,
+    ) -> impl Iterator<Item = C::Struct<'db>> + 'db {
+        db.zalsa_local().report_tracked_read(
+            DependencyIndex::for_table(self.ingredient_index),
+            Durability::LOW,
+            self.created_or_deleted_at
+                .load()
+                .unwrap_or_else(Revision::start),
+        );
+        let ids: Vec<Id> = self.live.lock().iter().copied().collect();
+        ids.into_iter().map(C::struct_from_id)
+    }
+
     /// Return reference to the field data ignoring dependency tracking.
     /// Used for debugging.
     pub fn leak_fields<'db>(
@@ -555,10 +598,19 @@ where
     fn maybe_changed_after(
         &self,
         _db: &dyn Database,
-        _input: Option<Id>,
-        _revision: Revision,
-    ) -> bool {
-        false
+        input: Option<Id>,
+        revision: Revision,
+    ) -> Result<bool, QueryError> {
+        Ok(match input {
+            Some(_) => false,
+
+            // The set of tracked structs, see `all`. Structs created or deleted in `revision` itself
+            // may have been so after the read, so they count as a change.
+            None => self
+                .created_or_deleted_at
+                .load()
+                .is_some_and(|changed_at| changed_at >= revision),
+        })
     }
 
     fn cycle_recovery_strategy(&self) -> CycleRecoveryStrategy {
@@ -569,6 +621,16 @@ where
         None
     }
 
+    fn computed_at(
+        &self,
+        _db: &dyn Database,
+        _key_index: crate::Id,
+    ) -> Option<(Revision, Durability)> {
+        None
+    }
+
+    fn evict_value(&self, _zalsa: &Zalsa, _key_index: crate::Id) {}
+
     fn mark_validated_output<'db>(
         &'db self,
         _db: &'db dyn Database,
@@ -601,6 +663,21 @@ where
         C::DEBUG_NAME
     }
 
+    fn kind(&self) -> IngredientKind {
+        IngredientKind::TrackedStruct
+    }
+
+    fn live_entries(&self, zalsa: &Zalsa) -> usize {
+        // Deleted structs stay allocated until their id is reused.
+        zalsa.table().allocated_slots(self.ingredient_index) - self.free_list.len()
+    }
+
+    fn lru_capacity(&self) -> Option<usize> {
+        None
+    }
+
+    fn sweep(&self, _db: &dyn Database, _kept_inputs: &FxHashSet<DependencyIndex>) {}
+
     fn requires_reset_for_new_revision(&self) -> bool {
         false
     }
@@ -675,4 +752,12 @@ where
         self.read_lock(current_revision);
         &self.syncs
     }
+
+    fn peek_memos(&self) -> &crate::table::memo::MemoTable {
+        &self.memos
+    }
+
+    fn memos_mut(&mut self) -> &mut crate::table::memo::MemoTable {
+        &mut self.memos
+    }
 }
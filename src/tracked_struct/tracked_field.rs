@@ -1,6 +1,13 @@
 use std::marker::PhantomData;
 
-use crate::{ingredient::Ingredient, zalsa::IngredientIndex, Database, Id};
+use crate::{
+    hash::FxHashSet,
+    ingredient::Ingredient,
+    introspection::IngredientKind,
+    key::DependencyIndex,
+    zalsa::{IngredientIndex, Zalsa},
+    Database, Id, QueryError,
+};
 
 use super::{Configuration, Value};
 
@@ -53,12 +60,12 @@ where
         db: &'db dyn Database,
         input: Option<Id>,
         revision: crate::Revision,
-    ) -> bool {
+    ) -> Result<bool, QueryError> {
         let zalsa = db.zalsa();
         let id = input.unwrap();
         let data = <super::IngredientImpl<C>>::data(zalsa.table(), id);
         let field_changed_at = data.revisions[self.field_index];
-        field_changed_at > revision
+        Ok(field_changed_at > revision)
     }
 
     fn origin(
@@ -69,6 +76,16 @@ where
         None
     }
 
+    fn computed_at(
+        &self,
+        _db: &dyn Database,
+        _key_index: crate::Id,
+    ) -> Option<(crate::Revision, crate::Durability)> {
+        None
+    }
+
+    fn evict_value(&self, _zalsa: &Zalsa, _key_index: crate::Id) {}
+
     fn mark_validated_output(
         &self,
         _db: &dyn Database,
@@ -112,6 +129,23 @@ where
     fn debug_name(&self) -> &'static str {
         C::FIELD_DEBUG_NAMES[self.field_index]
     }
+
+    fn kind(&self) -> IngredientKind {
+        IngredientKind::TrackedField
+    }
+
+    fn live_entries(&self, zalsa: &Zalsa) -> usize {
+        // The fields are stored along with the tracked struct, whose ingredient precedes those of its fields.
+        let struct_index =
+            IngredientIndex::from(self.ingredient_index.as_usize() - self.field_index - 1);
+        zalsa.lookup_ingredient(struct_index).live_entries(zalsa)
+    }
+
+    fn lru_capacity(&self) -> Option<usize> {
+        None
+    }
+
+    fn sweep(&self, _db: &dyn Database, _kept_inputs: &FxHashSet<DependencyIndex>) {}
 }
 
 impl<C> std::fmt::Debug for FieldIngredientImpl<C>
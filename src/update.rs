@@ -2,6 +2,7 @@ use std::{
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     hash::{BuildHasher, Hash},
     path::PathBuf,
+    sync::Arc,
 };
 
 use crate::Revision;
@@ -282,6 +283,37 @@ where
     }
 }
 
+unsafe impl<T> Update for Arc<T>
+where
+    T: Update + PartialEq,
+{
+    unsafe fn maybe_update(old_pointer: *mut Self, new_arc: Self) -> bool {
+        let old_arc: &mut Arc<T> = unsafe { &mut *old_pointer };
+
+        if Arc::ptr_eq(old_arc, &new_arc) {
+            return false;
+        }
+
+        // We can only update in place if nobody else holds on to either value;
+        // otherwise, swap in the new `Arc` if its contents differ.
+        let new_arc = match Arc::get_mut(old_arc) {
+            Some(old_inner) => match Arc::try_unwrap(new_arc) {
+                Ok(new_inner) => return T::maybe_update(old_inner, new_inner),
+                Err(new_arc) => new_arc,
+            },
+            None => new_arc,
+        };
+
+        // As in `update_fallback`, keep the old value if the contents are equal.
+        if **old_arc != *new_arc {
+            *old_arc = new_arc;
+            true
+        } else {
+            false
+        }
+    }
+}
+
 unsafe impl<T, const N: usize> Update for [T; N]
 where
     T: Update,
@@ -324,6 +356,8 @@ fallback_impl! {
     usize,
     isize,
     PathBuf,
+    Box<str>,
+    Arc<str>,
 }
 
 macro_rules! tuple_impl {
@@ -379,3 +413,114 @@ where
         }
     }
 }
+
+unsafe impl<T, E> Update for Result<T, E>
+where
+    T: Update,
+    E: Update,
+{
+    unsafe fn maybe_update(old_pointer: *mut Self, new_value: Self) -> bool {
+        let old_value = unsafe { &mut *old_pointer };
+        match (old_value, new_value) {
+            (Ok(old), Ok(new)) => T::maybe_update(old, new),
+            (Err(old), Err(new)) => E::maybe_update(old, new),
+            (old_value, new_value) => {
+                *old_value = new_value;
+                true
+            }
+        }
+    }
+}
+
+#[cfg(feature = "indexmap")]
+unsafe impl<K, V, S> Update for indexmap::IndexMap<K, V, S>
+where
+    K: Update + Eq + Hash,
+    V: Update,
+    S: BuildHasher,
+{
+    unsafe fn maybe_update(old_pointer: *mut Self, new_map: Self) -> bool {
+        let old_map: &mut Self = unsafe { &mut *old_pointer };
+
+        // Unlike for `HashMap`, the order of the keys is observable,
+        // so it has to be the same for the maps to be considered "equal".
+        if !old_map.keys().eq(new_map.keys()) {
+            old_map.clear();
+            old_map.extend(new_map);
+            return true;
+        }
+
+        let mut changed = false;
+        for (old_value, (_, new_value)) in old_map.values_mut().zip(new_map) {
+            changed |= V::maybe_update(old_value, new_value);
+        }
+        changed
+    }
+}
+
+#[cfg(feature = "indexmap")]
+unsafe impl<K, S> Update for indexmap::IndexSet<K, S>
+where
+    K: Update + Eq + Hash,
+    S: BuildHasher,
+{
+    unsafe fn maybe_update(old_pointer: *mut Self, new_set: Self) -> bool {
+        let old_set: &mut Self = unsafe { &mut *old_pointer };
+
+        // The order of the elements is observable, so compare them in order.
+        if old_set.iter().eq(new_set.iter()) {
+            false
+        } else {
+            old_set.clear();
+            old_set.extend(new_set);
+            true
+        }
+    }
+}
+
+#[cfg(feature = "smallvec")]
+unsafe impl<A> Update for smallvec::SmallVec<A>
+where
+    A: smallvec::Array,
+    A::Item: Update,
+{
+    unsafe fn maybe_update(old_pointer: *mut Self, new_vec: Self) -> bool {
+        let old_vec: &mut Self = unsafe { &mut *old_pointer };
+
+        if old_vec.len() != new_vec.len() {
+            old_vec.clear();
+            old_vec.extend(new_vec);
+            return true;
+        }
+
+        let mut changed = false;
+        for (old_element, new_element) in old_vec.iter_mut().zip(new_vec) {
+            changed |= A::Item::maybe_update(old_element, new_element);
+        }
+
+        changed
+    }
+}
+
+#[cfg(feature = "thin-vec")]
+unsafe impl<T> Update for thin_vec::ThinVec<T>
+where
+    T: Update,
+{
+    unsafe fn maybe_update(old_pointer: *mut Self, new_vec: Self) -> bool {
+        let old_vec: &mut Self = unsafe { &mut *old_pointer };
+
+        if old_vec.len() != new_vec.len() {
+            old_vec.clear();
+            old_vec.extend(new_vec);
+            return true;
+        }
+
+        let mut changed = false;
+        for (old_element, new_element) in old_vec.iter_mut().zip(new_vec) {
+            changed |= T::maybe_update(old_element, new_element);
+        }
+
+        changed
+    }
+}
@@ -0,0 +1,45 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::Mutex;
+
+use crate::{hash::FxHashSet, DatabaseKeyIndex, Revision};
+
+/// The state of the from-scratch verification mode, see
+/// [`Database::set_from_scratch_verification`](`crate::Database::set_from_scratch_verification`).
+pub(crate) struct Verification {
+    enabled: AtomicBool,
+
+    /// The queries verified in the current revision, which is also recorded.
+    verified: Mutex<(Revision, FxHashSet<DatabaseKeyIndex>)>,
+}
+
+impl Default for Verification {
+    fn default() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            verified: Mutex::new((Revision::start(), FxHashSet::default())),
+        }
+    }
+}
+
+impl Verification {
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if `database_key` was not verified yet in `current_revision`,
+    /// recording that it now is.
+    pub(crate) fn start(&self, database_key: DatabaseKeyIndex, current_revision: Revision) -> bool {
+        let mut verified = self.verified.lock();
+        let (revision, keys) = &mut *verified;
+        if *revision != current_revision {
+            *revision = current_revision;
+            keys.clear();
+        }
+        keys.insert(database_key)
+    }
+}
@@ -125,6 +125,11 @@ impl Views {
         });
     }
 
+    /// The type names of the views that can be cast to, in the order they were added.
+    pub fn type_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.view_casters.iter().map(|caster| caster.type_name)
+    }
+
     /// Convert one handle to a salsa database (including a `dyn Database`!) to another.
     ///
     /// # Panics
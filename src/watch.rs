@@ -0,0 +1,97 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use parking_lot::Mutex;
+
+use crate::{key::DependencyIndex, Database, DatabaseKeyIndex, Revision};
+
+/// Identifies a subscription created with the `watch` function generated
+/// for tracked functions, see [`Database::unwatch`](`crate::Database::unwatch`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WatchId(u64);
+
+type Notify = Arc<dyn Fn(&dyn Database) + Send + Sync>;
+
+/// The subscriptions to the results of tracked functions.
+#[derive(Default)]
+pub(crate) struct Watches {
+    next_id: AtomicU64,
+    watches: Mutex<Vec<Watch>>,
+}
+
+struct Watch {
+    id: WatchId,
+    database_key: DatabaseKeyIndex,
+
+    /// Last revision in which the subscriber saw the result of the query.
+    checked_at: Revision,
+
+    /// Fetches the result of the query and passes it to the subscriber.
+    notify: Notify,
+}
+
+/// Subscribes `notify` to the changes of the result of `database_key`,
+/// starting from the current revision.
+pub fn watch(
+    db: &dyn Database,
+    database_key: DatabaseKeyIndex,
+    notify: impl Fn(&dyn Database) + Send + Sync + 'static,
+) -> WatchId {
+    let zalsa = db.zalsa();
+    let watches = zalsa.watches();
+    let id = WatchId(watches.next_id.fetch_add(1, Ordering::Relaxed));
+    watches.watches.lock().push(Watch {
+        id,
+        database_key,
+        checked_at: zalsa.current_revision(),
+        notify: Arc::new(notify),
+    });
+    id
+}
+
+impl Watches {
+    pub(crate) fn unwatch(&self, id: WatchId) -> bool {
+        let mut watches = self.watches.lock();
+        let len = watches.len();
+        watches.retain(|watch| watch.id != id);
+        watches.len() != len
+    }
+
+    /// Notifies the subscribers whose query result changed since they last saw it.
+    pub(crate) fn notify(&self, db: &dyn Database) {
+        let current_revision = db.zalsa().current_revision();
+
+        // Subscribers may watch or unwatch queries, so do not hold the lock while notifying them.
+        let pending: Vec<(WatchId, DatabaseKeyIndex, Revision, Notify)> = self
+            .watches
+            .lock()
+            .iter()
+            .filter(|watch| watch.checked_at < current_revision)
+            .map(|watch| {
+                (
+                    watch.id,
+                    watch.database_key,
+                    watch.checked_at,
+                    watch.notify.clone(),
+                )
+            })
+            .collect();
+
+        for (id, database_key, checked_at, notify) in pending {
+            // If the query fails, the subscriber gets to see the failure when it fetches the result.
+            if DependencyIndex::from(database_key)
+                .maybe_changed_after(db, checked_at)
+                .unwrap_or(true)
+            {
+                tracing::debug!("{database_key:?} changed after {checked_at:?}, notifying {id:?}");
+                notify(db);
+            }
+
+            if let Some(watch) = self.watches.lock().iter_mut().find(|watch| watch.id == id) {
+                watch.checked_at = current_revision;
+            }
+        }
+    }
+}
@@ -3,18 +3,28 @@ use parking_lot::Mutex;
 use rustc_hash::FxHashMap;
 use std::any::{Any, TypeId};
 use std::marker::PhantomData;
+use std::sync::Arc;
 use std::thread::ThreadId;
 
 use crate::cycle::CycleRecoveryStrategy;
+use crate::hash::{FxDashMap, FxHashSet};
+use crate::hot::HotQueries;
 use crate::ingredient::{Ingredient, Jar, JarAux};
+use crate::introspection::IngredientKind;
+use crate::key::DependencyIndex;
+use crate::memory_budget::MemoryBudget;
 use crate::nonce::{Nonce, NonceGenerator};
+use crate::runtime::change_log::ChangeLog;
 use crate::runtime::{Runtime, WaitResult};
-use crate::table::memo::MemoTable;
+use crate::sweep::{SweepOptions, SweepReport, SweptMemos};
+use crate::table::memo::{Memo, MemoTable};
 use crate::table::sync::SyncTable;
 use crate::table::Table;
+use crate::verification::Verification;
 use crate::views::Views;
-use crate::zalsa_local::ZalsaLocal;
-use crate::{Database, DatabaseKeyIndex, Durability, Id, Revision};
+use crate::watch::Watches;
+use crate::zalsa_local::{EdgeKind, QueryOrigin, ZalsaLocal};
+use crate::{Database, DatabaseKeyIndex, Durability, Id, QueryError, Revision};
 
 /// Internal plumbing trait.
 ///
@@ -138,6 +148,18 @@ pub struct Zalsa {
     /// Indices of ingredients that require reset when a new revision starts.
     ingredients_requiring_reset: AppendOnlyVec<IngredientIndex>,
 
+    /// The memoized values counted against the memory budget, see [`Database::set_memory_budget`].
+    memory_budget: MemoryBudget,
+
+    /// The subscriptions to the results of tracked functions, see [`Database::notify_watchers`].
+    watches: Watches,
+
+    /// The queries to bring up to date after writes, see [`Database::warm_hot_queries`].
+    hot_queries: HotQueries,
+
+    /// See [`Database::set_from_scratch_verification`].
+    verification: Verification,
+
     /// The runtime for this particular salsa database handle.
     /// Each handle gets its own runtime, but the runtimes have shared state between them.
     runtime: Runtime,
@@ -151,6 +173,10 @@ impl Zalsa {
             jar_map: Default::default(),
             ingredients_vec: AppendOnlyVec::new(),
             ingredients_requiring_reset: AppendOnlyVec::new(),
+            memory_budget: MemoryBudget::default(),
+            watches: Watches::default(),
+            hot_queries: HotQueries::default(),
+            verification: Verification::default(),
             runtime: Runtime::default(),
             memo_ingredients: Default::default(),
         }
@@ -164,6 +190,35 @@ impl Zalsa {
         self.nonce
     }
 
+    pub(crate) fn memory_budget(&self) -> &MemoryBudget {
+        &self.memory_budget
+    }
+
+    pub(crate) fn watches(&self) -> &Watches {
+        &self.watches
+    }
+
+    pub(crate) fn hot_queries(&self) -> &HotQueries {
+        &self.hot_queries
+    }
+
+    pub(crate) fn verification(&self) -> &Verification {
+        &self.verification
+    }
+
+    pub(crate) fn change_log(&self) -> &ChangeLog {
+        self.runtime.change_log()
+    }
+
+    pub(crate) fn change_log_mut(&mut self) -> &mut ChangeLog {
+        self.runtime.change_log_mut()
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn recorder(&self) -> &crate::record::Recorder {
+        self.runtime.recorder()
+    }
+
     /// Returns the [`Table`][] used to store the value of salsa structs
     pub(crate) fn table(&self) -> &Table {
         self.runtime.table()
@@ -216,6 +271,11 @@ impl Zalsa {
         }
     }
 
+    /// The registered ingredients, in the order of their indices.
+    pub(crate) fn ingredients(&self) -> impl Iterator<Item = &dyn Ingredient> {
+        self.ingredients_vec.iter().map(|ingredient| &**ingredient)
+    }
+
     pub(crate) fn lookup_ingredient(&self, index: IngredientIndex) -> &dyn Ingredient {
         &*self.ingredients_vec[index.as_usize()]
     }
@@ -258,6 +318,9 @@ impl Zalsa {
     pub(crate) fn new_revision(&mut self) -> Revision {
         let new_revision = self.runtime.new_revision();
 
+        // Evict first, so that the evicted values are freed along with the other deleted entries.
+        self.memory_budget.evict_over_budget(self);
+
         for index in self.ingredients_requiring_reset.iter() {
             self.ingredients_vec[index.as_usize()].reset_for_new_revision();
         }
@@ -265,6 +328,148 @@ impl Zalsa {
         new_revision
     }
 
+    /// Drops the memos that were not verified recently, see [`Database::sweep`].
+    ///
+    /// Returns the dropped memos along with their keys: the caller must discard their outputs,
+    /// since the tracked structs and accumulated values they created are no longer referenced.
+    /// Drops the memos selected by `options`. Also returns the inputs of the memos that were
+    /// kept which belong to accumulators, see [`Ingredient::sweep`].
+    pub(crate) fn sweep(
+        &mut self,
+        options: &SweepOptions,
+    ) -> (SweepReport, SweptMemos, FxHashSet<DependencyIndex>) {
+        let current_revision = self.current_revision();
+        let kinds: Vec<IngredientKind> = self.ingredients_vec.iter().map(|i| i.kind()).collect();
+        let memo_ingredients = self.memo_ingredients.get_mut();
+        let memory_budget = &self.memory_budget;
+        let table = self.runtime.table_mut();
+
+        // Find the memos that may be dropped. Assigned values cannot be recomputed: if the query
+        // assigned values, we keep the memo so that the next execution reuses or discards them.
+        let mut candidates: FxHashMap<DatabaseKeyIndex, Arc<dyn Memo>> = FxHashMap::default();
+        let mut worklist: Vec<DependencyIndex> = vec![];
+        table.for_each_memo_table_mut(|id, memos| {
+            memos.for_each(|memo_ingredient_index, memo| {
+                let ingredient_index = memo_ingredients[memo_ingredient_index.as_usize()];
+                let age = current_revision.as_usize() - memo.verified_at().as_usize();
+                let sweepable = age > options.older_than_revisions
+                    && options
+                        .ingredients
+                        .as_ref()
+                        .is_none_or(|ingredients| ingredients.contains(&ingredient_index))
+                    && match memo.origin() {
+                        QueryOrigin::Derived(_) | QueryOrigin::DerivedUntracked(_) => {
+                            memo.origin().outputs().all(|output| {
+                                matches!(
+                                    kinds[output.ingredient_index.as_usize()],
+                                    IngredientKind::TrackedStruct | IngredientKind::Accumulator
+                                )
+                            })
+                        }
+                        QueryOrigin::Assigned(_) | QueryOrigin::BaseInput => false,
+                    };
+
+                if sweepable {
+                    let key = DatabaseKeyIndex {
+                        ingredient_index,
+                        key_index: id,
+                    };
+                    candidates.insert(key, memo);
+                } else {
+                    // The memos that are kept may hold the tracked structs they (transitively) read,
+                    // except for the one they are attached to, which is dropped along with them.
+                    worklist.extend(
+                        memo.origin()
+                            .inputs()
+                            .filter(|input| input.key_index != Some(id)),
+                    );
+                }
+            })
+        });
+
+        // A tracked struct may only be deleted if no memo that is kept can hold it, i.e. if it was not
+        // read by them, and the query that created it is not a (transitive) dependency of theirs.
+        let mut reached: FxHashSet<DependencyIndex> = FxHashSet::default();
+        let mut reached_ids: FxHashSet<Id> = FxHashSet::default();
+        while let Some(input) = worklist.pop() {
+            if !reached.insert(input) {
+                continue;
+            }
+            let Some(key_index) = input.key_index else {
+                continue;
+            };
+            reached_ids.insert(key_index);
+            let key = DatabaseKeyIndex {
+                ingredient_index: input.ingredient_index,
+                key_index,
+            };
+            if let Some(memo) = candidates.get(&key) {
+                worklist.extend(memo.origin().inputs());
+            }
+        }
+
+        let mut report = SweepReport::default();
+        let mut removed_memos = vec![];
+        let mut kept_inputs: FxHashSet<DependencyIndex> = FxHashSet::default();
+        table.for_each_memo_table_mut(|id, memos| {
+            let removed = memos.remove_if(|memo_ingredient_index, memo| {
+                let key = DatabaseKeyIndex {
+                    ingredient_index: memo_ingredients[memo_ingredient_index.as_usize()],
+                    key_index: id,
+                };
+                let remove = candidates.contains_key(&key)
+                    && memo.origin().outputs().all(|output| {
+                        kinds[output.ingredient_index.as_usize()] == IngredientKind::Accumulator
+                            || (!reached.contains(&key.into())
+                                && output.key_index.is_none_or(|id| !reached_ids.contains(&id)))
+                    });
+                if !remove {
+                    kept_inputs.extend(memo.origin().inputs().filter(|input| {
+                        kinds[input.ingredient_index.as_usize()] == IngredientKind::Accumulator
+                    }));
+                }
+                remove
+            });
+
+            for (memo_ingredient_index, memo) in removed {
+                let key = DatabaseKeyIndex {
+                    ingredient_index: memo_ingredients[memo_ingredient_index.as_usize()],
+                    key_index: id,
+                };
+                let value_size = memory_budget.forget(key).unwrap_or(0);
+                let edges = memo.origin().inputs().count() + memo.origin().outputs().count();
+                report.memos += 1;
+                report.values += usize::from(memo.has_value());
+                report.edges += memo.origin().inputs().count();
+                report.bytes += std::mem::size_of_val(&*memo)
+                    + edges * std::mem::size_of::<(EdgeKind, DependencyIndex)>()
+                    + value_size;
+                removed_memos.push((key, memo));
+            }
+        });
+
+        (report, removed_memos, kept_inputs)
+    }
+
+    /// Discards the outputs of the memos dropped by [`Self::sweep`]:
+    /// the tracked structs they created are deleted, and the values they accumulated are freed.
+    pub(crate) fn discard_swept_outputs(
+        &self,
+        db: &dyn Database,
+        removed_memos: SweptMemos,
+        report: &mut SweepReport,
+    ) {
+        for (executor, memo) in removed_memos {
+            for output in memo.origin().outputs() {
+                let ingredient = self.lookup_ingredient(output.ingredient_index);
+                if ingredient.kind() == IngredientKind::TrackedStruct {
+                    report.tracked_structs += 1;
+                }
+                ingredient.remove_stale_output(db, executor, output.key_index);
+            }
+        }
+    }
+
     /// See [`Runtime::block_on_or_unwind`][]
     pub(crate) fn block_on_or_unwind<QueryMutexGuard>(
         &self,
@@ -273,7 +478,7 @@ impl Zalsa {
         database_key: DatabaseKeyIndex,
         other_id: ThreadId,
         query_mutex_guard: QueryMutexGuard,
-    ) {
+    ) -> Result<(), QueryError> {
         self.runtime
             .block_on_or_unwind(db, local_state, database_key, other_id, query_mutex_guard)
     }
@@ -367,6 +572,58 @@ where
     }
 }
 
+/// Caches the ingredients of a generic salsa item in a database.
+///
+/// A generic item has one ingredient per monomorphization, but the `static`
+/// holding its cache cannot name the item's type parameters. So unlike
+/// [`IngredientCache`], the ingredient type is given on each call and the
+/// cache is keyed by its `TypeId`.
+pub struct GenericIngredientCache {
+    cached_data: std::sync::OnceLock<FxDashMap<TypeId, (Nonce<StorageNonce>, IngredientIndex)>>,
+}
+
+impl Default for GenericIngredientCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GenericIngredientCache {
+    /// Create a new cache
+    pub const fn new() -> Self {
+        Self {
+            cached_data: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Get a reference to the ingredient of type `I` in the database.
+    /// If the ingredient is not already in the cache, it will be created.
+    pub fn get_or_create<'s, I>(
+        &self,
+        db: &'s dyn Database,
+        create_index: impl Fn() -> IngredientIndex,
+    ) -> &'s I
+    where
+        I: Ingredient,
+    {
+        let zalsa = db.zalsa();
+        let cached_data = self.cached_data.get_or_init(Default::default);
+        let type_id = TypeId::of::<I>();
+
+        let cached = cached_data.get(&type_id).map(|entry| *entry);
+        let index = match cached {
+            Some((nonce, index)) if nonce == zalsa.nonce() => index,
+            _ => {
+                let index = create_index();
+                cached_data.insert(type_id, (zalsa.nonce(), index));
+                index
+            }
+        };
+
+        zalsa.lookup_ingredient(index).assert_type::<I>()
+    }
+}
+
 /// Given a wide pointer `T`, extracts the data pointer (typed as `U`).
 ///
 /// # Safety requirement
@@ -21,6 +21,7 @@ use crate::Id;
 use crate::Revision;
 use std::cell::RefCell;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// State that is specific to a single execution thread.
 ///
@@ -125,6 +126,16 @@ impl ZalsaLocal {
         })
     }
 
+    /// Returns the keys of the active queries, outermost first.
+    pub(crate) fn active_query_keys(&self) -> Vec<DatabaseKeyIndex> {
+        self.with_query_stack(|stack| {
+            stack
+                .iter()
+                .map(|active_query| active_query.database_key_index)
+                .collect()
+        })
+    }
+
     /// Add an output to the current query's list of dependencies
     pub(crate) fn add_output(&self, entity: DependencyIndex) {
         self.with_query_stack(|stack| {
@@ -188,6 +199,23 @@ impl ZalsaLocal {
         })
     }
 
+    /// Register that the current query failed to read a value,
+    /// and so has to be re-executed in a new revision.
+    ///
+    /// If the current query is a cycle participant, the read failed because of
+    /// that cycle: unwind so that it can recover, like [`Self::report_tracked_read`].
+    pub(crate) fn report_failed_read(&self, current_revision: Revision) {
+        self.with_query_stack(|stack| {
+            if let Some(top_query) = stack.last_mut() {
+                top_query.add_untracked_read(current_revision);
+
+                if let Some(cycle) = &top_query.cycle {
+                    cycle.clone().throw()
+                }
+            }
+        })
+    }
+
     /// Register that the current query read an untracked value
     ///
     /// # Parameters
@@ -231,10 +259,8 @@ impl ZalsaLocal {
         self.query_stack.replace(Some(stack));
     }
 
-    /// Called when the active queries creates an index from the
-    /// entity table with the index `entity_index`. Has the following effects:
+    /// Called when the active queries creates a tracked struct. Has the following effects:
     ///
-    /// * Add a query read on `DatabaseKeyIndex::for_table(entity_index)`
     /// * Identify a unique disambiguator for the hash within the current query,
     ///   adding the hash to the current query's disambiguator table.
     /// * Returns a tuple of:
@@ -242,23 +268,12 @@ impl ZalsaLocal {
     ///   * the current dependencies (durability, changed_at) of current query
     ///   * the disambiguator index
     #[track_caller]
-    pub(crate) fn disambiguate(
-        &self,
-        entity_index: IngredientIndex,
-        reset_at: Revision,
-        data_hash: u64,
-    ) -> (StampedValue<()>, Disambiguator) {
+    pub(crate) fn disambiguate(&self, data_hash: u64) -> (StampedValue<()>, Disambiguator) {
         assert!(
             self.query_in_progress(),
             "cannot create a tracked struct disambiguator outside of a tracked function"
         );
 
-        self.report_tracked_read(
-            DependencyIndex::for_table(entity_index),
-            Durability::MAX,
-            reset_at,
-        );
-
         self.with_query_stack(|stack| {
             let top_query = stack.last_mut().unwrap();
             let disambiguator = top_query.disambiguate(data_hash);
@@ -314,6 +329,13 @@ impl ZalsaLocal {
     /// `salsa_event` is emitted when this method is called, so that should be
     /// used instead.
     pub(crate) fn unwind_if_revision_cancelled(&self, db: &dyn Database) {
+        if let Err(cancelled) = self.check_revision_cancelled(db) {
+            cancelled.throw();
+        }
+    }
+
+    /// Like [`Self::unwind_if_revision_cancelled`], but returns the cancellation as an error.
+    pub(crate) fn check_revision_cancelled(&self, db: &dyn Database) -> Result<(), Cancelled> {
         let thread_id = std::thread::current().id();
         db.salsa_event(&|| Event {
             thread_id,
@@ -322,14 +344,15 @@ impl ZalsaLocal {
         });
         let zalsa = db.zalsa();
         if zalsa.load_cancellation_flag() {
-            self.unwind_cancelled(zalsa.current_revision());
+            return Err(self.cancelled(zalsa.current_revision()));
         }
+        Ok(())
     }
 
     #[cold]
-    pub(crate) fn unwind_cancelled(&self, current_revision: Revision) {
+    fn cancelled(&self, current_revision: Revision) -> Cancelled {
         self.report_untracked_read(current_revision);
-        Cancelled::PendingWrite.throw();
+        Cancelled::PendingWrite
     }
 }
 
@@ -538,6 +561,23 @@ impl ActiveQueryGuard<'_> {
         popped_query.into_revisions()
     }
 
+    /// Like [`pop`](`Self::pop`), invoked after executing the query function for `elapsed`.
+    /// Also returns the time spent in the function itself, without the queries it executed,
+    /// and counts `elapsed` as such for the enclosing query.
+    pub(crate) fn pop_executed(self, elapsed: Duration) -> (QueryRevisions, Duration) {
+        let local_state = self.local_state;
+        let popped_query = self.complete();
+        assert!(popped_query.cycle.is_none());
+
+        let execution_time = elapsed.saturating_sub(popped_query.nested_execution_time);
+        local_state.with_query_stack(|stack| {
+            if let Some(parent) = stack.last_mut() {
+                parent.nested_execution_time += elapsed;
+            }
+        });
+        (popped_query.into_revisions(), execution_time)
+    }
+
     /// If the active query is registered as a cycle participant, remove and
     /// return that cycle.
     pub(crate) fn take_cycle(&self) -> Option<Cycle> {
@@ -548,6 +588,17 @@ impl ActiveQueryGuard<'_> {
 
 impl Drop for ActiveQueryGuard<'_> {
     fn drop(&mut self) {
-        self.pop_helper();
+        let query = self.pop_helper();
+
+        // If the query unwinds, e.g. because its function panicked, the enclosing query
+        // may catch the panic and memoize it (see `catch_panic`), so it must depend on
+        // what this query read. Cycle participants are handled by cycle recovery instead.
+        if std::thread::panicking() && query.cycle.is_none() {
+            self.local_state.with_query_stack(|stack| {
+                if let Some(parent) = stack.last_mut() {
+                    parent.add_reads_from(&query);
+                }
+            });
+        }
     }
 }
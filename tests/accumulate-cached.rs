@@ -0,0 +1,161 @@
+//! Test that the results of `accumulated` are cached and revalidated
+//! correctly when the inputs of some (but not all) queries change.
+
+mod common;
+
+use expect_test::expect;
+use salsa::{Accumulator, Database, Setter};
+use test_log::test;
+
+#[salsa::input]
+struct File {
+    text: String,
+}
+
+#[salsa::input]
+struct Project {
+    files: Vec<File>,
+}
+
+#[salsa::accumulator]
+struct Diagnostic(#[allow(dead_code)] String);
+
+#[salsa::tracked]
+fn check_project(db: &dyn Database, project: Project) {
+    for file in project.files(db) {
+        check_file(db, file);
+    }
+}
+
+#[salsa::tracked]
+fn check_file(db: &dyn Database, file: File) -> usize {
+    let text = file.text(db);
+    for word in text.split_whitespace().filter(|w| w.starts_with('!')) {
+        Diagnostic(format!("bad word `{word}`")).accumulate(db);
+    }
+    text.len()
+}
+
+fn diagnostics(db: &dyn Database, project: Project) -> Vec<String> {
+    check_project::accumulated::<Diagnostic>(db, project)
+        .into_iter()
+        .map(|d| d.0)
+        .collect()
+}
+
+#[test]
+fn change_one_file() {
+    let mut db = salsa::DatabaseImpl::new();
+    let a = File::new(&db, "!a b".to_string());
+    let b = File::new(&db, "c !d".to_string());
+    let project = Project::new(&db, vec![a, b]);
+
+    expect![[r#"
+        [
+            "bad word `!a`",
+            "bad word `!d`",
+        ]
+    "#]]
+    .assert_debug_eq(&diagnostics(&db, project));
+
+    // Requesting them again in the same revision uses the cached result.
+    expect![[r#"
+        [
+            "bad word `!a`",
+            "bad word `!d`",
+        ]
+    "#]]
+    .assert_debug_eq(&diagnostics(&db, project));
+
+    b.set_text(&mut db).to("!c d".to_string());
+    expect![[r#"
+        [
+            "bad word `!a`",
+            "bad word `!c`",
+        ]
+    "#]]
+    .assert_debug_eq(&diagnostics(&db, project));
+
+    // The length, and hence the value of `check_file`, has not changed,
+    // but the accumulated values have.
+    b.set_text(&mut db).to("c  d".to_string());
+    expect![[r#"
+        [
+            "bad word `!a`",
+        ]
+    "#]]
+    .assert_debug_eq(&diagnostics(&db, project));
+}
+
+#[test]
+fn change_inputs_of_query() {
+    let mut db = salsa::DatabaseImpl::new();
+    let a = File::new(&db, "!a".to_string());
+    let b = File::new(&db, "!b".to_string());
+    let project = Project::new(&db, vec![a]);
+
+    expect![[r#"
+        [
+            "bad word `!a`",
+        ]
+    "#]]
+    .assert_debug_eq(&diagnostics(&db, project));
+
+    project.set_files(&mut db).to(vec![b, a]);
+    expect![[r#"
+        [
+            "bad word `!b`",
+            "bad word `!a`",
+        ]
+    "#]]
+    .assert_debug_eq(&diagnostics(&db, project));
+
+    // The accumulated values of a file on its own are cached separately.
+    expect![[r#"
+        [
+            Diagnostic(
+                "bad word `!b`",
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&check_file::accumulated::<Diagnostic>(&db, b));
+
+    project.set_files(&mut db).to(vec![a]);
+    expect![[r#"
+        [
+            "bad word `!a`",
+        ]
+    "#]]
+    .assert_debug_eq(&diagnostics(&db, project));
+}
+
+#[test]
+fn change_unrelated_input() {
+    let mut db = salsa::DatabaseImpl::new();
+    let a = File::new(&db, "!a".to_string());
+    let project = Project::new(&db, vec![a]);
+    let unrelated = File::new(&db, "!x".to_string());
+
+    expect![[r#"
+        [
+            "bad word `!a`",
+        ]
+    "#]]
+    .assert_debug_eq(&diagnostics(&db, project));
+
+    unrelated.set_text(&mut db).to("!y".to_string());
+    expect![[r#"
+        [
+            "bad word `!a`",
+        ]
+    "#]]
+    .assert_debug_eq(&diagnostics(&db, project));
+
+    a.set_text(&mut db).to("!b".to_string());
+    expect![[r#"
+        [
+            "bad word `!b`",
+        ]
+    "#]]
+    .assert_debug_eq(&diagnostics(&db, project));
+}
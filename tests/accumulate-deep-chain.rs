@@ -0,0 +1,40 @@
+//! Test that the values accumulated by a very deep chain of queries
+//! can be collected without overflowing the stack.
+
+use salsa::{Accumulator, Database, DatabaseImpl};
+use test_log::test;
+
+#[salsa::accumulator]
+struct Log(#[allow(dead_code)] u32);
+
+#[salsa::input]
+struct Root {
+    every: u32,
+}
+
+#[salsa::tracked]
+fn chain(db: &dyn Database, root: Root, n: u32) {
+    if n.is_multiple_of(root.every(db)) {
+        Log(n).accumulate(db);
+    }
+    if n > 0 {
+        chain(db, root, n - 1);
+    }
+}
+
+#[test]
+fn accumulate_deep_chain() {
+    const DEPTH: u32 = 100_000;
+
+    DatabaseImpl::new().attach(|db| {
+        let root = Root::new(db, 1000);
+
+        // Execute the queries from the bottom, so that executing them does not recurse.
+        for n in 0..=DEPTH {
+            chain(db, root, n);
+        }
+
+        let logs = chain::accumulated::<Log>(db, root, DEPTH);
+        assert_eq!(logs.len(), 101);
+    })
+}
@@ -0,0 +1,95 @@
+//! Test that a tracked function reading the values accumulated by
+//! another query only re-executes when those values change.
+
+mod common;
+use common::{LogDatabase, LoggerDatabase};
+
+use expect_test::expect;
+use salsa::{Accumulator, Setter};
+use test_log::test;
+
+#[salsa::input]
+struct File {
+    text: String,
+}
+
+#[salsa::input]
+struct Project {
+    files: Vec<File>,
+}
+
+#[salsa::accumulator]
+struct Diagnostic(#[allow(dead_code)] String);
+
+#[salsa::tracked]
+fn check_project(db: &dyn LogDatabase, project: Project) {
+    for file in project.files(db) {
+        check_file(db, file);
+    }
+}
+
+#[salsa::tracked]
+fn check_file(db: &dyn LogDatabase, file: File) -> usize {
+    let text = file.text(db);
+    db.push_log(format!("check_file({text:?})"));
+    for word in text.split_whitespace().filter(|w| w.starts_with('!')) {
+        Diagnostic(format!("bad word `{word}`")).accumulate(db);
+    }
+    text.len()
+}
+
+#[salsa::tracked]
+fn count_diagnostics(db: &dyn LogDatabase, project: Project) -> usize {
+    db.push_log("count_diagnostics".to_string());
+    check_project::accumulated::<Diagnostic>(db, project).len()
+}
+
+#[test]
+fn reexecute_only_if_accumulated_values_change() {
+    let mut db = LoggerDatabase::default();
+    let a = File::new(&db, "!a b".to_string());
+    let b = File::new(&db, "c".to_string());
+    let project = Project::new(&db, vec![a, b]);
+
+    assert_eq!(count_diagnostics(&db, project), 1);
+    db.assert_logs(expect![[r#"
+        [
+            "count_diagnostics",
+            "check_file(\"!a b\")",
+            "check_file(\"c\")",
+        ]"#]]);
+
+    // `b` has no diagnostics before or after, so the accumulated values do not change.
+    b.set_text(&mut db).to("c d".to_string());
+    assert_eq!(count_diagnostics(&db, project), 1);
+    db.assert_logs(expect![[r#"
+        [
+            "check_file(\"c d\")",
+        ]"#]]);
+
+    b.set_text(&mut db).to("c !d".to_string());
+    assert_eq!(count_diagnostics(&db, project), 2);
+    db.assert_logs(expect![[r#"
+        [
+            "check_file(\"c !d\")",
+            "count_diagnostics",
+        ]"#]]);
+}
+
+#[test]
+fn values_of_read_query_are_included() {
+    let db = LoggerDatabase::default();
+    let a = File::new(&db, "!a".to_string());
+    let project = Project::new(&db, vec![a]);
+
+    // The values read by `count_diagnostics` are accumulated by it, too,
+    // just as if it had called `check_project`.
+    expect![[r#"
+        [
+            Diagnostic(
+                "bad word `!a`",
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&count_diagnostics::accumulated::<Diagnostic>(&db, project));
+}
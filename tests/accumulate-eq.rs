@@ -0,0 +1,70 @@
+//! Test that accumulated values are compared with `PartialEq` when the
+//! accumulator implements it, and always count as changed otherwise.
+
+mod common;
+use common::{LogDatabase, LoggerDatabase};
+
+use expect_test::expect;
+use salsa::{Accumulator, Setter};
+use test_log::test;
+
+#[salsa::input]
+struct MyInput {
+    value: u32,
+}
+
+#[salsa::accumulator]
+#[derive(PartialEq)]
+struct Comparable(u32);
+
+/// A type without `PartialEq`.
+#[derive(Clone, Debug)]
+struct Opaque(#[allow(dead_code)] u32);
+
+#[salsa::accumulator]
+struct NotComparable(#[allow(dead_code)] Opaque);
+
+#[salsa::tracked]
+fn push(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log(format!("push({})", input.value(db)));
+    Comparable(input.value(db) / 10).accumulate(db);
+    NotComparable(Opaque(input.value(db) / 10)).accumulate(db);
+    input.value(db)
+}
+
+#[salsa::tracked]
+fn read_comparable(db: &dyn LogDatabase, input: MyInput) -> usize {
+    db.push_log("read_comparable".to_string());
+    push::accumulated::<Comparable>(db, input).len()
+}
+
+#[salsa::tracked]
+fn read_not_comparable(db: &dyn LogDatabase, input: MyInput) -> usize {
+    db.push_log("read_not_comparable".to_string());
+    push::accumulated::<NotComparable>(db, input).len()
+}
+
+#[test]
+fn only_comparable_values_are_reused() {
+    let mut db = LoggerDatabase::default();
+    let input = MyInput::new(&db, 1);
+
+    read_comparable(&db, input);
+    read_not_comparable(&db, input);
+    db.assert_logs(expect![[r#"
+        [
+            "read_comparable",
+            "push(1)",
+            "read_not_comparable",
+        ]"#]]);
+
+    // `push` accumulates equal values again.
+    input.set_value(&mut db).to(2);
+    read_comparable(&db, input);
+    read_not_comparable(&db, input);
+    db.assert_logs(expect![[r#"
+        [
+            "push(2)",
+            "read_not_comparable",
+        ]"#]]);
+}
@@ -0,0 +1,106 @@
+//! Test `accumulated_with_origin`, which returns the query that
+//! accumulated each value and can skip parts of the query graph.
+
+mod common;
+
+use expect_test::expect;
+use salsa::{plumbing::AsId, Accumulator, Database, DatabaseKeyIndex};
+use test_log::test;
+
+#[salsa::input]
+struct File {
+    name: String,
+    imports: Vec<File>,
+}
+
+#[salsa::accumulator]
+struct Diagnostic(String);
+
+#[salsa::tracked]
+fn check_file(db: &dyn Database, file: File) {
+    Diagnostic(format!("checked {}", file.name(db))).accumulate(db);
+    for import in file.imports(db) {
+        check_file(db, import);
+    }
+}
+
+fn diagnostics(
+    db: &dyn Database,
+    file: File,
+    filter: impl FnMut(DatabaseKeyIndex) -> bool,
+) -> Vec<String> {
+    check_file::accumulated_with_origin::<Diagnostic>(db, file, filter)
+        .into_iter()
+        .map(|(key, d)| format!("{key:?}: {}", d.0))
+        .collect()
+}
+
+#[test]
+fn origin() {
+    salsa::DatabaseImpl::new().attach(|db| {
+        let a = File::new(db, "a".to_string(), vec![]);
+        let b = File::new(db, "b".to_string(), vec![a]);
+        let c = File::new(db, "c".to_string(), vec![a, b]);
+
+        expect![[r#"
+            [
+                "check_file(Id(2)): checked c",
+                "check_file(Id(0)): checked a",
+                "check_file(Id(1)): checked b",
+            ]
+        "#]]
+        .assert_debug_eq(&diagnostics(db, c, |_| true));
+
+        // Each value is only returned once, like with `accumulated`.
+        let values: Vec<_> = check_file::accumulated::<Diagnostic>(db, c)
+            .into_iter()
+            .map(|d| d.0)
+            .collect();
+        expect![[r#"
+            [
+                "checked c",
+                "checked a",
+                "checked b",
+            ]
+        "#]]
+        .assert_debug_eq(&values);
+    })
+}
+
+#[test]
+fn skip_subtrees() {
+    salsa::DatabaseImpl::new().attach(|db| {
+        let a = File::new(db, "a".to_string(), vec![]);
+        let b = File::new(db, "b".to_string(), vec![a]);
+        let c = File::new(db, "c".to_string(), vec![b]);
+        let d = File::new(db, "d".to_string(), vec![c, a]);
+
+        // Skipping `b` skips the values of `a` reached through it...
+        expect![[r#"
+            [
+                "check_file(Id(2)): checked c",
+            ]
+        "#]]
+        .assert_debug_eq(&diagnostics(db, c, |key| key.key_index() != b.as_id()));
+
+        // ...but not those reached through another query.
+        expect![[r#"
+            [
+                "check_file(Id(3)): checked d",
+                "check_file(Id(2)): checked c",
+                "check_file(Id(0)): checked a",
+            ]
+        "#]]
+        .assert_debug_eq(&diagnostics(db, d, |key| key.key_index() != b.as_id()));
+
+        // Filter by ingredient.
+        expect![[r#"
+            [
+                "check_file(Id(3)): checked d",
+            ]
+        "#]]
+        .assert_debug_eq(&diagnostics(db, d, |key| {
+            db.ingredient_debug_name(key.ingredient_index()) != "check_file"
+        }));
+    })
+}
@@ -0,0 +1,32 @@
+//! Test that `as_view` reports the caller as the location of its panic.
+//!
+//! This replaces the process-wide panic hook, so it is the only test in this binary:
+//! tests of the same binary run in parallel and would panic through this hook.
+
+use salsa::{Database, DatabaseImpl};
+
+#[salsa::db]
+trait OtherDb: Database {}
+
+#[test]
+fn as_view_panics_at_caller() {
+    let location = std::sync::Arc::new(std::sync::Mutex::new(None));
+    let hook_location = location.clone();
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        *hook_location.lock().unwrap() = info.location().map(|l| l.file().to_string());
+    }));
+
+    let db = DatabaseImpl::new();
+    let dyn_db: &dyn Database = &db;
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        dyn_db.as_view::<dyn OtherDb>();
+    }));
+    std::panic::set_hook(hook);
+
+    assert!(result.is_err());
+    assert_eq!(
+        location.lock().unwrap().as_deref(),
+        Some("tests/as_view_panic_location.rs")
+    );
+}
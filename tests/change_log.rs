@@ -0,0 +1,84 @@
+//! Test that writes to input fields are recorded in the change log.
+
+use expect_test::expect;
+use salsa::{plumbing::current_revision, Database, DatabaseImpl, Durability, Setter};
+use test_log::test;
+
+#[salsa::input]
+struct MyInput {
+    field1: u32,
+    field2: String,
+}
+
+#[test]
+fn changes_since() {
+    let mut db = DatabaseImpl::new();
+    let input1 = MyInput::new(&db, 1, "a".to_string());
+    let input2 = MyInput::new(&db, 2, "b".to_string());
+    let start = current_revision(&db);
+
+    input1.set_field1(&mut db).to(10);
+    let after_first = current_revision(&db);
+    input2
+        .set_field2(&mut db)
+        .with_durability(Durability::HIGH)
+        .to("c".to_string());
+
+    expect![[r#"
+        Some(
+            [
+                InputChange {
+                    revision: R2,
+                    ingredient_index: IngredientIndex(
+                        1,
+                    ),
+                    id: Id(0),
+                    field_index: 0,
+                    durability: Durability(
+                        0,
+                    ),
+                },
+                InputChange {
+                    revision: R3,
+                    ingredient_index: IngredientIndex(
+                        2,
+                    ),
+                    id: Id(1),
+                    field_index: 1,
+                    durability: Durability(
+                        2,
+                    ),
+                },
+            ],
+        )
+    "#]]
+    .assert_debug_eq(&db.changes_since(start));
+
+    let changes = db.changes_since(after_first).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].field_index, 1);
+    assert_eq!(
+        db.ingredient_debug_name(changes[0].ingredient_index),
+        "field2"
+    );
+
+    assert_eq!(db.changes_since(current_revision(&db)), Some(vec![]));
+}
+
+#[test]
+fn bounded_log() {
+    let mut db = DatabaseImpl::new();
+    let input = MyInput::new(&db, 0, "".to_string());
+
+    db.set_change_log_capacity(2);
+    let mut revisions = vec![current_revision(&db)];
+    for i in 1..=3 {
+        input.set_field1(&mut db).to(i);
+        revisions.push(current_revision(&db));
+    }
+
+    // The first write was dropped.
+    assert_eq!(db.changes_since(revisions[0]), None);
+    assert_eq!(db.changes_since(revisions[1]).map(|c| c.len()), Some(2));
+    assert_eq!(db.changes_since(revisions[3]), Some(vec![]));
+}
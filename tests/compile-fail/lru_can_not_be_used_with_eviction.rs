@@ -0,0 +1,11 @@
+#[salsa::input]
+struct MyInput {
+    field: u32,
+}
+
+#[salsa::tracked(lru = 3, eviction = salsa::eviction::Lfu::new(3))]
+fn lru_can_not_be_used_with_eviction(db: &dyn salsa::Database, input: MyInput) -> u32 {
+    input.field(db)
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+#[salsa::input]
+struct MyInput {
+    field: u32,
+}
+
+#[salsa::tracked(return_ref, return_arc)]
+fn return_ref_can_not_be_used_with_return_arc(db: &dyn salsa::Database, input: MyInput) -> u32 {
+    input.field(db)
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+#[salsa::input]
+struct MyInput {
+    field: u32,
+}
+
+fn same(a: &u32, b: &u32) -> bool {
+    a == b
+}
+
+#[salsa::tracked(no_eq, eq = same)]
+fn tracked_fn(db: &dyn salsa::Database, input: MyInput) -> u32 {
+    input.field(db)
+}
+
+fn main() {}
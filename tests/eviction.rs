@@ -0,0 +1,167 @@
+//! Test the built-in eviction policies and a custom one.
+
+mod common;
+use common::{LogDatabase, LoggerDatabase};
+
+use expect_test::expect;
+use salsa::{
+    eviction::{EvictionPolicy, Lfu, NotUsedInLast},
+    Id, Revision, Setter,
+};
+use std::sync::Mutex;
+use test_log::test;
+
+#[salsa::input]
+struct MyInput {
+    field: u32,
+}
+
+#[salsa::tracked(eviction = Lfu::new(2))]
+fn lfu(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log(format!("lfu({})", input.field(db)));
+    input.field(db)
+}
+
+#[salsa::tracked(eviction = NotUsedInLast::revisions(2))]
+fn not_used(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log(format!("not_used({})", input.field(db)));
+    input.field(db)
+}
+
+#[salsa::tracked]
+fn uses_not_used(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    not_used(db, input)
+}
+
+/// Keeps only the value used last.
+#[derive(Default)]
+struct KeepLast(Mutex<Option<Id>>);
+
+impl EvictionPolicy for KeepLast {
+    fn record_use(
+        &self,
+        id: Id,
+        _revision: Revision,
+        _verified_at: &dyn Fn(Id) -> Option<Revision>,
+        evict: &mut dyn FnMut(Id),
+    ) {
+        let mut last = self.0.lock().unwrap();
+        if let Some(previous) = last.replace(id) {
+            if previous != id {
+                evict(previous);
+            }
+        }
+    }
+}
+
+#[salsa::tracked(eviction = KeepLast::default())]
+fn keep_last(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log(format!("keep_last({})", input.field(db)));
+    input.field(db)
+}
+
+#[test]
+fn lfu_evicts_least_frequently_used() {
+    let db = LoggerDatabase::default();
+    let a = MyInput::new(&db, 1);
+    let b = MyInput::new(&db, 2);
+    let c = MyInput::new(&db, 3);
+
+    for _ in 0..3 {
+        lfu(&db, a);
+    }
+    lfu(&db, b);
+    lfu(&db, c);
+    db.assert_logs(expect![[r#"
+        [
+            "lfu(1)",
+            "lfu(2)",
+            "lfu(3)",
+        ]"#]]);
+
+    // `b` was used as often as `c`, but less recently, so its value was evicted.
+    lfu(&db, a);
+    lfu(&db, c);
+    lfu(&db, b);
+    db.assert_logs(expect![[r#"
+        [
+            "lfu(2)",
+        ]"#]]);
+}
+
+#[test]
+fn not_used_in_last_revisions() {
+    let mut db = LoggerDatabase::default();
+    let a = MyInput::new(&db, 1);
+    let b = MyInput::new(&db, 2);
+    let unrelated = MyInput::new(&db, 0);
+
+    not_used(&db, a);
+    not_used(&db, b);
+    db.assert_logs(expect![[r#"
+        [
+            "not_used(1)",
+            "not_used(2)",
+        ]"#]]);
+
+    unrelated.set_field(&mut db).to(1);
+    not_used(&db, a);
+    unrelated.set_field(&mut db).to(2);
+    not_used(&db, a);
+    db.assert_logs(expect![[r#"
+        []"#]]);
+
+    // `b` was last verified two revisions ago, so its value was evicted.
+    not_used(&db, b);
+    db.assert_logs(expect![[r#"
+        [
+            "not_used(2)",
+        ]"#]]);
+}
+
+#[test]
+fn not_used_in_last_revisions_keeps_verified() {
+    let mut db = LoggerDatabase::default();
+    let a = MyInput::new(&db, 1);
+    let b = MyInput::new(&db, 2);
+    let unrelated = MyInput::new(&db, 0);
+
+    uses_not_used(&db, b);
+    not_used(&db, a);
+    db.assert_logs(expect![[r#"
+        [
+            "not_used(2)",
+            "not_used(1)",
+        ]"#]]);
+
+    // Verifying `uses_not_used(b)` verifies `not_used(b)` without fetching its value.
+    unrelated.set_field(&mut db).to(1);
+    uses_not_used(&db, b);
+    not_used(&db, a);
+    unrelated.set_field(&mut db).to(2);
+    uses_not_used(&db, b);
+    not_used(&db, a);
+
+    // `b` was last fetched two revisions ago, but it was verified since, so its value was kept.
+    not_used(&db, b);
+    db.assert_logs(expect![[r#"
+        []"#]]);
+}
+
+#[test]
+fn custom_policy() {
+    let db = LoggerDatabase::default();
+    let a = MyInput::new(&db, 1);
+    let b = MyInput::new(&db, 2);
+
+    keep_last(&db, a);
+    keep_last(&db, a);
+    keep_last(&db, b);
+    keep_last(&db, a);
+    db.assert_logs(expect![[r#"
+        [
+            "keep_last(1)",
+            "keep_last(2)",
+            "keep_last(1)",
+        ]"#]]);
+}
@@ -0,0 +1,108 @@
+//! Test that queries reporting external reads are only re-executed
+//! when the state they read is invalidated.
+
+mod common;
+use common::{LogDatabase, LoggerDatabase};
+
+use expect_test::expect;
+use salsa::Database;
+use std::{collections::HashMap, sync::Mutex};
+use test_log::test;
+
+/// Stand-in for the environment variables of the process.
+static ENV: Mutex<Option<HashMap<&'static str, &'static str>>> = Mutex::new(None);
+
+fn set_env(name: &'static str, value: &'static str) {
+    ENV.lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(name, value);
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct EnvVar(&'static str);
+
+#[salsa::input]
+struct Config {
+    var: &'static str,
+}
+
+#[salsa::tracked]
+fn env_var(db: &dyn LogDatabase, config: Config) -> Option<&'static str> {
+    let name = config.var(db);
+    db.report_external_read(&EnvVar(name));
+    db.push_log(format!("env_var({name})"));
+    ENV.lock().unwrap().as_ref()?.get(name).copied()
+}
+
+#[salsa::tracked]
+fn env_var_len(db: &dyn LogDatabase, config: Config) -> usize {
+    let len = env_var(db, config).map_or(0, str::len);
+    db.push_log(format!("env_var_len({len})"));
+    len
+}
+
+#[test]
+fn only_readers_of_invalidated_key_reexecute() {
+    let mut db = LoggerDatabase::default();
+    set_env("EXTERNAL_READS_A", "a");
+    set_env("EXTERNAL_READS_B", "b");
+    let a = Config::new(&db, "EXTERNAL_READS_A");
+    let b = Config::new(&db, "EXTERNAL_READS_B");
+
+    assert_eq!(env_var(&db, a), Some("a"));
+    assert_eq!(env_var(&db, b), Some("b"));
+    db.assert_logs(expect![[r#"
+        [
+            "env_var(EXTERNAL_READS_A)",
+            "env_var(EXTERNAL_READS_B)",
+        ]"#]]);
+
+    set_env("EXTERNAL_READS_A", "aa");
+    db.invalidate_external(&EnvVar("EXTERNAL_READS_A"));
+    assert_eq!(env_var(&db, a), Some("aa"));
+    assert_eq!(env_var(&db, b), Some("b"));
+    db.assert_logs(expect![[r#"
+        [
+            "env_var(EXTERNAL_READS_A)",
+        ]"#]]);
+
+    // Keys of other types, or that were never read, do not invalidate anything.
+    db.invalidate_external(&"EXTERNAL_READS_B");
+    db.invalidate_external(&EnvVar("EXTERNAL_READS_C"));
+    assert_eq!(env_var(&db, a), Some("aa"));
+    assert_eq!(env_var(&db, b), Some("b"));
+    db.assert_logs(expect![[r#"
+        []"#]]);
+}
+
+#[test]
+fn dependents_are_backdated() {
+    let mut db = LoggerDatabase::default();
+    set_env("EXTERNAL_READS_D", "d");
+    let d = Config::new(&db, "EXTERNAL_READS_D");
+
+    assert_eq!(env_var_len(&db, d), 1);
+    db.assert_logs(expect![[r#"
+        [
+            "env_var(EXTERNAL_READS_D)",
+            "env_var_len(1)",
+        ]"#]]);
+
+    // The variable was invalidated, but did not change.
+    db.invalidate_external(&EnvVar("EXTERNAL_READS_D"));
+    assert_eq!(env_var_len(&db, d), 1);
+    db.assert_logs(expect![[r#"
+        [
+            "env_var(EXTERNAL_READS_D)",
+        ]"#]]);
+
+    set_env("EXTERNAL_READS_D", "dd");
+    db.invalidate_external(&EnvVar("EXTERNAL_READS_D"));
+    assert_eq!(env_var_len(&db, d), 2);
+    db.assert_logs(expect![[r#"
+        [
+            "env_var(EXTERNAL_READS_D)",
+            "env_var_len(2)",
+        ]"#]]);
+}
@@ -0,0 +1,110 @@
+//! Test that the from-scratch verification mode re-executes memoized functions
+//! and panics if their value changed.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use salsa::{Database, Setter, Storage};
+use test_log::test;
+
+#[salsa::db]
+#[derive(Default)]
+struct ExternalDatabase {
+    storage: Storage<Self>,
+
+    /// State read by `add_external`, which is not an input.
+    external: AtomicU32,
+}
+
+#[salsa::db]
+impl Database for ExternalDatabase {
+    fn salsa_event(&self, _event: &dyn Fn() -> salsa::Event) {}
+}
+
+#[salsa::db]
+trait Db: Database {
+    fn external(&self) -> u32;
+}
+
+#[salsa::db]
+impl Db for ExternalDatabase {
+    fn external(&self) -> u32 {
+        self.external.load(Ordering::SeqCst)
+    }
+}
+
+#[salsa::input]
+struct MyInput {
+    field: u32,
+}
+
+#[salsa::tracked]
+fn double(db: &dyn Db, input: MyInput) -> u32 {
+    input.field(db) * 2
+}
+
+#[salsa::tracked]
+fn add_external(db: &dyn Db, input: MyInput) -> u32 {
+    input.field(db) + db.external()
+}
+
+#[salsa::tracked]
+fn add_external_untracked(db: &dyn Db, input: MyInput) -> u32 {
+    db.report_untracked_read();
+    input.field(db) + db.external()
+}
+
+#[salsa::tracked]
+fn caller(db: &dyn Db, input: MyInput) -> u32 {
+    add_external(db, input) + 1
+}
+
+#[test]
+fn deterministic() {
+    let mut db = ExternalDatabase::default();
+    db.set_from_scratch_verification(true);
+    let input = MyInput::new(&db, 1);
+    let other = MyInput::new(&db, 1);
+
+    assert_eq!(double(&db, input), 2);
+    other.set_field(&mut db).to(2);
+    assert_eq!(double(&db, input), 2);
+}
+
+#[test]
+fn reported_untracked_read() {
+    let mut db = ExternalDatabase::default();
+    db.set_from_scratch_verification(true);
+    let input = MyInput::new(&db, 1);
+
+    assert_eq!(add_external_untracked(&db, input), 1);
+    db.external.store(10, Ordering::SeqCst);
+    db.synthetic_write(salsa::Durability::LOW);
+    assert_eq!(add_external_untracked(&db, input), 11);
+}
+
+#[test]
+fn disabled() {
+    let mut db = ExternalDatabase::default();
+    let input = MyInput::new(&db, 1);
+
+    assert_eq!(add_external(&db, input), 1);
+    db.external.store(10, Ordering::SeqCst);
+    db.synthetic_write(salsa::Durability::LOW);
+    assert_eq!(add_external(&db, input), 1);
+}
+
+#[test]
+#[should_panic(expected = "from-scratch verification failed: the memoized value of \
+    `add_external(Id(0))` differs from the value of a new execution\n  \
+    dependency chain: [caller(Id(0)), add_external(Id(0))]\n  \
+    recorded inputs: [field(Id(0))]")]
+fn missing_untracked_read() {
+    let mut db = ExternalDatabase::default();
+    db.set_from_scratch_verification(true);
+    let input = MyInput::new(&db, 1);
+
+    assert_eq!(caller(&db, input), 2);
+    db.external.store(10, Ordering::SeqCst);
+    db.synthetic_write(salsa::Durability::LOW);
+    caller(&db, input);
+}
@@ -0,0 +1,107 @@
+//! Test generic tracked functions and salsa structs:
+//! each monomorphization gets its own ingredients.
+
+mod common;
+use common::{LogDatabase, LoggerDatabase};
+
+use expect_test::expect;
+use salsa::Setter;
+use test_log::test;
+
+trait Language: Send + Sync {
+    const NAME: &'static str;
+
+    fn weight(text: &str) -> usize;
+}
+
+struct Rust;
+
+impl Language for Rust {
+    const NAME: &'static str = "rust";
+
+    fn weight(text: &str) -> usize {
+        text.len()
+    }
+}
+
+struct Python;
+
+impl Language for Python {
+    const NAME: &'static str = "python";
+
+    fn weight(text: &str) -> usize {
+        text.split_whitespace().count()
+    }
+}
+
+#[salsa::input]
+struct SourceFile<L: Language> {
+    text: String,
+}
+
+#[salsa::interned]
+struct Symbol<'db, L>
+where
+    L: Language,
+{
+    name: String,
+}
+
+#[salsa::tracked]
+struct Parsed<'db, L: Language> {
+    #[id]
+    symbol: Symbol<'db, L>,
+
+    weight: usize,
+}
+
+#[salsa::tracked]
+fn parse<'db, L: Language>(db: &'db dyn LogDatabase, file: SourceFile<L>) -> Parsed<'db, L> {
+    db.push_log(format!("parse::<{}>", L::NAME));
+    let text = file.text(db);
+    let symbol = Symbol::new(db, text.split_whitespace().next().unwrap_or("").to_string());
+    Parsed::new(db, symbol, L::weight(&text))
+}
+
+#[salsa::tracked]
+fn weight<L: Language>(db: &dyn LogDatabase, file: SourceFile<L>) -> usize {
+    db.push_log(format!("weight::<{}>", L::NAME));
+    parse(db, file).weight(db)
+}
+
+#[test]
+fn tracked_fn_per_monomorphization() {
+    let mut db = LoggerDatabase::default();
+    let rust = SourceFile::<Rust>::new(&db, "fn main() {}".to_string());
+    let python = SourceFile::<Python>::new(&db, "def main(): pass".to_string());
+
+    assert_eq!(weight(&db, rust), 12);
+    assert_eq!(weight(&db, python), 3);
+    db.assert_logs(expect![[r#"
+        [
+            "weight::<rust>",
+            "parse::<rust>",
+            "weight::<python>",
+            "parse::<python>",
+        ]"#]]);
+
+    python.set_text(&mut db).to("def main(): ...".to_string());
+    assert_eq!(weight(&db, rust), 12);
+    assert_eq!(weight(&db, python), 3);
+    db.assert_logs(expect![[r#"
+        [
+            "parse::<python>",
+        ]"#]]);
+}
+
+#[test]
+fn interned_per_monomorphization() {
+    let db = LoggerDatabase::default();
+    let rust = SourceFile::<Rust>::new(&db, "main".to_string());
+    let python = SourceFile::<Python>::new(&db, "main".to_string());
+
+    let rust_symbol = parse(&db, rust).symbol(&db);
+    assert_eq!(rust_symbol, Symbol::<Rust>::new(&db, "main".to_string()));
+    assert_eq!(rust_symbol.name(&db), "main");
+    assert_eq!(parse(&db, python).symbol(&db).name(&db), "main");
+}
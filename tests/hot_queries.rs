@@ -0,0 +1,60 @@
+//! Test that hot queries are brought up to date by `warm_hot_queries`.
+
+mod common;
+use common::{LogDatabase, LoggerDatabase};
+
+use expect_test::expect;
+use salsa::{Database, Setter};
+use test_log::test;
+
+#[salsa::input]
+struct MyInput {
+    field: u32,
+}
+
+#[salsa::tracked]
+fn double(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log(format!("double({})", input.field(db)));
+    input.field(db) * 2
+}
+
+#[salsa::tracked]
+fn triple(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log(format!("triple({})", input.field(db)));
+    input.field(db) * 3
+}
+
+#[test]
+fn warm_hot_queries() {
+    let mut db = LoggerDatabase::default();
+    let input = MyInput::new(&db, 1);
+
+    double::register_hot(&db, input);
+    double::register_hot(&db, input);
+    db.warm_hot_queries().unwrap();
+    db.assert_logs(expect![[r#"
+        [
+            "double(1)",
+        ]"#]]);
+
+    input.set_field(&mut db).to(2);
+    db.warm_hot_queries().unwrap();
+    db.assert_logs(expect![[r#"
+        [
+            "double(2)",
+        ]"#]]);
+
+    // The hot query is memoized, unlike the others.
+    assert_eq!(double(&db, input), 4);
+    assert_eq!(triple(&db, input), 6);
+    db.assert_logs(expect![[r#"
+        [
+            "triple(2)",
+        ]"#]]);
+
+    db.clear_hot_queries();
+    input.set_field(&mut db).to(3);
+    db.warm_hot_queries().unwrap();
+    db.assert_logs(expect![[r#"
+        []"#]]);
+}
@@ -0,0 +1,127 @@
+//! Test that `IncrementalEquivalence` compares incremental results with
+//! results computed from scratch.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use salsa::testing::{IncrementalEquivalence, TestDatabase, TestRng};
+use salsa::{Database, Setter};
+
+#[salsa::input]
+struct File {
+    text: String,
+}
+
+#[salsa::input]
+struct Project {
+    files: Vec<File>,
+}
+
+#[salsa::tracked]
+fn word_count(db: &dyn Database, file: File) -> usize {
+    file.text(db).split_whitespace().count()
+}
+
+#[salsa::tracked]
+fn total_words(db: &dyn Database, project: Project) -> usize {
+    project
+        .files(db)
+        .into_iter()
+        .map(|file| word_count(db, file))
+        .sum()
+}
+
+/// Extra words counted by `total_words_with_bonus`, which is not a salsa input.
+static BONUS: AtomicUsize = AtomicUsize::new(0);
+
+#[salsa::tracked]
+fn total_words_with_bonus(db: &dyn Database, project: Project) -> usize {
+    total_words(db, project) + BONUS.load(Ordering::SeqCst)
+}
+
+#[derive(Debug)]
+enum Edit {
+    SetText(usize, String),
+    AddFile(String),
+    SetBonus(usize),
+}
+
+#[derive(Clone, Default)]
+struct State {
+    texts: Vec<String>,
+    bonus: usize,
+}
+
+fn build(state: &State) -> (TestDatabase, Project) {
+    let db = TestDatabase::default();
+    let files = state
+        .texts
+        .iter()
+        .map(|text| File::new(&db, text.clone()))
+        .collect();
+    let project = Project::new(&db, files);
+    BONUS.store(state.bonus, Ordering::SeqCst);
+    (db, project)
+}
+
+fn random_text(rng: &mut TestRng) -> String {
+    let words = ["a", "b", "c d", ""];
+    (0..rng.below(4))
+        .map(|_| *rng.choose(&words).unwrap())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn generate(rng: &mut TestRng, state: &State, with_bonus: bool) -> Edit {
+    if with_bonus && rng.below(4) == 0 {
+        return Edit::SetBonus(rng.below(3));
+    }
+    if state.texts.is_empty() || rng.gen_bool() {
+        Edit::AddFile(random_text(rng))
+    } else {
+        Edit::SetText(rng.below(state.texts.len()), random_text(rng))
+    }
+}
+
+fn apply(state: &mut State, db: &mut TestDatabase, project: &mut Project, edit: &Edit) {
+    match edit {
+        Edit::SetText(index, text) => {
+            state.texts[*index] = text.clone();
+            let file = project.files(db)[*index];
+            file.set_text(db).to(text.clone());
+        }
+        Edit::AddFile(text) => {
+            state.texts.push(text.clone());
+            let mut files = project.files(db);
+            files.push(File::new(db, text.clone()));
+            project.set_files(db).to(files);
+        }
+        Edit::SetBonus(bonus) => {
+            state.bonus = *bonus;
+            BONUS.store(*bonus, Ordering::SeqCst);
+        }
+    }
+}
+
+#[test]
+fn equivalent() {
+    IncrementalEquivalence::new(build, |rng, state| generate(rng, state, false), apply)
+        .root("total_words", |db, project| total_words(db, *project))
+        .root("word_counts", |db, project| {
+            project
+                .files(db)
+                .into_iter()
+                .map(|file| word_count(db, file))
+                .collect::<Vec<_>>()
+        })
+        .run(&State::default(), 0..20, 30);
+}
+
+#[test]
+#[should_panic(expected = "root `total_words_with_bonus` differs with seed")]
+fn untracked_read_is_caught() {
+    IncrementalEquivalence::new(build, |rng, state| generate(rng, state, true), apply)
+        .root("total_words_with_bonus", |db, project| {
+            total_words_with_bonus(db, *project)
+        })
+        .run(&State::default(), 0..20, 30);
+}
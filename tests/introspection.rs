@@ -0,0 +1,141 @@
+//! Test that `Database::ingredients` describes the ingredients of the database.
+
+use expect_test::expect;
+use salsa::{Accumulator, Database, DatabaseImpl, IngredientKind};
+
+#[salsa::input]
+struct MyInput {
+    field: u32,
+}
+
+#[salsa::interned]
+struct MyInterned<'db> {
+    name: String,
+}
+
+#[salsa::tracked]
+struct MyTracked<'db> {
+    value: u32,
+}
+
+#[salsa::accumulator]
+struct Log(#[allow(dead_code)] String);
+
+#[salsa::tracked(lru = 8)]
+fn tracked_fn(db: &dyn Database, input: MyInput) -> u32 {
+    Log(format!("tracked_fn({})", input.field(db))).accumulate(db);
+    let tracked = MyTracked::new(db, input.field(db));
+    MyInterned::new(db, "name".to_string());
+    tracked.value(db)
+}
+
+#[salsa::tracked]
+fn accumulated(db: &dyn Database, input: MyInput) -> usize {
+    tracked_fn::accumulated::<Log>(db, input).len()
+}
+
+#[test]
+fn ingredients() {
+    let db = DatabaseImpl::new();
+    let inputs = [MyInput::new(&db, 1), MyInput::new(&db, 2)];
+    for input in inputs {
+        tracked_fn(&db, input);
+    }
+    accumulated(&db, inputs[0]);
+
+    expect![[r#"
+        [
+            IngredientInfo {
+                index: IngredientIndex(
+                    0,
+                ),
+                debug_name: "MyInput",
+                kind: Input,
+                live_entries: 2,
+                lru_capacity: None,
+                cycle_strategy: Panic,
+            },
+            IngredientInfo {
+                index: IngredientIndex(
+                    1,
+                ),
+                debug_name: "field",
+                kind: InputField,
+                live_entries: 2,
+                lru_capacity: None,
+                cycle_strategy: Panic,
+            },
+            IngredientInfo {
+                index: IngredientIndex(
+                    2,
+                ),
+                debug_name: "tracked_fn",
+                kind: Function,
+                live_entries: 2,
+                lru_capacity: Some(
+                    8,
+                ),
+                cycle_strategy: Panic,
+            },
+            IngredientInfo {
+                index: IngredientIndex(
+                    3,
+                ),
+                debug_name: "Log",
+                kind: Accumulator,
+                live_entries: 2,
+                lru_capacity: None,
+                cycle_strategy: Panic,
+            },
+            IngredientInfo {
+                index: IngredientIndex(
+                    4,
+                ),
+                debug_name: "MyTracked",
+                kind: TrackedStruct,
+                live_entries: 2,
+                lru_capacity: None,
+                cycle_strategy: Panic,
+            },
+            IngredientInfo {
+                index: IngredientIndex(
+                    5,
+                ),
+                debug_name: "value",
+                kind: TrackedField,
+                live_entries: 2,
+                lru_capacity: None,
+                cycle_strategy: Panic,
+            },
+            IngredientInfo {
+                index: IngredientIndex(
+                    6,
+                ),
+                debug_name: "MyInterned",
+                kind: Interned,
+                live_entries: 1,
+                lru_capacity: None,
+                cycle_strategy: Panic,
+            },
+            IngredientInfo {
+                index: IngredientIndex(
+                    7,
+                ),
+                debug_name: "accumulated",
+                kind: Function,
+                live_entries: 1,
+                lru_capacity: None,
+                cycle_strategy: Panic,
+            },
+        ]
+    "#]]
+    .assert_debug_eq(&db.ingredients());
+
+    let functions: Vec<_> = db
+        .ingredients()
+        .into_iter()
+        .filter(|ingredient| ingredient.kind == IngredientKind::Function)
+        .map(|ingredient| (ingredient.debug_name, ingredient.live_entries))
+        .collect();
+    assert_eq!(functions, [("tracked_fn", 2), ("accumulated", 1)]);
+}
@@ -0,0 +1,188 @@
+//! Test that values of functions with `estimated_size` are evicted
+//! when the memory budget of the database is exceeded.
+
+mod common;
+use common::{LogDatabase, LoggerDatabase};
+
+use expect_test::expect;
+use salsa::{Database, EstimatedSize, Setter};
+use std::sync::Arc;
+use std::time::Duration;
+use test_log::test;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Blob(usize);
+
+impl EstimatedSize for Blob {
+    fn estimated_size(&self) -> usize {
+        self.0
+    }
+}
+
+#[salsa::input]
+struct MyInput {
+    size: usize,
+    millis: u64,
+}
+
+#[salsa::tracked(estimated_size)]
+fn blob(db: &dyn LogDatabase, input: MyInput) -> Blob {
+    db.push_log(format!("blob({})", input.size(db)));
+    std::thread::sleep(Duration::from_millis(input.millis(db)));
+    Blob(input.size(db))
+}
+
+#[salsa::tracked]
+fn blob_len(db: &dyn LogDatabase, input: MyInput) -> usize {
+    db.push_log(format!("blob_len({})", input.size(db)));
+    blob(db, input).0
+}
+
+#[salsa::tracked]
+fn slow(db: &dyn LogDatabase, input: MyInput) -> u64 {
+    db.push_log(format!("slow({})", input.size(db)));
+    std::thread::sleep(Duration::from_millis(input.millis(db)));
+    input.millis(db)
+}
+
+/// Cheap to compute itself, but invokes the slow query.
+#[salsa::tracked(estimated_size)]
+fn blob_after_slow(db: &dyn LogDatabase, input: MyInput) -> Blob {
+    db.push_log(format!("blob_after_slow({})", input.size(db)));
+    slow(db, input);
+    Blob(input.size(db))
+}
+
+/// Holds a reference to the token of its input, to observe when it is freed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Token(Arc<()>);
+
+impl EstimatedSize for Token {
+    fn estimated_size(&self) -> usize {
+        1
+    }
+}
+
+#[salsa::input]
+struct TokenInput {
+    token: Arc<()>,
+}
+
+#[salsa::tracked(estimated_size)]
+fn token(db: &dyn LogDatabase, input: TokenInput) -> Token {
+    Token(input.token(db))
+}
+
+#[test]
+fn evicts_cheapest_per_byte() {
+    let mut db = LoggerDatabase::default();
+    // `large` takes longer to compute, but is cheaper to recompute per byte.
+    let large = MyInput::new(&db, 10_000_000, 20);
+    let small = MyInput::new(&db, 10, 0);
+    let unrelated = MyInput::new(&db, 0, 0);
+
+    blob(&db, large);
+    blob(&db, small);
+    db.assert_logs(expect![[r#"
+        [
+            "blob(10000000)",
+            "blob(10)",
+        ]"#]]);
+
+    // Setting the budget evicts values right away.
+    db.set_memory_budget(Some(10_000_000));
+    blob(&db, small);
+    db.assert_logs(expect![[r#"
+        []"#]]);
+    blob(&db, large);
+    db.assert_logs(expect![[r#"
+        [
+            "blob(10000000)",
+        ]"#]]);
+
+    // The budget is checked again when a new revision starts.
+    unrelated.set_size(&mut db).to(1);
+    blob(&db, large);
+    blob(&db, small);
+    db.assert_logs(expect![[r#"
+        [
+            "blob(10000000)",
+        ]"#]]);
+}
+
+#[test]
+fn dependents_are_not_reexecuted() {
+    let mut db = LoggerDatabase::default();
+    let input = MyInput::new(&db, 100, 0);
+
+    assert_eq!(blob_len(&db, input), 100);
+    db.assert_logs(expect![[r#"
+        [
+            "blob_len(100)",
+            "blob(100)",
+        ]"#]]);
+
+    // Values that do not count against the budget are not evicted,
+    // and the dependencies of evicted values are kept, so that
+    // their dependents can still be verified.
+    db.set_memory_budget(Some(0));
+    assert_eq!(blob_len(&db, input), 100);
+    db.assert_logs(expect![[r#"
+        []"#]]);
+    blob(&db, input);
+    db.assert_logs(expect![[r#"
+        [
+            "blob(100)",
+        ]"#]]);
+
+    db.set_memory_budget(None);
+    blob(&db, input);
+    db.assert_logs(expect![[r#"
+        []"#]]);
+}
+
+#[test]
+fn evicted_values_are_freed() {
+    let mut db = LoggerDatabase::default();
+    let shared = Arc::new(());
+    let input = TokenInput::new(&db, shared.clone());
+
+    // Held by `shared`, the input and the value of `token`.
+    token(&db, input);
+    assert_eq!(Arc::strong_count(&shared), 3);
+
+    db.set_memory_budget(Some(0));
+    assert_eq!(Arc::strong_count(&shared), 2);
+
+    // Values re-executed during a revision are freed when the next one starts.
+    token(&db, input);
+    assert_eq!(Arc::strong_count(&shared), 3);
+    db.synthetic_write(salsa::Durability::LOW);
+    assert_eq!(Arc::strong_count(&shared), 2);
+}
+
+#[test]
+fn cost_does_not_include_nested_queries() {
+    let mut db = LoggerDatabase::default();
+    // `after_slow` takes longer to execute, but only because of `slow`:
+    // `blob` is more expensive to recompute per byte.
+    let after_slow = MyInput::new(&db, 10, 20);
+    let own = MyInput::new(&db, 10, 5);
+
+    blob_after_slow(&db, after_slow);
+    blob(&db, own);
+    db.assert_logs(expect![[r#"
+        [
+            "blob_after_slow(10)",
+            "slow(10)",
+            "blob(10)",
+        ]"#]]);
+
+    db.set_memory_budget(Some(10));
+    blob(&db, own);
+    blob_after_slow(&db, after_slow);
+    db.assert_logs(expect![[r#"
+        [
+            "blob_after_slow(10)",
+        ]"#]]);
+}
@@ -1,8 +1,11 @@
 mod setup;
 
 mod parallel_cancellation;
+mod parallel_catch_panic;
 mod parallel_cycle_all_recover;
 mod parallel_cycle_mid_recover;
 mod parallel_cycle_none_recover;
 mod parallel_cycle_one_recover;
+mod parallel_try_call;
+mod parallel_warm_hot_queries;
 mod signal;
@@ -0,0 +1,52 @@
+//! Test that a thread blocked on a `catch_panic` query
+//! sees the original panic of the thread executing it.
+
+use crate::setup::Knobs;
+use crate::setup::KnobsDatabase;
+
+#[salsa::input]
+struct MyInput {
+    field: i32,
+}
+
+#[salsa::tracked(catch_panic)]
+fn a(db: &dyn KnobsDatabase, input: MyInput) -> i32 {
+    db.signal(1);
+    db.wait_for(2);
+    panic!("a({}) failed", input.field(db))
+}
+
+// Thread A                   Thread B
+// --------                   --------
+// a                          wait for stage 1
+// signal stage 1             a
+// wait for stage 2           blocks on thread A, signals stage 2
+// panics, memoizes panic     |
+//                            (unblocked)
+//                            re-raises memoized panic
+
+#[test]
+fn execute() {
+    let db = Knobs::default();
+
+    let input = MyInput::new(&db, 1);
+
+    let thread_a = std::thread::spawn({
+        let db = db.clone();
+        move || a(&db, input)
+    });
+
+    let thread_b = std::thread::spawn({
+        let db = db.clone();
+        move || {
+            db.wait_for(1);
+            db.signal_on_will_block.store(2);
+            a(&db, input)
+        }
+    });
+
+    let panic_a = thread_a.join().unwrap_err().downcast::<String>().unwrap();
+    let panic_b = thread_b.join().unwrap_err().downcast::<String>().unwrap();
+    assert_eq!(*panic_a, "a(1) failed");
+    assert_eq!(*panic_b, "a(1) failed");
+}
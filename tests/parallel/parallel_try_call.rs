@@ -0,0 +1,60 @@
+//! Test that `try_call` returns a cancellation as an error.
+
+use salsa::QueryError;
+use salsa::Setter;
+
+use crate::setup::Knobs;
+use crate::setup::KnobsDatabase;
+
+#[salsa::input]
+struct MyInput {
+    field: i32,
+}
+
+#[salsa::tracked]
+fn a1(db: &dyn KnobsDatabase, input: MyInput) -> i32 {
+    db.signal(1);
+    db.wait_for(2);
+    dummy(db, input)
+}
+
+#[salsa::tracked]
+fn dummy(_db: &dyn KnobsDatabase, _input: MyInput) -> i32 {
+    panic!("should never get here!")
+}
+
+// Same pattern as `parallel_cancellation`, but thread A uses `try_call`.
+//
+// Thread A                   Thread B
+// --------                   --------
+// a1
+// |                          wait for stage 1
+// signal stage 1             set input, triggers cancellation
+// wait for stage 2 (blocks)  triggering cancellation sends stage 2
+// |
+// (unblocked)
+// dummy
+// returns `Err(Cancelled)`
+
+#[test]
+fn execute() {
+    let mut db = Knobs::default();
+
+    let input = MyInput::new(&db, 1);
+
+    let thread_a = std::thread::spawn({
+        let db = db.clone();
+        move || a1::try_call(&db, input)
+    });
+
+    db.signal_on_did_cancel.store(2);
+    input.set_field(&mut db).to(2);
+
+    let Err(QueryError::Cancelled(cancelled)) = thread_a.join().unwrap() else {
+        panic!("expected a cancellation");
+    };
+    expect_test::expect![[r#"
+        PendingWrite
+    "#]]
+    .assert_debug_eq(&cancelled);
+}
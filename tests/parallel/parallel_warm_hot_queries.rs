@@ -0,0 +1,57 @@
+//! Test that warming hot queries in the background
+//! stops when the database is written to.
+
+use salsa::{Database, Setter};
+
+use crate::setup::{Knobs, KnobsDatabase};
+
+#[salsa::input]
+struct MyInput {
+    field: i32,
+}
+
+#[salsa::tracked]
+fn slow(db: &dyn KnobsDatabase, input: MyInput) -> i32 {
+    db.signal(1);
+    db.wait_for(2);
+    fast(db, input) + 1
+}
+
+#[salsa::tracked]
+fn fast(db: &dyn KnobsDatabase, input: MyInput) -> i32 {
+    input.field(db)
+}
+
+// Thread A                   Thread B
+// --------                   --------
+// slow
+// |                          wait for stage 1
+// signal stage 1             set input, triggers cancellation
+// wait for stage 2 (blocks)  triggering cancellation sends stage 2
+// |
+// (unblocked)
+// fast
+// cancelled
+
+#[test]
+fn execute() {
+    let mut db = Knobs::default();
+    let input = MyInput::new(&db, 1);
+
+    slow::register_hot(&db, input);
+    let thread_a = db.warm_hot_queries_in_background();
+
+    db.wait_for(1);
+    db.signal_on_did_cancel.store(2);
+    input.set_field(&mut db).to(2);
+
+    let cancelled = thread_a.join().unwrap().unwrap_err();
+    expect_test::expect![[r#"
+        PendingWrite
+    "#]]
+    .assert_debug_eq(&cancelled);
+
+    // Warming again (with no write pending) completes.
+    db.warm_hot_queries().unwrap();
+    assert_eq!(slow(&db, input), 3);
+}
@@ -83,8 +83,10 @@ fn test_leaked_inputs_ignored() {
             "Event { thread_id: ThreadId(2), kind: WillCheckCancellation }",
             "Event { thread_id: ThreadId(2), kind: WillCheckCancellation }",
             "Event { thread_id: ThreadId(2), kind: WillExecute { database_key: counter_field(Id(800)) } }",
+            "Event { thread_id: ThreadId(2), kind: DidBackdateValue { database_key: counter_field(Id(800)) } }",
             "Event { thread_id: ThreadId(2), kind: WillExecute { database_key: function(Id(0)) } }",
             "Event { thread_id: ThreadId(2), kind: WillCheckCancellation }",
+            "Event { thread_id: ThreadId(2), kind: DidBackdateValue { database_key: function(Id(0)) } }",
         ]"#]]);
 
     // Salsa will re-execute `counter_field` before re-executing
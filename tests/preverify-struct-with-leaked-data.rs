@@ -82,6 +82,7 @@ fn test_leaked_inputs_ignored() {
             "Event { thread_id: ThreadId(2), kind: DidValidateMemoizedValue { database_key: counter_field(Id(400)) } }",
             "Event { thread_id: ThreadId(2), kind: WillExecute { database_key: function(Id(0)) } }",
             "Event { thread_id: ThreadId(2), kind: WillCheckCancellation }",
+            "Event { thread_id: ThreadId(2), kind: DidBackdateValue { database_key: function(Id(0)) } }",
         ]"#]]);
 
     // Because salsa does not see any way for the tracked
@@ -0,0 +1,236 @@
+//! Test recording the writes to inputs and replaying them on another database.
+#![cfg(feature = "serde")]
+
+use expect_test::expect;
+use salsa::{
+    Database, DatabaseImpl, Durability, RecordedEvent, Recording, Replay, ReplayError, Setter,
+};
+
+#[salsa::input(serde)]
+struct File {
+    path: String,
+    #[return_ref]
+    text: String,
+}
+
+#[salsa::input]
+struct NotRecorded {
+    field: u32,
+}
+
+#[salsa::tracked]
+fn line_count(db: &dyn Database, file: File) -> usize {
+    file.text(db).lines().count()
+}
+
+#[test]
+fn record_and_replay() {
+    let mut db = DatabaseImpl::new();
+    let before = File::new(&db, "before.rs".to_string(), String::new());
+
+    db.start_recording();
+    let file = File::new(&db, "lib.rs".to_string(), "fn a() {}".to_string());
+    NotRecorded::new(&db, 0).set_field(&mut db).to(1);
+    file.set_text(&mut db)
+        .with_durability(Durability::HIGH)
+        .to("fn a() {}\nfn b() {}".to_string());
+    db.synthetic_write(Durability::LOW);
+    let recording = db.stop_recording();
+    before.set_text(&mut db).to("not recorded".to_string());
+
+    expect![[r#"
+        Recording {
+            events: [
+                NewInput {
+                    input: "record_replay::File",
+                    id: 1,
+                    fields: [
+                        "\"lib.rs\"",
+                        "\"fn a() {}\"",
+                    ],
+                    durabilities: [
+                        0,
+                        0,
+                    ],
+                },
+                SetField {
+                    input: "record_replay::File",
+                    id: 1,
+                    field_index: 1,
+                    value: "\"fn a() {}\\nfn b() {}\"",
+                    durability: Some(
+                        2,
+                    ),
+                },
+                SyntheticWrite {
+                    durability: 0,
+                },
+            ],
+            unrecorded_inputs: [
+                "NotRecorded",
+            ],
+            errors: [],
+        }
+    "#]]
+    .assert_debug_eq(&recording);
+
+    // The recording can be saved, e.g. to be attached to a bug report.
+    let json = serde_json::to_string(&recording).unwrap();
+    let recording: Recording = serde_json::from_str(&json).unwrap();
+
+    // Replay on a fresh database, checking the result of a query after each event.
+    let recorded_id = salsa::plumbing::AsId::as_id(&file).as_u32();
+    let mut replayed = DatabaseImpl::new();
+    let mut line_counts = vec![];
+    Replay::new(&recording)
+        .input::<File>()
+        .run(&mut replayed, |db, step| {
+            let file: File = step.input(recorded_id).unwrap();
+            line_counts.push((step.index, line_count(db, file)));
+        })
+        .unwrap();
+    assert_eq!(line_counts, [(0, 1), (1, 2), (2, 2)]);
+}
+
+#[test]
+fn unknown_input() {
+    let db = DatabaseImpl::new();
+    db.start_recording();
+    File::new(&db, "lib.rs".to_string(), String::new());
+    let recording = db.stop_recording();
+
+    let result = Replay::new(&recording).run(&mut DatabaseImpl::new(), |_, _| {});
+    assert_eq!(
+        result,
+        Err(ReplayError::UnknownInput("record_replay::File".to_string()))
+    );
+}
+
+#[salsa::input(serde)]
+struct Project {
+    files: Vec<File>,
+}
+
+#[test]
+fn inputs_in_fields_are_recorded_by_id() {
+    let mut db = DatabaseImpl::new();
+    db.start_recording();
+    let a = File::new(&db, "a.rs".to_string(), String::new());
+    let project = Project::new(&db, vec![a]);
+    let b = File::new(&db, "b.rs".to_string(), String::new());
+    project.set_files(&mut db).to(vec![a, b]);
+    let recording = db.stop_recording();
+
+    let field = |event: &RecordedEvent| match event {
+        RecordedEvent::NewInput { fields, .. } => fields[0].clone(),
+        RecordedEvent::SetField { value, .. } => value.clone(),
+        RecordedEvent::SyntheticWrite { .. } => unreachable!(),
+    };
+    let id = |input: File| salsa::plumbing::AsId::as_id(&input).as_u32();
+    assert_eq!(field(&recording.events[1]), format!("[{}]", id(a)));
+    assert_eq!(
+        field(&recording.events[3]),
+        format!("[{},{}]", id(a), id(b))
+    );
+
+    // The replayed project refers to the replayed files.
+    let project_id = salsa::plumbing::AsId::as_id(&project).as_u32();
+    let mut replayed = DatabaseImpl::new();
+    let mut last = None;
+    Replay::new(&recording)
+        .input::<File>()
+        .input::<Project>()
+        .run(&mut replayed, |db, step| {
+            if let Some(project) = step.input::<Project>(project_id) {
+                let files = project.files(db);
+                let expected: Vec<File> = [a, b][..files.len()]
+                    .iter()
+                    .map(|&file| step.input(id(file)).unwrap())
+                    .collect();
+                assert_eq!(files, expected);
+                last = Some(files.len());
+            }
+        })
+        .unwrap();
+    assert_eq!(last, Some(2));
+}
+
+mod other {
+    #[salsa::input(serde)]
+    pub struct File {
+        pub size: u32,
+    }
+}
+
+#[test]
+fn inputs_are_recorded_with_their_module() {
+    let db = DatabaseImpl::new();
+    db.start_recording();
+    File::new(&db, "lib.rs".to_string(), String::new());
+    let other_file = other::File::new(&db, 1);
+    let recording = db.stop_recording();
+    let other_file_id = salsa::plumbing::AsId::as_id(&other_file).as_u32();
+
+    let mut replayed = DatabaseImpl::new();
+    let mut sizes = vec![];
+    Replay::new(&recording)
+        .input::<File>()
+        .input::<other::File>()
+        .run(&mut replayed, |db, step| {
+            if let Some(file) = step.input::<other::File>(other_file_id) {
+                sizes.push(file.size(db));
+            }
+        })
+        .unwrap();
+    assert_eq!(sizes, [1]);
+}
+
+#[test]
+#[should_panic(expected = "input `record_replay::File` is registered twice")]
+fn duplicate_input() {
+    let recording = Recording::default();
+    let _ = Replay::new(&recording).input::<File>().input::<File>();
+}
+
+#[test]
+fn malformed_recording() {
+    let db = DatabaseImpl::new();
+    db.start_recording();
+    File::new(&db, "lib.rs".to_string(), String::new());
+    let recording = db.stop_recording();
+
+    let run = |recording: &Recording| {
+        Replay::new(recording)
+            .input::<File>()
+            .run(&mut DatabaseImpl::new(), |_, _| {})
+    };
+
+    let mut missing_field = recording.clone();
+    if let RecordedEvent::NewInput { fields, .. } = &mut missing_field.events[0] {
+        fields.pop();
+    }
+    assert_eq!(
+        run(&missing_field),
+        Err(ReplayError::FieldCount {
+            input: "record_replay::File".to_string(),
+            expected: 2,
+            found: 1,
+        })
+    );
+
+    let mut unknown_field = recording.clone();
+    unknown_field.events.push(RecordedEvent::SetField {
+        input: "record_replay::File".to_string(),
+        id: 0,
+        field_index: 2,
+        value: "\"\"".to_string(),
+        durability: None,
+    });
+    assert_eq!(
+        run(&unknown_field),
+        Err(ReplayError::UnknownField {
+            input: "record_replay::File".to_string(),
+            field_index: 2,
+        })
+    );
+}
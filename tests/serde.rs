@@ -0,0 +1,139 @@
+//! Test the `serde` option on salsa structs, which (de)serializes
+//! their fields using the attached database.
+#![cfg(feature = "serde")]
+
+use expect_test::expect;
+use salsa::Database;
+
+#[salsa::input(serde)]
+struct File {
+    path: String,
+    #[return_ref]
+    text: String,
+}
+
+#[salsa::interned(serde)]
+struct Name<'db> {
+    text: String,
+}
+
+#[salsa::tracked(serde)]
+struct Item<'db> {
+    #[id]
+    name: Name<'db>,
+    arity: usize,
+}
+
+#[salsa::tracked]
+fn items(db: &dyn salsa::Database, file: File) -> Vec<Item<'_>> {
+    file.text(db)
+        .lines()
+        .map(|line| Item::new(db, Name::new(db, line.to_string()), line.len()))
+        .collect()
+}
+
+#[salsa::input]
+struct Saved {
+    json: String,
+}
+
+#[salsa::tracked]
+fn reload_items(db: &dyn salsa::Database, saved: Saved) -> Vec<usize> {
+    let items: Vec<Item<'_>> = serde_json::from_str(&saved.json(db)).unwrap();
+    items.into_iter().map(|item| item.arity(db)).collect()
+}
+
+#[test]
+fn input() {
+    salsa::DatabaseImpl::new().attach(|db| {
+        let file = File::new(db, "lib.rs".to_string(), "fn".to_string());
+        let json = serde_json::to_string(&file).unwrap();
+        expect![[r#"{"path":"lib.rs","text":"fn"}"#]].assert_eq(&json);
+
+        let copy: File = serde_json::from_str(&json).unwrap();
+        assert_ne!(copy, file);
+        assert_eq!(copy.text(db), "fn");
+    })
+}
+
+#[test]
+fn interned_is_reinterned() {
+    salsa::DatabaseImpl::new().attach(|db| {
+        let name = Name::new(db, "main".to_string());
+        let json = serde_json::to_string(&name).unwrap();
+        expect![[r#"{"text":"main"}"#]].assert_eq(&json);
+
+        let copy: Name<'_> = serde_json::from_str(&json).unwrap();
+        assert_eq!(copy, name);
+
+        // Field names are optional for formats that write sequences.
+        let copy: Name<'_> = serde_json::from_str(r#"["main"]"#).unwrap();
+        assert_eq!(copy, name);
+    })
+}
+
+#[test]
+fn tracked() {
+    salsa::DatabaseImpl::new().attach(|db| {
+        let file = File::new(db, "lib.rs".to_string(), "a\nbc".to_string());
+        let json = serde_json::to_string(&items(db, file)).unwrap();
+        expect![[r#"[{"name":{"text":"a"},"arity":1},{"name":{"text":"bc"},"arity":2}]"#]]
+            .assert_eq(&json);
+
+        // Tracked structs can only be created within a tracked function.
+        let saved = Saved::new(db, json);
+        assert_eq!(reload_items(db, saved), [1, 2]);
+    })
+}
+
+#[test]
+fn errors() {
+    let db = salsa::DatabaseImpl::new();
+    let name = Name::new(&db, "main".to_string());
+    let error = serde_json::to_string(&name).unwrap_err();
+    expect!["cannot serialize `Name` without an attached database"].assert_eq(&error.to_string());
+
+    db.attach(|_| {
+        let error =
+            serde_json::from_str::<Name<'_>>(r#"{"text":"main","text":"main"}"#).unwrap_err();
+        expect!["duplicate field `text` at line 1 column 21"].assert_eq(&error.to_string());
+
+        let error = serde_json::from_str::<Name<'_>>(r#"{}"#).unwrap_err();
+        expect!["missing field `text` at line 1 column 2"].assert_eq(&error.to_string());
+    })
+}
+
+#[salsa::input(serde)]
+struct Inner {
+    n: u32,
+}
+
+#[salsa::input(serde)]
+struct Outer {
+    inner: Inner,
+    s: String,
+}
+
+#[salsa::tracked]
+fn outer_json(_db: &dyn salsa::Database, outer: Outer) -> String {
+    serde_json::to_string(&outer).unwrap()
+}
+
+#[test]
+fn serialize_in_tracked_fn_records_reads() {
+    use salsa::Setter;
+
+    let mut db = salsa::DatabaseImpl::new();
+    let inner = Inner::new(&db, 1);
+    let outer = Outer::new(&db, inner, "a".to_string());
+    let json = db.attach(|db| outer_json(db, outer));
+    expect![[r#"{"inner":{"n":1},"s":"a"}"#]].assert_eq(&json);
+
+    outer.set_s(&mut db).to("b".to_string());
+    let json = db.attach(|db| outer_json(db, outer));
+    expect![[r#"{"inner":{"n":1},"s":"b"}"#]].assert_eq(&json);
+
+    inner.set_n(&mut db).to(2);
+    let json = db.attach(|db| outer_json(db, outer));
+    expect![[r#"{"inner":{"n":2},"s":"b"}"#]].assert_eq(&json);
+}
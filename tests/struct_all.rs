@@ -0,0 +1,159 @@
+//! Test the `all` function generated for inputs, interned and tracked structs.
+
+use salsa::testing::{assert_executed, TestDatabase};
+use salsa::{Database, Setter};
+
+#[salsa::input]
+struct File {
+    text: String,
+}
+
+#[salsa::interned]
+struct Word<'db> {
+    text: String,
+}
+
+#[salsa::tracked]
+struct Line<'db> {
+    text: String,
+}
+
+#[salsa::tracked]
+fn total_len(db: &dyn Database) -> usize {
+    File::all(db).map(|file| file.text(db).len()).sum()
+}
+
+#[salsa::tracked]
+fn lines(db: &dyn Database, file: File) -> Vec<Line<'_>> {
+    file.text(db)
+        .lines()
+        .map(|line| Line::new(db, line.to_string()))
+        .collect()
+}
+
+#[salsa::tracked]
+fn words(db: &dyn Database, file: File) -> Vec<Word<'_>> {
+    file.text(db)
+        .split_whitespace()
+        .map(|word| Word::new(db, word.to_string()))
+        .collect()
+}
+
+#[test]
+fn inputs() {
+    let db = TestDatabase::default();
+    assert_eq!(File::all(&db).count(), 0);
+
+    let a = File::new(&db, "a".to_string());
+    let b = File::new(&db, "b".to_string());
+    assert_eq!(File::all(&db).collect::<Vec<_>>(), [a, b]);
+}
+
+#[test]
+fn inputs_in_tracked_function() {
+    let mut db = TestDatabase::default();
+    File::new(&db, "hello".to_string());
+    assert_eq!(total_len(&db), 5);
+    assert_executed!(db, [total_len()]);
+
+    // Creating an input does not start a new revision.
+    let file = File::new(&db, "world".to_string());
+    db.synthetic_write(salsa::Durability::LOW);
+    assert_eq!(total_len(&db), 10);
+    assert_executed!(db, [total_len()]);
+
+    // No input was created since the last execution.
+    db.synthetic_write(salsa::Durability::LOW);
+    assert_eq!(total_len(&db), 10);
+    assert_executed!(db, []);
+
+    file.set_text(&mut db).to("!".to_string());
+    assert_eq!(total_len(&db), 6);
+    assert_executed!(db, [total_len()]);
+}
+
+#[test]
+fn interned() {
+    let db = TestDatabase::default();
+    let file = File::new(&db, "a b a c".to_string());
+    words(&db, file);
+
+    let all: Vec<String> = Word::all(&db).map(|word| word.text(&db)).collect();
+    assert_eq!(all, ["a", "b", "c"]);
+}
+
+#[test]
+fn tracked_structs_skip_deleted() {
+    let mut db = TestDatabase::default();
+    let file = File::new(&db, "a\nb\nc".to_string());
+    lines(&db, file);
+
+    let all: Vec<String> = Line::all(&db).map(|line| line.text(&db)).collect();
+    assert_eq!(all, ["a", "b", "c"]);
+
+    // `b` and `c` are no longer created, so they are deleted.
+    file.set_text(&mut db).to("a\nd".to_string());
+    lines(&db, file);
+
+    let all: Vec<String> = Line::all(&db).map(|line| line.text(&db)).collect();
+    assert_eq!(all, ["a", "d"]);
+}
+
+#[salsa::tracked]
+fn word_count(db: &dyn Database) -> usize {
+    Word::all(db).count()
+}
+
+#[salsa::tracked]
+fn line_count(db: &dyn Database) -> usize {
+    Line::all(db).count()
+}
+
+#[test]
+fn interned_in_tracked_function() {
+    let mut db = TestDatabase::default();
+    let file = File::new(&db, "a b".to_string());
+    words(&db, file);
+    db.synthetic_write(salsa::Durability::LOW);
+    assert_eq!(word_count(&db), 2);
+    assert_executed!(db, [words(file), word_count()]);
+
+    // Interning existing values does not change the set of interned values.
+    file.set_text(&mut db).to("b a".to_string());
+    words(&db, file);
+    assert_eq!(word_count(&db), 2);
+    assert_executed!(db, [words(file)]);
+
+    file.set_text(&mut db).to("b a c".to_string());
+    words(&db, file);
+    assert_eq!(word_count(&db), 3);
+    assert_executed!(db, [words(file), word_count()]);
+}
+
+#[test]
+fn tracked_structs_in_tracked_function() {
+    let mut db = TestDatabase::default();
+    let file = File::new(&db, "a\nb".to_string());
+    lines(&db, file);
+    db.synthetic_write(salsa::Durability::LOW);
+    assert_eq!(line_count(&db), 2);
+    assert_executed!(db, [lines(file), line_count()]);
+
+    // No tracked struct was created or deleted since the last execution.
+    db.synthetic_write(salsa::Durability::LOW);
+    lines(&db, file);
+    assert_eq!(line_count(&db), 2);
+    assert_executed!(db, []);
+
+    // `c` is created.
+    file.set_text(&mut db).to("a\nb\nc".to_string());
+    lines(&db, file);
+    assert_eq!(line_count(&db), 3);
+    assert_executed!(db, [lines(file), line_count()]);
+
+    // `b` and `c` are deleted.
+    file.set_text(&mut db).to("a".to_string());
+    lines(&db, file);
+    assert_eq!(line_count(&db), 1);
+    assert_executed!(db, [lines(file), line_count()]);
+}
@@ -0,0 +1,356 @@
+//! Test that `sweep` drops the memos that were not verified recently,
+//! and that they are recomputed when needed again.
+
+mod common;
+use common::{HasLogger, LogDatabase, Logger};
+
+use expect_test::expect;
+use salsa::{
+    Database, DatabaseKeyIndex, Durability, EventKind, IngredientKind, Setter, SweepOptions,
+    SweepReport,
+};
+use std::sync::Mutex;
+use test_log::test;
+
+/// Remembers the queries that were executed, to find the ingredient of a function.
+#[salsa::db]
+#[derive(Default)]
+struct KeyLoggerDatabase {
+    storage: salsa::Storage<Self>,
+    logger: Logger,
+    executed: Mutex<Vec<DatabaseKeyIndex>>,
+}
+
+#[salsa::db]
+impl Database for KeyLoggerDatabase {
+    fn salsa_event(&self, event: &dyn Fn() -> salsa::Event) {
+        if let EventKind::WillExecute { database_key } = event().kind {
+            self.executed.lock().unwrap().push(database_key);
+        }
+    }
+}
+
+impl HasLogger for KeyLoggerDatabase {
+    fn logger(&self) -> &Logger {
+        &self.logger
+    }
+}
+
+#[salsa::input]
+struct MyInput {
+    field: u32,
+}
+
+#[salsa::tracked]
+fn double(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log(format!("double({})", input.field(db)));
+    input.field(db) * 2
+}
+
+#[salsa::tracked]
+fn quadruple(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log(format!("quadruple({})", input.field(db)));
+    double(db, input) * 2
+}
+
+#[test]
+fn sweep_memos_not_verified_recently() {
+    let mut db = KeyLoggerDatabase::default();
+    let a = MyInput::new(&db, 1);
+    let b = MyInput::new(&db, 2);
+    let unrelated = MyInput::new(&db, 0);
+
+    double(&db, a);
+    double(&db, b);
+    unrelated.set_field(&mut db).to(1);
+    double(&db, a);
+    db.assert_logs(expect![[r#"
+        [
+            "double(1)",
+            "double(2)",
+        ]"#]]);
+
+    // Sweeping starts a new revision, in which `a` was verified one revision ago and `b` two.
+    let report = db.sweep(SweepOptions {
+        older_than_revisions: 1,
+        ingredients: None,
+    });
+    assert_eq!(report.memos, 1);
+    assert_eq!(report.values, 1);
+    assert_eq!(report.edges, 1);
+    assert_eq!(report.tracked_structs, 0);
+    assert!(report.bytes > 0);
+
+    assert_eq!(double(&db, a), 2);
+    assert_eq!(double(&db, b), 4);
+    db.assert_logs(expect![[r#"
+        [
+            "double(2)",
+        ]"#]]);
+
+    // Nothing is left to sweep.
+    let report = db.sweep(SweepOptions {
+        older_than_revisions: 1,
+        ingredients: None,
+    });
+    assert_eq!(report, SweepReport::default());
+}
+
+#[test]
+fn dependents_of_swept_memos_are_recomputed() {
+    let mut db = KeyLoggerDatabase::default();
+    let a = MyInput::new(&db, 1);
+
+    double(&db, a);
+    let double_ingredient = db.executed.lock().unwrap()[0].ingredient_index();
+    quadruple(&db, a);
+    db.assert_logs(expect![[r#"
+        [
+            "double(1)",
+            "quadruple(1)",
+        ]"#]]);
+
+    // Only sweep `double`: `quadruple` is kept, but its input has low durability, so it has to be
+    // verified in the new revision, which cannot tell whether `double` changed.
+    let report = db.sweep(SweepOptions {
+        older_than_revisions: 0,
+        ingredients: Some(vec![double_ingredient]),
+    });
+    assert_eq!(report.memos, 1);
+
+    assert_eq!(quadruple(&db, a), 4);
+    db.assert_logs(expect![[r#"
+        [
+            "quadruple(1)",
+            "double(1)",
+        ]"#]]);
+}
+
+#[test]
+fn durable_dependents_of_swept_memos_are_reused() {
+    let mut db = KeyLoggerDatabase::default();
+    let a = MyInput::new(&db, 0);
+    a.set_field(&mut db).with_durability(Durability::HIGH).to(1);
+
+    double(&db, a);
+    let double_ingredient = db.executed.lock().unwrap()[0].ingredient_index();
+    quadruple(&db, a);
+    db.assert_logs(expect![[r#"
+        [
+            "double(1)",
+            "quadruple(1)",
+        ]"#]]);
+
+    // The value of `quadruple` is still up to date, and no high durability input changed since it was
+    // verified, so it is reused without looking at `double`.
+    let report = db.sweep(SweepOptions {
+        older_than_revisions: 0,
+        ingredients: Some(vec![double_ingredient]),
+    });
+    assert_eq!(report.memos, 1);
+
+    assert_eq!(quadruple(&db, a), 4);
+    db.assert_logs(expect![[r#"
+        []"#]]);
+
+    // Once its input changes, `quadruple` cannot tell whether `double` changed, so it is recomputed.
+    a.set_field(&mut db).with_durability(Durability::HIGH).to(2);
+    assert_eq!(quadruple(&db, a), 8);
+    db.assert_logs(expect![[r#"
+        [
+            "quadruple(2)",
+            "double(2)",
+        ]"#]]);
+}
+
+#[salsa::accumulator]
+struct Note(#[allow(dead_code)] u32);
+
+#[salsa::tracked]
+fn note(db: &dyn LogDatabase, input: MyInput) {
+    salsa::Accumulator::accumulate(Note(input.field(db)), db);
+}
+
+#[salsa::tracked]
+fn notes(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log(format!("notes({})", input.field(db)));
+    note(db, input);
+    double(db, input)
+}
+
+#[test]
+fn accumulated_values_after_sweep() {
+    let mut db = KeyLoggerDatabase::default();
+    let a = MyInput::new(&db, 1);
+
+    notes(&db, a);
+    let values = notes::accumulated::<Note>(&db, a);
+    assert_eq!(values.len(), 1);
+    db.assert_logs(expect![[r#"
+        [
+            "notes(1)",
+            "double(1)",
+        ]"#]]);
+
+    db.sweep(SweepOptions {
+        older_than_revisions: 0,
+        ingredients: None,
+    });
+
+    a.set_field(&mut db).to(2);
+    notes(&db, a);
+    let values = notes::accumulated::<Note>(&db, a);
+    assert_eq!(values.len(), 1);
+    db.assert_logs(expect![[r#"
+        [
+            "notes(2)",
+            "double(2)",
+        ]"#]]);
+}
+
+#[salsa::tracked]
+fn count_notes(db: &dyn LogDatabase, input: MyInput) -> usize {
+    db.push_log(format!("count_notes({})", input.field(db)));
+    notes::accumulated::<Note>(db, input).len()
+}
+
+#[test]
+fn accumulated_values_read_after_sweep() {
+    let mut db = KeyLoggerDatabase::default();
+    let a = MyInput::new(&db, 1);
+    let b = MyInput::new(&db, 2);
+
+    count_notes(&db, a);
+    db.sweep(SweepOptions {
+        older_than_revisions: 0,
+        ingredients: None,
+    });
+    db.assert_logs(expect![[r#"
+        [
+            "count_notes(1)",
+            "notes(1)",
+            "double(1)",
+        ]"#]]);
+
+    // No memo depends on the values accumulated for `a` anymore,
+    // so the dependency on those for `b` reuses its key.
+    count_notes(&db, b);
+    a.set_field(&mut db).to(3);
+    count_notes(&db, b);
+    db.assert_logs(expect![[r#"
+        [
+            "count_notes(2)",
+            "notes(2)",
+            "double(2)",
+        ]"#]]);
+
+    b.set_field(&mut db).to(4);
+    assert_eq!(count_notes(&db, b), 1);
+    db.assert_logs(expect![[r#"
+        [
+            "count_notes(4)",
+            "notes(4)",
+            "double(4)",
+        ]"#]]);
+}
+
+fn live_entries(db: &dyn Database, kind: IngredientKind) -> usize {
+    db.ingredients()
+        .into_iter()
+        .filter(|ingredient| ingredient.kind == kind)
+        .map(|ingredient| ingredient.live_entries)
+        .sum()
+}
+
+#[test]
+fn accumulated_values_of_swept_memos_are_freed() {
+    let mut db = KeyLoggerDatabase::default();
+    let a = MyInput::new(&db, 1);
+
+    notes(&db, a);
+    assert_eq!(live_entries(&db, IngredientKind::Accumulator), 1);
+
+    let report = db.sweep(SweepOptions {
+        older_than_revisions: 0,
+        ingredients: None,
+    });
+    assert_eq!(report.memos, 3);
+    assert_eq!(live_entries(&db, IngredientKind::Accumulator), 0);
+}
+
+#[salsa::tracked]
+struct Word<'db> {
+    text: String,
+}
+
+#[salsa::input]
+struct Text {
+    text: String,
+}
+
+#[salsa::tracked]
+fn words(db: &dyn LogDatabase, text: Text) -> Vec<Word<'_>> {
+    db.push_log(format!("words({})", text.text(db)));
+    text.text(db)
+        .split_whitespace()
+        .map(|word| Word::new(db, word.to_string()))
+        .collect()
+}
+
+#[salsa::tracked]
+fn longest(db: &dyn LogDatabase, text: Text) -> usize {
+    db.push_log(format!("longest({})", text.text(db)));
+    words(db, text)
+        .iter()
+        .map(|word| word.text(db).len())
+        .max()
+        .unwrap_or(0)
+}
+
+#[test]
+fn tracked_structs_of_swept_memos_are_deleted() {
+    let mut db = KeyLoggerDatabase::default();
+    let text = Text::new(&db, "a bb ccc".to_string());
+
+    words(&db, text);
+    assert_eq!(live_entries(&db, IngredientKind::TrackedStruct), 3);
+
+    let report = db.sweep(SweepOptions {
+        older_than_revisions: 0,
+        ingredients: None,
+    });
+    assert_eq!(report.memos, 1);
+    assert_eq!(report.tracked_structs, 3);
+    assert_eq!(live_entries(&db, IngredientKind::TrackedStruct), 0);
+
+    assert_eq!(words(&db, text).len(), 3);
+    db.assert_logs(expect![[r#"
+        [
+            "words(a bb ccc)",
+            "words(a bb ccc)",
+        ]"#]]);
+}
+
+#[test]
+fn referenced_tracked_structs_are_kept() {
+    let mut db = KeyLoggerDatabase::default();
+    let text = Text::new(&db, "a bb ccc".to_string());
+
+    assert_eq!(longest(&db, text), 3);
+    let words_ingredient = db.executed.lock().unwrap()[1].ingredient_index();
+
+    // `longest` is kept and read the words, so the memo of `words` that created them is kept too.
+    let report = db.sweep(SweepOptions {
+        older_than_revisions: 0,
+        ingredients: Some(vec![words_ingredient]),
+    });
+    assert_eq!(report, SweepReport::default());
+    assert_eq!(live_entries(&db, IngredientKind::TrackedStruct), 3);
+
+    assert_eq!(longest(&db, text), 3);
+    db.assert_logs(expect![[r#"
+        [
+            "longest(a bb ccc)",
+            "words(a bb ccc)",
+        ]"#]]);
+}
@@ -0,0 +1,66 @@
+//! Test the assertions of `salsa::testing`.
+
+use salsa::testing::{assert_backdated, assert_executed, assert_reused, TestDatabase};
+use salsa::Setter;
+
+#[salsa::input]
+struct File {
+    text: String,
+}
+
+#[salsa::tracked]
+fn len(db: &dyn salsa::Database, file: File) -> usize {
+    file.text(db).len()
+}
+
+#[salsa::tracked]
+fn is_long(db: &dyn salsa::Database, file: File) -> bool {
+    len(db, file) > 5
+}
+
+#[salsa::tracked]
+fn repeat(db: &dyn salsa::Database, file: File, times: usize) -> String {
+    file.text(db).repeat(times)
+}
+
+#[test]
+fn executed_reused_backdated() {
+    let mut db = TestDatabase::default();
+    let file = File::new(&db, "hello".to_string());
+
+    assert!(!is_long(&db, file));
+    assert_executed!(db, [is_long(file), len(file)]);
+
+    // Same length: `len` is backdated and `is_long` reused.
+    file.set_text(&mut db).to("world".to_string());
+    assert!(!is_long(&db, file));
+    assert_executed!(db, [len(file)]);
+    assert_backdated!(db, [len(file)]);
+    assert_reused!(db, [is_long(file)]);
+
+    file.set_text(&mut db).to("hello world".to_string());
+    assert!(is_long(&db, file));
+    assert_executed!(db, [len(file), is_long(file)]);
+    assert_backdated!(db, []);
+    assert_reused!(db, []);
+}
+
+#[test]
+fn interned_arguments() {
+    let db = TestDatabase::default();
+    let file = File::new(&db, "ab".to_string());
+
+    assert_eq!(repeat(&db, file, 2), "abab");
+    assert_eq!(repeat(&db, file, 3), "ababab");
+    assert_executed!(db, [repeat(file, 2), repeat(file, 3)]);
+}
+
+#[test]
+#[should_panic(expected = "unexpected Executed queries\n  expected: []\n    actual: [len(Id(0))]")]
+fn unexpected_execution() {
+    let db = TestDatabase::default();
+    let file = File::new(&db, "hello".to_string());
+
+    len(&db, file);
+    assert_executed!(db, []);
+}
@@ -0,0 +1,61 @@
+//! Test a tracked struct field with a custom equality used for backdating.
+
+mod common;
+
+use common::LogDatabase;
+use expect_test::expect;
+use salsa::Setter as _;
+use test_log::test;
+
+#[salsa::input]
+struct MyInput {
+    field: f64,
+}
+
+/// Values within 0.1 of each other are considered the same.
+fn roughly_equal(old: &f64, new: &f64) -> bool {
+    (old - new).abs() < 0.1
+}
+
+#[salsa::tracked]
+struct MyTracked<'db> {
+    #[eq(roughly_equal)]
+    value: f64,
+}
+
+#[salsa::tracked]
+fn make_tracked<'db>(db: &'db dyn LogDatabase, input: MyInput) -> MyTracked<'db> {
+    MyTracked::new(db, input.field(db))
+}
+
+#[salsa::tracked]
+fn read_value<'db>(db: &'db dyn LogDatabase, tracked: MyTracked<'db>) -> bool {
+    db.push_log(format!("read_value({})", tracked.value(db)));
+    tracked.value(db) > 1.0
+}
+
+#[salsa::tracked]
+fn the_fn(db: &dyn LogDatabase, input: MyInput) -> bool {
+    read_value(db, make_tracked(db, input))
+}
+
+#[test]
+fn execute() {
+    let mut db = common::LoggerDatabase::default();
+
+    let input = MyInput::new(&db, 1.0);
+    assert!(!the_fn(&db, input));
+
+    // Roughly equal: `read_value` is not re-executed.
+    input.set_field(&mut db).to(1.05);
+    assert!(!the_fn(&db, input));
+
+    input.set_field(&mut db).to(1.5);
+    assert!(the_fn(&db, input));
+
+    db.assert_logs(expect![[r#"
+        [
+            "read_value(1)",
+            "read_value(1.5)",
+        ]"#]]);
+}
@@ -0,0 +1,163 @@
+//! Test that a `tracked` fn with the `catch_panic` option
+//! memoizes its panic until one of its inputs changes.
+
+mod common;
+use common::{LogDatabase, LoggerDatabase};
+
+use expect_test::expect;
+use salsa::Setter;
+use std::panic::AssertUnwindSafe;
+use test_log::test;
+
+#[salsa::input]
+struct MyInput {
+    dividend: u32,
+    divisor: u32,
+}
+
+#[salsa::tracked(catch_panic)]
+fn quotient(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log(format!("quotient({:?})", input.divisor(db)));
+    if input.divisor(db) == 0 {
+        panic!("attempt to divide by zero");
+    }
+    input.dividend(db) / input.divisor(db)
+}
+
+#[salsa::tracked]
+fn checked_quotient(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log(format!("checked_quotient({:?})", input.divisor(db)));
+    if input.divisor(db) == 0 {
+        panic!("divisor is {}", input.divisor(db));
+    }
+    input.dividend(db) / input.divisor(db)
+}
+
+#[salsa::tracked(catch_panic)]
+fn checked_quotient_plus_one(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log("checked_quotient_plus_one".to_string());
+    checked_quotient(db, input) + 1
+}
+
+#[derive(Debug, PartialEq)]
+struct DivideByZero;
+
+#[salsa::tracked(catch_panic)]
+fn quotient_or_panic_any(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    if input.divisor(db) == 0 {
+        std::panic::panic_any(DivideByZero);
+    }
+    input.dividend(db) / input.divisor(db)
+}
+
+#[salsa::tracked(catch_panic)]
+fn quotient_plus_one(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log("quotient_plus_one".to_string());
+    quotient(db, input) + 1
+}
+
+#[track_caller]
+fn extract_panic(f: impl FnOnce() -> u32) -> String {
+    match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(v) => panic!("expected a panic, got {v}"),
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(s) => s.to_string(),
+            Err(payload) => *payload.downcast::<String>().unwrap(),
+        },
+    }
+}
+
+#[test]
+fn panic_is_memoized() {
+    let db = LoggerDatabase::default();
+    let input = MyInput::new(&db, 22, 0);
+
+    assert_eq!(
+        extract_panic(|| quotient(&db, input)),
+        "attempt to divide by zero"
+    );
+    assert_eq!(
+        extract_panic(|| quotient(&db, input)),
+        "attempt to divide by zero"
+    );
+
+    db.assert_logs(expect![[r#"
+        [
+            "quotient(0)",
+        ]"#]]);
+}
+
+#[test]
+fn panic_is_cleared_when_inputs_change() {
+    let mut db = LoggerDatabase::default();
+    let input = MyInput::new(&db, 22, 0);
+    let other = MyInput::new(&db, 1, 1);
+
+    extract_panic(|| quotient_plus_one(&db, input));
+    db.assert_logs(expect![[r#"
+        [
+            "quotient_plus_one",
+            "quotient(0)",
+        ]"#]]);
+
+    // Changing an input that was not read keeps the memoized panic.
+    other.set_divisor(&mut db).to(2);
+    extract_panic(|| quotient_plus_one(&db, input));
+    db.assert_logs(expect![[r#"
+        []"#]]);
+
+    // Changing the divisor re-executes both queries.
+    input.set_divisor(&mut db).to(2);
+    assert_eq!(quotient_plus_one(&db, input), 12);
+    db.assert_logs(expect![[r#"
+        [
+            "quotient_plus_one",
+            "quotient(2)",
+        ]"#]]);
+}
+
+#[test]
+fn panic_of_nested_query_depends_on_its_inputs() {
+    let mut db = LoggerDatabase::default();
+    let input = MyInput::new(&db, 22, 0);
+
+    assert_eq!(
+        extract_panic(|| checked_quotient_plus_one(&db, input)),
+        "divisor is 0"
+    );
+    db.assert_logs(expect![[r#"
+        [
+            "checked_quotient_plus_one",
+            "checked_quotient(0)",
+        ]"#]]);
+
+    // `checked_quotient` unwound without a memo, but its read of the divisor was kept.
+    input.set_dividend(&mut db).to(44);
+    extract_panic(|| checked_quotient_plus_one(&db, input));
+    db.assert_logs(expect![[r#"
+        []"#]]);
+
+    input.set_divisor(&mut db).to(2);
+    assert_eq!(checked_quotient_plus_one(&db, input), 23);
+    db.assert_logs(expect![[r#"
+        [
+            "checked_quotient_plus_one",
+            "checked_quotient(2)",
+        ]"#]]);
+}
+
+#[test]
+fn panic_payload_keeps_its_type() {
+    let db = LoggerDatabase::default();
+    let input = MyInput::new(&db, 22, 0);
+
+    let payload = std::panic::catch_unwind(AssertUnwindSafe(|| quotient(&db, input))).unwrap_err();
+    assert_eq!(
+        payload.downcast_ref::<&'static str>(),
+        Some(&"attempt to divide by zero")
+    );
+
+    let payload = std::panic::catch_unwind(AssertUnwindSafe(|| quotient_or_panic_any(&db, input)))
+        .unwrap_err();
+    assert_eq!(payload.downcast_ref::<DivideByZero>(), Some(&DivideByZero));
+}
@@ -0,0 +1,73 @@
+//! Test a tracked function with a custom equality used for backdating.
+
+mod common;
+
+use common::LogDatabase;
+use expect_test::expect;
+use salsa::Setter as _;
+
+#[salsa::input]
+struct Input {
+    text: String,
+}
+
+#[derive(Clone, Debug)]
+struct Word {
+    text: String,
+    offset: usize,
+}
+
+/// Words are the same if their text is, wherever they are.
+fn same_words(old: &[Word], new: &[Word]) -> bool {
+    old.len() == new.len() && old.iter().zip(new).all(|(a, b)| a.text == b.text)
+}
+
+#[salsa::tracked(eq = same_words)]
+fn words(db: &dyn LogDatabase, input: Input) -> Vec<Word> {
+    let text = input.text(db);
+    db.push_log(format!("words({text:?})"));
+    text.split(' ')
+        .scan(0, |offset, word| {
+            let w = Word {
+                text: word.to_string(),
+                offset: *offset,
+            };
+            *offset += word.len() + 1;
+            Some(w)
+        })
+        .filter(|w| !w.text.is_empty())
+        .collect()
+}
+
+#[salsa::tracked]
+fn word_count(db: &dyn LogDatabase, input: Input) -> usize {
+    db.push_log("word_count".to_string());
+    words(db, input).len()
+}
+
+#[test]
+fn invoke() {
+    let mut db = common::LoggerDatabase::default();
+
+    let input = Input::new(&db, "a b".to_string());
+    assert_eq!(word_count(&db, input), 2);
+
+    // Only the offsets change, so `word_count` is not re-executed...
+    input.set_text(&mut db).to(" a  b".to_string());
+    assert_eq!(word_count(&db, input), 2);
+
+    // ...but the new value is returned.
+    assert_eq!(words(&db, input)[1].offset, 4);
+
+    input.set_text(&mut db).to("a c".to_string());
+    assert_eq!(word_count(&db, input), 2);
+
+    db.assert_logs(expect![[r#"
+        [
+            "word_count",
+            "words(\"a b\")",
+            "words(\" a  b\")",
+            "words(\"a c\")",
+            "word_count",
+        ]"#]]);
+}
@@ -0,0 +1,91 @@
+//! Test that a tracked function with the `fingerprint` option is
+//! backdated after its value was evicted.
+
+mod common;
+use common::{LogDatabase, LoggerDatabase};
+
+use expect_test::expect;
+use salsa::Setter;
+use test_log::test;
+
+#[salsa::input]
+struct MyInput {
+    text: String,
+}
+
+#[salsa::tracked(fingerprint, lru = 1)]
+fn trimmed_len(db: &dyn LogDatabase, input: MyInput) -> usize {
+    db.push_log(format!("trimmed_len({:?})", input.text(db)));
+    input.text(db).trim().len()
+}
+
+#[salsa::tracked]
+fn double_len(db: &dyn LogDatabase, input: MyInput) -> usize {
+    let len = trimmed_len(db, input);
+    db.push_log(format!("double_len({len})"));
+    len * 2
+}
+
+#[salsa::tracked(lru = 1)]
+fn trimmed_len_no_fingerprint(db: &dyn LogDatabase, input: MyInput) -> usize {
+    db.push_log(format!("trimmed_len_no_fingerprint({:?})", input.text(db)));
+    input.text(db).trim().len()
+}
+
+#[salsa::tracked]
+fn double_len_no_fingerprint(db: &dyn LogDatabase, input: MyInput) -> usize {
+    let len = trimmed_len_no_fingerprint(db, input);
+    db.push_log(format!("double_len_no_fingerprint({len})"));
+    len * 2
+}
+
+#[test]
+fn backdate_evicted_value() {
+    let mut db = LoggerDatabase::default();
+    let a = MyInput::new(&db, "abc".to_string());
+    let b = MyInput::new(&db, "de".to_string());
+
+    assert_eq!(double_len(&db, a), 6);
+    // Evicts the value of `trimmed_len(a)`, but keeps its fingerprint.
+    assert_eq!(trimmed_len(&db, b), 2);
+    db.assert_logs(expect![[r#"
+        [
+            "trimmed_len(\"abc\")",
+            "double_len(3)",
+            "trimmed_len(\"de\")",
+        ]"#]]);
+
+    // `trimmed_len(a)` is recomputed, and since its fingerprint did not change,
+    // `double_len(a)` is not.
+    a.set_text(&mut db).to(" abc ".to_string());
+    assert_eq!(double_len(&db, a), 6);
+    db.assert_logs(expect![[r#"
+        [
+            "trimmed_len(\" abc \")",
+        ]"#]]);
+}
+
+#[test]
+fn evicted_value_without_fingerprint() {
+    let mut db = LoggerDatabase::default();
+    let a = MyInput::new(&db, "abc".to_string());
+    let b = MyInput::new(&db, "de".to_string());
+
+    assert_eq!(double_len_no_fingerprint(&db, a), 6);
+    assert_eq!(trimmed_len_no_fingerprint(&db, b), 2);
+    db.assert_logs(expect![[r#"
+        [
+            "trimmed_len_no_fingerprint(\"abc\")",
+            "double_len_no_fingerprint(3)",
+            "trimmed_len_no_fingerprint(\"de\")",
+        ]"#]]);
+
+    // Without the old value or its fingerprint, the value is considered changed.
+    a.set_text(&mut db).to(" abc ".to_string());
+    assert_eq!(double_len_no_fingerprint(&db, a), 6);
+    db.assert_logs(expect![[r#"
+        [
+            "trimmed_len_no_fingerprint(\" abc \")",
+            "double_len_no_fingerprint(3)",
+        ]"#]]);
+}
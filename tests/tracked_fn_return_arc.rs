@@ -0,0 +1,59 @@
+//! Test that `return_arc` functions hand out clones of the same `Arc`,
+//! which can be held across a new revision.
+
+use std::sync::Arc;
+
+use salsa::Setter;
+
+#[salsa::input]
+struct Input {
+    number: usize,
+}
+
+#[salsa::tracked(return_arc)]
+fn test(db: &dyn salsa::Database, input: Input) -> Vec<String> {
+    (0..input.number(db))
+        .map(|i| format!("test {}", i))
+        .collect()
+}
+
+#[salsa::tracked]
+impl Input {
+    #[salsa::tracked(return_arc)]
+    fn method(self, db: &dyn salsa::Database) -> String {
+        format!("method {}", self.number(db))
+    }
+}
+
+#[test]
+fn shares_memoized_value() {
+    let db = salsa::DatabaseImpl::new();
+    let input = Input::new(&db, 3);
+    let x: Arc<Vec<String>> = test(&db, input);
+    let y = test(&db, input);
+    assert!(Arc::ptr_eq(&x, &y));
+    expect_test::expect![[r#"
+        [
+            "test 0",
+            "test 1",
+            "test 2",
+        ]
+    "#]]
+    .assert_debug_eq(&x);
+
+    let m: Arc<String> = input.method(&db);
+    assert_eq!(*m, "method 3");
+}
+
+#[test]
+fn outlives_new_revision() {
+    let mut db = salsa::DatabaseImpl::new();
+    let input = Input::new(&db, 1);
+    let old = test(&db, input);
+
+    input.set_number(&mut db).to(2);
+    let new = test(&db, input);
+
+    assert_eq!(*old, ["test 0"]);
+    assert_eq!(*new, ["test 0", "test 1"]);
+}
@@ -0,0 +1,170 @@
+//! Test the `try_call` variant of tracked functions,
+//! which reports cycles as errors instead of unwinding.
+
+mod common;
+use common::{LogDatabase, LoggerDatabase};
+
+use expect_test::expect;
+use salsa::{Database, QueryError, Setter};
+use test_log::test;
+
+#[salsa::input]
+struct MyInput {
+    field: u32,
+}
+
+#[salsa::tracked]
+fn double(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    db.push_log(format!("double({})", input.field(db)));
+    input.field(db) * 2
+}
+
+#[salsa::tracked(return_ref)]
+fn digits(db: &dyn LogDatabase, input: MyInput, base: u32) -> Vec<u32> {
+    let mut n = input.field(db);
+    let mut digits = vec![];
+    while n > 0 {
+        digits.push(n % base);
+        n /= base;
+    }
+    digits
+}
+
+#[salsa::tracked]
+fn cycle_a(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    if input.field(db) == 0 {
+        0
+    } else {
+        cycle_b(db, input)
+    }
+}
+
+#[salsa::tracked]
+fn cycle_b(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    cycle_a(db, input) + 1
+}
+
+#[salsa::tracked]
+fn checked_a(db: &dyn LogDatabase, input: MyInput) -> Result<u32, QueryError> {
+    if input.field(db) == 0 {
+        Ok(0)
+    } else {
+        checked_b(db, input)
+    }
+}
+
+#[salsa::tracked]
+fn checked_b(db: &dyn LogDatabase, input: MyInput) -> Result<u32, QueryError> {
+    db.push_log(format!("checked_b({})", input.field(db)));
+    Ok(checked_a::try_call(db, input)?? + 1)
+}
+
+/// Unwinds if it is dropped during a panic, i.e. if a cycle unwinds through the function.
+struct NoUnwind;
+
+impl Drop for NoUnwind {
+    fn drop(&mut self) {
+        assert!(
+            !std::thread::panicking(),
+            "unwound through a query function"
+        );
+    }
+}
+
+#[salsa::tracked]
+fn guarded_a(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    let _guard = NoUnwind;
+    guarded_b::try_call(db, input).unwrap_or(100)
+}
+
+#[salsa::tracked]
+fn guarded_b(db: &dyn LogDatabase, input: MyInput) -> u32 {
+    let _guard = NoUnwind;
+    guarded_a::try_call(db, input).unwrap_or(10) + 1
+}
+
+#[test]
+fn ok() {
+    let db = LoggerDatabase::default();
+    let input = MyInput::new(&db, 22);
+
+    assert_eq!(double::try_call(&db, input).unwrap(), 44);
+    assert_eq!(double(&db, input), 44);
+
+    // The value is memoized, no matter which entry point computed it.
+    db.assert_logs(expect![[r#"
+        [
+            "double(22)",
+        ]"#]]);
+}
+
+#[test]
+fn ok_return_ref_with_interned_args() {
+    let db = LoggerDatabase::default();
+    let input = MyInput::new(&db, 22);
+
+    let result: &Vec<u32> = digits::try_call(&db, input, 10).unwrap();
+    assert_eq!(result, &[2, 2]);
+}
+
+#[test]
+fn cycle() {
+    let mut db = LoggerDatabase::default();
+    let input = MyInput::new(&db, 1);
+
+    db.attach(|db| {
+        let Err(QueryError::Cycle(cycle)) = cycle_a::try_call(db, input) else {
+            panic!("expected a cycle");
+        };
+        expect![[r#"
+            [
+                cycle_a(Id(0)),
+                cycle_b(Id(0)),
+            ]
+        "#]]
+        .assert_debug_eq(&cycle.all_participants(db));
+    });
+
+    // Once the cycle is broken, the same query succeeds.
+    input.set_field(&mut db).to(0);
+    assert_eq!(cycle_a::try_call(&db, input).unwrap(), 0);
+    assert_eq!(cycle_b::try_call(&db, input).unwrap(), 1);
+}
+
+#[test]
+fn cycle_in_query() {
+    let mut db = LoggerDatabase::default();
+    let input = MyInput::new(&db, 1);
+
+    // `checked_b` gets the cycle as an error and returns it to `checked_a`.
+    let Err(QueryError::Cycle(_)) = checked_a(&db, input) else {
+        panic!("expected a cycle");
+    };
+    db.assert_logs(expect![[r#"
+        [
+            "checked_b(1)",
+        ]"#]]);
+
+    // A query that got an error is executed again in the next revision.
+    db.synthetic_write(salsa::Durability::LOW);
+    let Err(QueryError::Cycle(_)) = checked_a(&db, input) else {
+        panic!("expected a cycle");
+    };
+    db.assert_logs(expect![[r#"
+        [
+            "checked_b(1)",
+        ]"#]]);
+
+    input.set_field(&mut db).to(0);
+    assert_eq!(checked_b(&db, input).unwrap(), 1);
+}
+
+#[test]
+fn cycle_does_not_unwind_through_try_call() {
+    let db = LoggerDatabase::default();
+    let input = MyInput::new(&db, 1);
+
+    // `guarded_b` detects the cycle and continues with the value of the error branch.
+    assert_eq!(guarded_a::try_call(&db, input).unwrap(), 11);
+    assert_eq!(guarded_b(&db, input), 11);
+}
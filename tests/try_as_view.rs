@@ -0,0 +1,55 @@
+//! Test `try_as_view` and `view_type_names`.
+
+use salsa::{Database, DatabaseImpl};
+
+#[salsa::db]
+trait Db: Database {}
+
+#[salsa::db]
+impl Db for DatabaseImpl {}
+
+#[salsa::db]
+trait OtherDb: Database {}
+
+#[salsa::input]
+struct MyInput {
+    field: u32,
+}
+
+#[salsa::tracked]
+fn double(db: &dyn Db, input: MyInput) -> u32 {
+    input.field(db) * 2
+}
+
+#[test]
+fn try_as_view() {
+    let db = DatabaseImpl::new();
+    let input = MyInput::new(&db, 1);
+    let dyn_db: &dyn Database = &db;
+
+    // The view is added when the tracked function is first called.
+    assert!(dyn_db.try_as_view::<dyn Db>().is_none());
+    assert_eq!(double(&db, input), 2);
+    let view = dyn_db.try_as_view::<dyn Db>().unwrap();
+    assert_eq!(double(view, input), 2);
+
+    assert!(dyn_db.try_as_view::<dyn OtherDb>().is_none());
+}
+
+#[test]
+fn view_type_names() {
+    let db = DatabaseImpl::new();
+    let input = MyInput::new(&db, 1);
+    double(&db, input);
+
+    let dyn_db: &dyn Database = &db;
+    assert_eq!(dyn_db.view_type_names(), ["dyn try_as_view::Db"]);
+}
+
+#[test]
+#[should_panic(expected = "was not added to the database")]
+fn as_view_panics() {
+    let db = DatabaseImpl::new();
+    let dyn_db: &dyn Database = &db;
+    dyn_db.as_view::<dyn OtherDb>();
+}
@@ -0,0 +1,141 @@
+//! Test the `Update` implementations for std and third-party types,
+//! and `#[derive(Update)]` on generic types.
+
+use std::sync::Arc;
+
+use salsa::Update;
+
+/// Updates `old` in place and returns whether it changed.
+fn update<T: Update>(old: &mut T, new: T) -> bool {
+    unsafe { T::maybe_update(old, new) }
+}
+
+#[derive(Debug, PartialEq, Eq, Update)]
+struct Wrapper<T> {
+    items: Vec<T>,
+    label: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Update)]
+enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+#[test]
+fn arc() {
+    let mut old = Arc::new(vec![1u32, 2]);
+    assert!(!update(&mut old, Arc::new(vec![1, 2])));
+    assert!(update(&mut old, Arc::new(vec![1, 3])));
+    assert_eq!(*old, [1, 3]);
+
+    // A shared `Arc` cannot be updated in place, so it is replaced if the contents differ.
+    let shared = old.clone();
+    assert!(!update(&mut old, Arc::new(vec![1, 3])));
+    assert!(Arc::ptr_eq(&old, &shared));
+    assert!(update(&mut old, Arc::new(vec![1, 4])));
+    assert!(!Arc::ptr_eq(&old, &shared));
+    assert_eq!(*old, [1, 4]);
+
+    let same = old.clone();
+    assert!(!update(&mut old, same));
+}
+
+#[test]
+fn result() {
+    let mut old: Result<u32, String> = Ok(1);
+    assert!(!update(&mut old, Ok(1)));
+    assert!(update(&mut old, Err("oops".to_string())));
+    assert!(!update(&mut old, Err("oops".to_string())));
+    assert!(update(&mut old, Ok(2)));
+    assert_eq!(old, Ok(2));
+}
+
+#[test]
+fn str_slices() {
+    let mut boxed: Box<str> = "a".into();
+    assert!(!update(&mut boxed, "a".into()));
+    assert!(update(&mut boxed, "b".into()));
+
+    let mut arced: Arc<str> = "a".into();
+    assert!(!update(&mut arced, "a".into()));
+    assert!(update(&mut arced, "b".into()));
+    assert_eq!(&*arced, "b");
+}
+
+#[test]
+fn derive_generic() {
+    let mut old = Wrapper {
+        items: vec![Arc::<str>::from("a")],
+        label: "x".to_string(),
+    };
+    assert!(!update(
+        &mut old,
+        Wrapper {
+            items: vec!["a".into()],
+            label: "x".to_string(),
+        }
+    ));
+    assert!(update(
+        &mut old,
+        Wrapper {
+            items: vec!["a".into(), "b".into()],
+            label: "x".to_string(),
+        }
+    ));
+
+    let mut either: Either<u32, Result<u8, u16>> = Either::Left(1);
+    assert!(!update(&mut either, Either::Left(1)));
+    assert!(update(&mut either, Either::Right(Ok(2))));
+    assert!(update(&mut either, Either::Right(Err(2))));
+    assert_eq!(either, Either::Right(Err(2)));
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn indexmap() {
+    use indexmap::{IndexMap, IndexSet};
+
+    let mut map: IndexMap<u32, String> = [(1, "a".to_string()), (2, "b".to_string())].into();
+    assert!(!update(
+        &mut map,
+        [(1, "a".to_string()), (2, "b".to_string())].into()
+    ));
+    assert!(update(
+        &mut map,
+        [(1, "a".to_string()), (2, "c".to_string())].into()
+    ));
+    // Same entries in a different order.
+    assert!(update(
+        &mut map,
+        [(2, "c".to_string()), (1, "a".to_string())].into()
+    ));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), [2, 1]);
+
+    let mut set: IndexSet<u32> = [1, 2].into();
+    assert!(!update(&mut set, [1, 2].into()));
+    assert!(update(&mut set, [2, 1].into()));
+    assert_eq!(set.iter().copied().collect::<Vec<_>>(), [2, 1]);
+}
+
+#[cfg(feature = "smallvec")]
+#[test]
+fn smallvec() {
+    use smallvec::{smallvec, SmallVec};
+
+    let mut old: SmallVec<[u32; 2]> = smallvec![1, 2];
+    assert!(!update(&mut old, smallvec![1, 2]));
+    assert!(update(&mut old, smallvec![1, 2, 3]));
+    assert_eq!(&old[..], [1, 2, 3]);
+}
+
+#[cfg(feature = "thin-vec")]
+#[test]
+fn thin_vec() {
+    use thin_vec::{thin_vec, ThinVec};
+
+    let mut old: ThinVec<u32> = thin_vec![1, 2];
+    assert!(!update(&mut old, thin_vec![1, 2]));
+    assert!(update(&mut old, thin_vec![1, 3]));
+    assert_eq!(&old[..], [1, 3]);
+}
@@ -0,0 +1,92 @@
+//! Test that subscribers of tracked functions are only notified
+//! when the result of the function changed.
+
+mod common;
+use common::{LogDatabase, LoggerDatabase};
+
+use expect_test::expect;
+use salsa::{Database, Setter};
+use std::sync::{Arc, Mutex};
+use test_log::test;
+
+#[salsa::input]
+struct MyInput {
+    text: String,
+}
+
+#[salsa::tracked]
+fn word_count(db: &dyn LogDatabase, input: MyInput) -> usize {
+    db.push_log(format!("word_count({:?})", input.text(db)));
+    input.text(db).split_whitespace().count()
+}
+
+#[salsa::tracked(return_ref)]
+fn words(db: &dyn LogDatabase, input: MyInput) -> Vec<String> {
+    input
+        .text(db)
+        .split_whitespace()
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn notify_on_change() {
+    let mut db = LoggerDatabase::default();
+    let input = MyInput::new(&db, "a b".to_string());
+    let other = MyInput::new(&db, "c".to_string());
+
+    let seen = Arc::new(Mutex::new(vec![]));
+    word_count::watch(&db, input, {
+        let seen = seen.clone();
+        move |_db, count| seen.lock().unwrap().push(*count)
+    });
+
+    // Nothing changed since the subscription.
+    db.notify_watchers();
+    db.assert_logs(expect![[r#"
+        []"#]]);
+
+    input.set_text(&mut db).to("a b c".to_string());
+    db.notify_watchers();
+    db.assert_logs(expect![[r#"
+        [
+            "word_count(\"a b c\")",
+        ]"#]]);
+
+    // The function is re-executed, but its result did not change.
+    input.set_text(&mut db).to("d e f".to_string());
+    db.notify_watchers();
+    db.assert_logs(expect![[r#"
+        [
+            "word_count(\"d e f\")",
+        ]"#]]);
+
+    // The function is not re-executed at all.
+    other.set_text(&mut db).to("g".to_string());
+    db.notify_watchers();
+    db.assert_logs(expect![[r#"
+        []"#]]);
+
+    assert_eq!(*seen.lock().unwrap(), [3]);
+}
+
+#[test]
+fn unwatch() {
+    let mut db = LoggerDatabase::default();
+    let input = MyInput::new(&db, "a".to_string());
+
+    let seen = Arc::new(Mutex::new(vec![]));
+    let id = words::watch(&db, input, {
+        let seen = seen.clone();
+        move |_db, words| seen.lock().unwrap().push(words.join(" "))
+    });
+
+    input.set_text(&mut db).to("a b".to_string());
+    db.notify_watchers();
+    assert!(db.unwatch(id));
+    assert!(!db.unwatch(id));
+
+    input.set_text(&mut db).to("a b c".to_string());
+    db.notify_watchers();
+    assert_eq!(*seen.lock().unwrap(), ["a b"]);
+}